@@ -0,0 +1,392 @@
+//! `#[derive(ToLeb128, FromLeb128)]` for `leb128-rs`.
+//!
+//! The derives implement `ToLeb128u` and `FromLeb128u`, encoding fields in
+//! declaration order. Fields of the primitive signed types (`i8` to `isize`
+//! and their `NonZero` forms) use SLEB128 and all others ULEB128; mark a field
+//! `#[leb128(signed)]` or `#[leb128(unsigned)]` to choose explicitly. Enums
+//! are prefixed with the variant's index as a ULEB128 `u32`.
+//!
+//! Generic parameters are not given bounds; declare them on the type.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Member, Type};
+
+#[proc_macro_derive(ToLeb128, attributes(leb128))]
+pub fn derive_to_leb128(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input, to_leb128)
+}
+
+#[proc_macro_derive(FromLeb128, attributes(leb128))]
+pub fn derive_from_leb128(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input, from_leb128)
+}
+
+fn expand(input: &DeriveInput, f: fn(&DeriveInput, &[Shape]) -> TokenStream2) -> TokenStream {
+    match shapes(input) {
+        Ok(shapes) => f(input, &shapes).into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Unsigned,
+    Signed,
+}
+
+struct Field {
+    member: Member,
+    ty: Type,
+    encoding: Encoding,
+}
+
+/// A struct body, or one variant of an enum with its index.
+struct Shape {
+    path: TokenStream2,
+    index: Option<u32>,
+    fields: Vec<Field>,
+}
+
+impl Shape {
+    /// A pattern binding every field to `__f0`, `__f1`, ...
+    fn pattern(&self) -> TokenStream2 {
+        let path = &self.path;
+        let members = self.fields.iter().map(|f| &f.member);
+        let bindings = self.bindings();
+        quote!(#path { #(#members: #bindings),* })
+    }
+
+    fn bindings(&self) -> Vec<syn::Ident> {
+        (0..self.fields.len())
+            .map(|i| format_ident!("__f{}", i))
+            .collect()
+    }
+
+    /// Builds the value from field expressions given by `decode`.
+    fn construct(&self, decode: impl Fn(&Field) -> TokenStream2) -> TokenStream2 {
+        let path = &self.path;
+        let members = self.fields.iter().map(|f| &f.member);
+        let values = self.fields.iter().map(decode);
+        quote!(#path { #(#members: #values),* })
+    }
+}
+
+fn shapes(input: &DeriveInput) -> syn::Result<Vec<Shape>> {
+    match &input.data {
+        Data::Struct(data) => Ok(vec![Shape {
+            path: quote!(Self),
+            index: None,
+            fields: fields(&data.fields)?,
+        }]),
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .enumerate()
+            .map(|(i, variant)| {
+                let ident = &variant.ident;
+                Ok(Shape {
+                    path: quote!(Self::#ident),
+                    index: Some(
+                        u32::try_from(i)
+                            .map_err(|_| syn::Error::new_spanned(variant, "too many variants"))?,
+                    ),
+                    fields: fields(&variant.fields)?,
+                })
+            })
+            .collect(),
+        Data::Union(data) => Err(syn::Error::new_spanned(
+            data.union_token,
+            "unions cannot derive LEB128 encodings",
+        )),
+    }
+}
+
+fn fields(fields: &Fields) -> syn::Result<Vec<Field>> {
+    fields
+        .members()
+        .zip(fields.iter())
+        .map(|(member, field)| {
+            let mut encoding = default_encoding(&field.ty);
+
+            for attr in &field.attrs {
+                if !attr.path().is_ident("leb128") {
+                    continue;
+                }
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("signed") {
+                        encoding = Encoding::Signed;
+                        Ok(())
+                    } else if meta.path.is_ident("unsigned") {
+                        encoding = Encoding::Unsigned;
+                        Ok(())
+                    } else {
+                        Err(meta.error("expected `signed` or `unsigned`"))
+                    }
+                })?;
+            }
+
+            Ok(Field {
+                member,
+                ty: field.ty.clone(),
+                encoding,
+            })
+        })
+        .collect()
+}
+
+fn default_encoding(ty: &Type) -> Encoding {
+    const SIGNED: &[&str] = &[
+        "i8",
+        "i16",
+        "i32",
+        "i64",
+        "i128",
+        "isize",
+        "NonZeroI8",
+        "NonZeroI16",
+        "NonZeroI32",
+        "NonZeroI64",
+        "NonZeroI128",
+        "NonZeroIsize",
+    ];
+
+    match ty {
+        Type::Path(path) if path.qself.is_none() => match path.path.segments.last() {
+            Some(segment) if SIGNED.iter().any(|s| segment.ident == s) => Encoding::Signed,
+            _ => Encoding::Unsigned,
+        },
+        _ => Encoding::Unsigned,
+    }
+}
+
+fn to_trait(encoding: Encoding) -> TokenStream2 {
+    match encoding {
+        Encoding::Unsigned => quote!(::leb128_rs::ToLeb128u),
+        Encoding::Signed => quote!(::leb128_rs::ToLeb128i),
+    }
+}
+
+fn from_trait(encoding: Encoding) -> TokenStream2 {
+    match encoding {
+        Encoding::Unsigned => quote!(::leb128_rs::FromLeb128u),
+        Encoding::Signed => quote!(::leb128_rs::FromLeb128i),
+    }
+}
+
+/// Names a method by its unsigned form, e.g. `"to_leb128{}"` gives
+/// `to_leb128u` or `to_leb128i`.
+fn method(template: &str, encoding: Encoding) -> syn::Ident {
+    let suffix = match encoding {
+        Encoding::Unsigned => "u",
+        Encoding::Signed => "i",
+    };
+    format_ident!("{}", template.replace("{}", suffix))
+}
+
+fn to_leb128(input: &DeriveInput, shapes: &[Shape]) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let is_enum = matches!(input.data, Data::Enum(_));
+
+    let field_max = |shape: &Shape| {
+        let sizes = shape.fields.iter().map(|f| {
+            let ty = &f.ty;
+            let trait_ = to_trait(f.encoding);
+            quote!(<#ty as #trait_>::MAX_LEB128_LEN)
+        });
+        quote!(0 #(+ #sizes)*)
+    };
+    // `usize::max` is not const, so variants are folded by a private helper.
+    let max = if is_enum {
+        let variants = shapes.iter().map(field_max);
+        quote! {
+            <u32 as ::leb128_rs::ToLeb128u>::MAX_LEB128_LEN
+                + ::leb128_rs::__private::max(&[#(#variants),*])
+        }
+    } else {
+        field_max(&shapes[0])
+    };
+
+    let arms = |per_field: &dyn Fn(&Field, &syn::Ident) -> TokenStream2,
+                index: &dyn Fn(u32) -> TokenStream2| {
+        let arms = shapes.iter().map(|shape| {
+            let pattern = shape.pattern();
+            let index = shape.index.map(index);
+            let fields = shape
+                .fields
+                .iter()
+                .zip(shape.bindings())
+                .map(|(f, b)| per_field(f, &b));
+            quote!(#pattern => { #index #(#fields)* })
+        });
+        quote!(match self { #(#arms)* })
+    };
+
+    let len = arms(
+        &|f, b| {
+            let trait_ = to_trait(f.encoding);
+            let len_fn = method("leb128{}_len", f.encoding);
+            quote!(count += #trait_::#len_fn(#b);)
+        },
+        &|i| quote!(count += ::leb128_rs::ToLeb128u::leb128u_len(&#i);),
+    );
+    let write = arms(
+        &|f, b| {
+            let trait_ = to_trait(f.encoding);
+            let to_fn = method("to_leb128{}", f.encoding);
+            quote!(count += #trait_::#to_fn(#b, writer)?;)
+        },
+        &|i| quote!(count += ::leb128_rs::ToLeb128u::to_leb128u(&#i, writer)?;),
+    );
+    // Fields encode into initialized bytes, so a field that misreports its
+    // length cannot expose uninitialized memory.
+    let rest = quote! {
+        buf.get_mut(count..).ok_or(::leb128_rs::EncodeError::BufferTooSmall)?
+    };
+    let to_buf = arms(
+        &|f, b| {
+            let trait_ = to_trait(f.encoding);
+            let buf_fn = method("to_leb128{}_buf", f.encoding);
+            quote!(count += #trait_::#buf_fn(#b, #rest)?;)
+        },
+        &|i| quote!(count += ::leb128_rs::ToLeb128u::to_leb128u_buf(&#i, #rest)?;),
+    );
+
+    quote! {
+        impl #impl_generics ::leb128_rs::ToLeb128u for #name #ty_generics #where_clause {
+            const MAX_LEB128_LEN: usize = #max;
+
+            #[allow(unused_mut)]
+            fn leb128u_len(&self) -> usize {
+                let mut count = 0;
+                #len
+                count
+            }
+
+            ::leb128_rs::__if_std! {
+                #[allow(unused_mut)]
+                fn to_leb128u(
+                    &self,
+                    writer: &mut (impl ::std::io::Write + ?Sized),
+                ) -> ::std::io::Result<usize> {
+                    let mut count = 0;
+                    #write
+                    ::core::result::Result::Ok(count)
+                }
+            }
+
+            fn to_leb128u_uninit<'__buf>(
+                &self,
+                buf: &'__buf mut [::core::mem::MaybeUninit<u8>],
+            ) -> ::core::result::Result<&'__buf [u8], ::leb128_rs::EncodeError> {
+                let buf = ::leb128_rs::__private::zeroed(buf, <Self as ::leb128_rs::ToLeb128u>::MAX_LEB128_LEN);
+                let count = ::leb128_rs::ToLeb128u::to_leb128u_buf(self, buf)?;
+                buf.get(..count).ok_or(::leb128_rs::EncodeError::BufferTooSmall)
+            }
+
+            #[allow(unused_mut)]
+            fn to_leb128u_buf(
+                &self,
+                buf: &mut [u8],
+            ) -> ::core::result::Result<usize, ::leb128_rs::EncodeError> {
+                let mut count = 0;
+                #to_buf
+                ::core::result::Result::Ok(count)
+            }
+        }
+    }
+}
+
+fn from_leb128(input: &DeriveInput, shapes: &[Shape]) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let is_enum = matches!(input.data, Data::Enum(_));
+
+    // Decodes from `reader` with the method named by `template`.
+    let read = |template: &str| {
+        let field = |f: &Field| {
+            let ty = &f.ty;
+            let trait_ = from_trait(f.encoding);
+            let from_fn = method(template, f.encoding);
+            quote!(<#ty as #trait_>::#from_fn(reader)?)
+        };
+
+        if is_enum {
+            let index_fn = method(template, Encoding::Unsigned);
+            let arms = shapes.iter().map(|shape| {
+                let index = shape.index;
+                let value = shape.construct(field);
+                quote!(#index => ::core::result::Result::Ok(#value),)
+            });
+            quote! {
+                match <u32 as ::leb128_rs::FromLeb128u>::#index_fn(reader)? {
+                    #(#arms)*
+                    _ => ::core::result::Result::Err(::leb128_rs::FromLeb128Error::Malformed),
+                }
+            }
+        } else {
+            let value = shapes[0].construct(field);
+            quote!(::core::result::Result::Ok(#value))
+        }
+    };
+
+    let slice_field = |ty: &dyn quote::ToTokens, encoding: Encoding| {
+        let trait_ = from_trait(encoding);
+        let slice_fn = method("from_leb128{}_slice", encoding);
+        quote!({
+            let (value, len) = <#ty as #trait_>::#slice_fn(&bytes[count..])?;
+            count += len;
+            value
+        })
+    };
+    let slice = if is_enum {
+        let index = slice_field(&quote!(u32), Encoding::Unsigned);
+        let arms = shapes.iter().map(|shape| {
+            let index = shape.index;
+            let value = shape.construct(|f| slice_field(&f.ty, f.encoding));
+            quote!(#index => #value,)
+        });
+        quote! {
+            match #index {
+                #(#arms)*
+                _ => return ::core::result::Result::Err(::leb128_rs::FromLeb128Error::Malformed),
+            }
+        }
+    } else {
+        shapes[0].construct(|f| slice_field(&f.ty, f.encoding))
+    };
+
+    let from = read("from_leb128{}");
+    let canonical = read("from_leb128{}_canonical");
+
+    quote! {
+        impl #impl_generics ::leb128_rs::FromLeb128u for #name #ty_generics #where_clause {
+            ::leb128_rs::__if_std! {
+                fn from_leb128u(
+                    reader: &mut (impl ::std::io::Read + ?Sized),
+                ) -> ::core::result::Result<Self, ::leb128_rs::FromLeb128Error> {
+                    #from
+                }
+
+                fn from_leb128u_canonical(
+                    reader: &mut (impl ::std::io::Read + ?Sized),
+                ) -> ::core::result::Result<Self, ::leb128_rs::FromLeb128Error> {
+                    #canonical
+                }
+            }
+
+            #[allow(unused_mut)]
+            fn from_leb128u_slice(
+                bytes: &[u8],
+            ) -> ::core::result::Result<(Self, usize), ::leb128_rs::FromLeb128Error> {
+                let mut count = 0;
+                let value = #slice;
+                ::core::result::Result::Ok((value, count))
+            }
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::mem::MaybeUninit;
+
+use leb128_rs::{EncodeError, FromLeb128, FromLeb128Error, FromLeb128u, ToLeb128, ToLeb128u};
+
+#[derive(Debug, PartialEq, ToLeb128, FromLeb128)]
+struct Header {
+    version: u32,
+    offset: i64,
+    #[leb128(signed)]
+    delta: (i8, i16),
+}
+
+#[derive(Debug, PartialEq, ToLeb128, FromLeb128)]
+struct Pair(u8, i8);
+
+#[derive(Debug, PartialEq, ToLeb128, FromLeb128)]
+enum Message {
+    Ping,
+    Data(Pair),
+    Move { dx: i32, dy: i32 },
+}
+
+// Returns bytes it never wrote to `buf`.
+struct Canned;
+
+impl ToLeb128u for Canned {
+    const MAX_LEB128_LEN: usize = 2;
+
+    fn to_leb128u_uninit<'a>(&self, _: &'a mut [MaybeUninit<u8>]) -> Result<&'a [u8], EncodeError> {
+        Ok(&[0x80, 0x01])
+    }
+}
+
+#[derive(ToLeb128)]
+struct Wrapped(u8, Canned);
+
+#[test]
+fn struct_round_trip() {
+    let header = Header {
+        version: 300,
+        offset: -65,
+        delta: (-1, 64),
+    };
+    let mut buf = Vec::new();
+
+    assert_eq!(header.to_leb128u(&mut buf).unwrap(), 7);
+    assert_eq!(buf, [0xac, 0x02, 0xbf, 0x7f, 0x7f, 0xc0, 0x00]);
+    assert_eq!(header.leb128u_len(), 7);
+    assert_eq!(Header::MAX_LEB128_LEN, 5 + 10 + 2 + 3);
+    assert_eq!(Header::from_leb128u(&mut &buf[..]).unwrap(), header);
+    assert_eq!(Header::from_leb128u_slice(&buf).unwrap(), (header, 7));
+    assert_eq!(Pair(200, -1).to_leb128u_vec(), [0xc8, 0x01, 0x7f]);
+}
+
+#[test]
+fn enum_round_trip() {
+    let messages = [
+        Message::Ping,
+        Message::Data(Pair(1, -2)),
+        Message::Move { dx: -1, dy: 300 },
+    ];
+    let mut buf = Vec::new();
+    for message in &messages {
+        message.to_leb128u(&mut buf).unwrap();
+    }
+
+    assert_eq!(&buf[..4], &[0x00, 0x01, 0x01, 0x7e]);
+    assert_eq!(Message::MAX_LEB128_LEN, 5 + 10);
+
+    let mut reader = &buf[..];
+    for message in messages {
+        assert_eq!(Message::from_leb128u(&mut reader).unwrap(), message);
+    }
+    assert!(matches!(
+        Message::from_leb128u_exact(&[0x03]),
+        Err(FromLeb128Error::Malformed)
+    ));
+    assert!(matches!(
+        Message::from_leb128u_canonical(&mut &[0x02, 0xff, 0x7f, 0x80, 0x00][..]),
+        Err(FromLeb128Error::NonCanonical)
+    ));
+}
+
+#[test]
+fn uninit_uses_field_bytes() {
+    let mut buf = [MaybeUninit::uninit(); 3];
+    assert_eq!(
+        Wrapped(1, Canned).to_leb128u_uninit(&mut buf).unwrap(),
+        &[0x01, 0x80, 0x01]
+    );
+    assert!(Wrapped(1, Canned).to_leb128u_uninit(&mut buf[..2]).is_err());
+}
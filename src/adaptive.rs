@@ -0,0 +1,273 @@
+//! Block encoding that picks the smallest of several schemes per block.
+//!
+//! Each block is a 1-byte header naming the scheme, the value count as
+//! ULEB128, then the payload:
+//!
+//! - `Raw(width)`: every value as `width` little-endian bytes. A width of 0
+//!   holds only zeros and at most [`MAX_ZERO_WIDTH_COUNT`] of them.
+//! - `Plain`: every value as ULEB128.
+//! - `Delta`: the first value as ULEB128, then the zigzag ULEB128 of each
+//!   wrapping difference from the previous value.
+
+use std::io::{self, Read, Write};
+
+use crate::format::encoded_len_of_slice;
+use crate::{zigzag, FromLeb128Error, FromLeb128u, ToLeb128u};
+
+const HEADER_PLAIN: u8 = 0x00;
+const HEADER_DELTA: u8 = 0x01;
+const HEADER_RAW: u8 = 0x10;
+
+/// Largest count of a `Raw(0)` block, whose values take no input bytes.
+pub const MAX_ZERO_WIDTH_COUNT: u64 = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    /// Fixed-width little-endian values of 0 to 8 bytes.
+    Raw(u8),
+    Plain,
+    Delta,
+}
+
+impl Scheme {
+    fn header(self) -> u8 {
+        match self {
+            Scheme::Raw(width) => HEADER_RAW | width,
+            Scheme::Plain => HEADER_PLAIN,
+            Scheme::Delta => HEADER_DELTA,
+        }
+    }
+
+    fn from_header(header: u8) -> Option<Self> {
+        match header {
+            HEADER_PLAIN => Some(Scheme::Plain),
+            HEADER_DELTA => Some(Scheme::Delta),
+            h if h & 0xf0 == HEADER_RAW && h & 0x0f <= 8 => Some(Scheme::Raw(h & 0x0f)),
+            _ => None,
+        }
+    }
+
+    /// Payload size of `values` under this scheme.
+    pub fn payload_len(self, values: &[u64]) -> usize {
+        match self {
+            Scheme::Raw(width) => values.len() * width as usize,
+            Scheme::Plain => encoded_len_of_slice(values),
+            Scheme::Delta => {
+                deltas(values)
+                    .map(|d| zigzag::encode_i64(d).leb128u_len())
+                    .sum::<usize>()
+                    + values.first().map_or(0, |v| v.leb128u_len())
+            }
+        }
+    }
+}
+
+fn deltas(values: &[u64]) -> impl Iterator<Item = i64> + '_ {
+    values.windows(2).map(|w| w[1].wrapping_sub(w[0]) as i64)
+}
+
+/// The scheme giving the smallest payload for `values`; ties prefer `Raw`,
+/// then `Plain`.
+pub fn choose(values: &[u64]) -> Scheme {
+    let max = values.iter().copied().max().unwrap_or(0);
+    let mut width = (64 - max.leading_zeros() as u8).div_ceil(8);
+    if values.len() as u64 > MAX_ZERO_WIDTH_COUNT {
+        width = width.max(1);
+    }
+
+    [Scheme::Raw(width), Scheme::Plain, Scheme::Delta]
+        .into_iter()
+        .min_by_key(|s| s.payload_len(values))
+        .unwrap()
+}
+
+/// Encodes `values` as one block with the scheme picked by [`choose`].
+pub fn encode_block(values: &[u64], writer: &mut impl Write) -> io::Result<usize> {
+    encode_block_with(choose(values), values, writer)
+}
+
+/// Encodes `values` as one block with the given scheme.
+///
+/// Fails with `io::ErrorKind::InvalidInput` if a value does not fit a `Raw`
+/// width, or a `Raw(0)` block would exceed [`MAX_ZERO_WIDTH_COUNT`].
+pub fn encode_block_with(
+    scheme: Scheme,
+    values: &[u64],
+    writer: &mut impl Write,
+) -> io::Result<usize> {
+    let mut buf = Vec::with_capacity(11 + scheme.payload_len(values));
+    buf.push(scheme.header());
+    (values.len() as u64).to_leb128u(&mut buf)?;
+
+    match scheme {
+        Scheme::Raw(width) => {
+            if width > 8 || values.iter().any(|&v| width < 8 && v >> (width * 8) != 0) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "value does not fit the raw width",
+                ));
+            }
+            if width == 0 && values.len() as u64 > MAX_ZERO_WIDTH_COUNT {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "too many values for a zero-width block",
+                ));
+            }
+            for &v in values {
+                buf.extend_from_slice(&v.to_le_bytes()[..width as usize]);
+            }
+        }
+        Scheme::Plain => {
+            for &v in values {
+                v.to_leb128u(&mut buf)?;
+            }
+        }
+        Scheme::Delta => {
+            if let Some(&first) = values.first() {
+                first.to_leb128u(&mut buf)?;
+            }
+            for delta in deltas(values) {
+                zigzag::encode_i64(delta).to_leb128u(&mut buf)?;
+            }
+        }
+    }
+
+    writer.write_all(&buf)?;
+    Ok(buf.len())
+}
+
+/// Encodes `values` in blocks of `block_len`, choosing a scheme per block.
+pub fn encode_blocks(
+    values: &[u64],
+    block_len: usize,
+    writer: &mut impl Write,
+) -> io::Result<usize> {
+    let mut count = 0;
+
+    for block in values.chunks(block_len.max(1)) {
+        count += encode_block(block, writer)?;
+    }
+
+    Ok(count)
+}
+
+/// Decodes one block, appending its values to `out`.
+///
+/// Fails with `FromLeb128Error::Malformed` on an unknown header or a `Raw(0)`
+/// count above [`MAX_ZERO_WIDTH_COUNT`].
+pub fn decode_block(reader: &mut impl Read, out: &mut Vec<u64>) -> Result<(), FromLeb128Error> {
+    let mut header = [0; 1];
+    reader.read_exact(&mut header)?;
+    decode_payload(header[0], reader, out)
+}
+
+/// Decodes blocks until a clean end of input.
+pub fn decode_blocks(reader: &mut impl Read) -> Result<Vec<u64>, FromLeb128Error> {
+    let mut out = Vec::new();
+    let mut header = [0; 1];
+
+    while reader.read(&mut header)? == 1 {
+        decode_payload(header[0], reader, &mut out)?;
+    }
+
+    Ok(out)
+}
+
+fn decode_payload(
+    header: u8,
+    reader: &mut impl Read,
+    out: &mut Vec<u64>,
+) -> Result<(), FromLeb128Error> {
+    let scheme = Scheme::from_header(header).ok_or(FromLeb128Error::Malformed)?;
+    let count = u64::from_leb128u(reader)?;
+    if scheme == Scheme::Raw(0) && count > MAX_ZERO_WIDTH_COUNT {
+        return Err(FromLeb128Error::Malformed);
+    }
+    out.try_reserve(count.min(4096) as usize)?;
+
+    match scheme {
+        Scheme::Raw(width) => {
+            for _ in 0..count {
+                let mut bytes = [0; 8];
+                reader.read_exact(&mut bytes[..width as usize])?;
+                push(out, u64::from_le_bytes(bytes))?;
+            }
+        }
+        Scheme::Plain => {
+            for _ in 0..count {
+                push(out, u64::from_leb128u(reader)?)?;
+            }
+        }
+        Scheme::Delta => {
+            let mut prev = 0u64;
+            for i in 0..count {
+                let value = u64::from_leb128u(reader)?;
+                prev = if i == 0 {
+                    value
+                } else {
+                    prev.wrapping_add(zigzag::decode_i64(value) as u64)
+                };
+                push(out, prev)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn push(out: &mut Vec<u64>, value: u64) -> Result<(), FromLeb128Error> {
+    out.try_reserve(1)?;
+    out.push(value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_smallest() {
+        assert_eq!(choose(&[200, 201, 255, 0]), Scheme::Raw(1));
+        assert_eq!(choose(&[1, 2, 3, 1 << 40]), Scheme::Plain);
+        assert_eq!(
+            choose(&[1 << 50, (1 << 50) + 1, (1 << 50) + 3]),
+            Scheme::Delta
+        );
+        assert_eq!(choose(&[0, 0, 0]), Scheme::Raw(0));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut values: Vec<u64> = (0..100).map(|i| 1_000_000 + i * 3).collect();
+        values.extend([0, u64::MAX, 5, 300]);
+        values.extend((0..50).map(|i| i * 5));
+
+        let mut buf = Vec::new();
+        encode_blocks(&values, 32, &mut buf).unwrap();
+        assert_eq!(decode_blocks(&mut &buf[..]).unwrap(), values);
+
+        let mut buf = Vec::new();
+        encode_block_with(Scheme::Raw(2), &[1, 0xffff], &mut buf).unwrap();
+        assert_eq!(buf, vec![0x12, 2, 1, 0, 0xff, 0xff]);
+        assert!(encode_block_with(Scheme::Raw(1), &[256], &mut Vec::new()).is_err());
+        assert!(matches!(
+            decode_blocks(&mut &[0x19, 0][..]),
+            Err(FromLeb128Error::Malformed)
+        ));
+    }
+
+    #[test]
+    fn zero_width_limit() {
+        let mut buf = vec![0x10];
+        u64::MAX.to_leb128u(&mut buf).unwrap();
+        assert!(matches!(
+            decode_blocks(&mut &buf[..]),
+            Err(FromLeb128Error::Malformed)
+        ));
+
+        let zeros = vec![0; MAX_ZERO_WIDTH_COUNT as usize + 1];
+        assert_eq!(choose(&zeros), Scheme::Raw(1));
+        assert!(encode_block_with(Scheme::Raw(0), &zeros, &mut Vec::new()).is_err());
+        assert_eq!(choose(&zeros[1..]), Scheme::Raw(0));
+    }
+}
@@ -0,0 +1,137 @@
+use std::io::{self, Read, Write};
+
+use arrow_array::builder::PrimitiveBuilder;
+use arrow_array::types::ArrowPrimitiveType;
+use arrow_array::{Array, PrimitiveArray};
+
+use crate::{FromLeb128Error, FromLeb128i, FromLeb128u, ToLeb128i, ToLeb128u};
+
+/// Encodes an array of unsigned integers.
+///
+/// The layout is the length, the null count, the validity bitmap (only when
+/// there are nulls) and then the non-null values as ULEB128.
+pub fn encode_unsigned<T>(array: &PrimitiveArray<T>, writer: &mut impl Write) -> io::Result<usize>
+where
+    T: ArrowPrimitiveType,
+    T::Native: ToLeb128u,
+{
+    encode(array, writer, |v, w| v.to_leb128u(w))
+}
+
+pub fn decode_unsigned<T>(reader: &mut impl Read) -> Result<PrimitiveArray<T>, FromLeb128Error>
+where
+    T: ArrowPrimitiveType,
+    T::Native: FromLeb128u,
+{
+    decode(reader, |r| T::Native::from_leb128u(r))
+}
+
+/// Encodes an array of signed integers; the layout matches [`encode_unsigned`]
+/// with SLEB128 values.
+pub fn encode_signed<T>(array: &PrimitiveArray<T>, writer: &mut impl Write) -> io::Result<usize>
+where
+    T: ArrowPrimitiveType,
+    T::Native: ToLeb128i,
+{
+    encode(array, writer, |v, w| v.to_leb128i(w))
+}
+
+pub fn decode_signed<T>(reader: &mut impl Read) -> Result<PrimitiveArray<T>, FromLeb128Error>
+where
+    T: ArrowPrimitiveType,
+    T::Native: FromLeb128i,
+{
+    decode(reader, |r| T::Native::from_leb128i(r))
+}
+
+fn encode<T: ArrowPrimitiveType>(
+    array: &PrimitiveArray<T>,
+    writer: &mut impl Write,
+    mut encode_value: impl FnMut(&T::Native, &mut Vec<u8>) -> io::Result<usize>,
+) -> io::Result<usize> {
+    let mut buf = Vec::with_capacity(array.len() + 20);
+    (array.len() as u64).to_leb128u(&mut buf)?;
+    (array.null_count() as u64).to_leb128u(&mut buf)?;
+
+    if let Some(nulls) = array.nulls().filter(|n| n.null_count() > 0) {
+        let mut bitmap = vec![0u8; array.len().div_ceil(8)];
+        for i in (0..array.len()).filter(|&i| nulls.is_valid(i)) {
+            bitmap[i / 8] |= 1 << (i % 8);
+        }
+        buf.extend_from_slice(&bitmap);
+    }
+
+    for (i, value) in array.values().iter().enumerate() {
+        if array.is_valid(i) {
+            encode_value(value, &mut buf)?;
+        }
+    }
+
+    writer.write_all(&buf)?;
+    Ok(buf.len())
+}
+
+fn decode<T: ArrowPrimitiveType, R: Read>(
+    reader: &mut R,
+    mut decode_value: impl FnMut(&mut R) -> Result<T::Native, FromLeb128Error>,
+) -> Result<PrimitiveArray<T>, FromLeb128Error> {
+    let len = usize::try_from(u64::from_leb128u(reader)?).map_err(|_| FromLeb128Error::Overflow)?;
+    let null_count = u64::from_leb128u(reader)?;
+
+    if null_count > len as u64 {
+        return Err(FromLeb128Error::Malformed);
+    }
+
+    let mut bitmap = Vec::new();
+    if null_count > 0 {
+        reader
+            .take(len.div_ceil(8) as u64)
+            .read_to_end(&mut bitmap)?;
+        if bitmap.len() < len.div_ceil(8) {
+            return Err(FromLeb128Error::UnexpectedEof);
+        }
+    }
+
+    let mut builder = PrimitiveBuilder::<T>::with_capacity(len.min(4096));
+    let mut nulls = 0;
+    for i in 0..len {
+        if bitmap.is_empty() || bitmap[i / 8] & (1 << (i % 8)) != 0 {
+            builder.append_value(decode_value(reader)?);
+        } else {
+            builder.append_null();
+            nulls += 1;
+        }
+    }
+
+    if nulls != null_count {
+        return Err(FromLeb128Error::Malformed);
+    }
+
+    Ok(builder.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::types::{Int64Type, UInt32Type};
+
+    #[test]
+    fn round_trip_with_nulls() {
+        let array = PrimitiveArray::<UInt32Type>::from(vec![Some(1), None, Some(300), None]);
+        let mut buf = Vec::new();
+        encode_unsigned(&array, &mut buf).unwrap();
+
+        assert_eq!(buf, vec![4, 2, 0b0101, 1, 0xac, 0x02]);
+        assert_eq!(decode_unsigned::<UInt32Type>(&mut &buf[..]).unwrap(), array);
+    }
+
+    #[test]
+    fn round_trip_signed() {
+        let array = PrimitiveArray::<Int64Type>::from(vec![-1, 0, i64::MIN]);
+        let mut buf = Vec::new();
+        encode_signed(&array, &mut buf).unwrap();
+
+        assert_eq!(&buf[..3], &[3, 0, 0x7f]);
+        assert_eq!(decode_signed::<Int64Type>(&mut &buf[..]).unwrap(), array);
+    }
+}
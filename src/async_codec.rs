@@ -0,0 +1,47 @@
+use asynchronous_codec::{Decoder, Encoder};
+use bytes::{Bytes, BytesMut};
+
+use crate::{buf, FromLeb128Error};
+
+/// Length-delimited framing for `asynchronous-codec`: each frame is its
+/// length as ULEB128 followed by the payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Leb128FrameCodec {
+    max_frame_len: usize,
+}
+
+impl Leb128FrameCodec {
+    /// Frames longer than `max_frame_len` are rejected in both directions.
+    pub fn new(max_frame_len: usize) -> Self {
+        Self { max_frame_len }
+    }
+
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+}
+
+impl Default for Leb128FrameCodec {
+    /// A codec with an 8 MiB frame limit.
+    fn default() -> Self {
+        Self::new(buf::DEFAULT_MAX_FRAME_LEN)
+    }
+}
+
+impl Encoder for Leb128FrameCodec {
+    type Item<'a> = &'a [u8];
+    type Error = FromLeb128Error;
+
+    fn encode(&mut self, item: &[u8], dst: &mut BytesMut) -> Result<(), FromLeb128Error> {
+        buf::put_frame(dst, item, self.max_frame_len)
+    }
+}
+
+impl Decoder for Leb128FrameCodec {
+    type Item = Bytes;
+    type Error = FromLeb128Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Bytes>, FromLeb128Error> {
+        Ok(buf::split_frame(src, self.max_frame_len)?.map(BytesMut::freeze))
+    }
+}
@@ -0,0 +1,283 @@
+//! The async encode and decode functions, shared by the `futures` and `tokio`
+//! modules.
+
+/// Expands to the async API over the `AsyncRead`, `AsyncReadExt`,
+/// `AsyncWrite` and `AsyncWriteExt` traits in scope where it is invoked.
+macro_rules! async_io {
+    () => {
+        use std::io;
+        use std::mem::MaybeUninit;
+
+        use $crate::pending::{Pending, SCRATCH_LEN};
+        use $crate::position::DecodeError;
+        use $crate::zigzag::Leb128Zigzag;
+        use $crate::{stream, FromLeb128Error, FromLeb128i, FromLeb128u, ToLeb128i, ToLeb128u};
+
+        async fn write_uninit(
+            writer: &mut (impl AsyncWrite + Unpin),
+            encode: impl FnOnce(&mut [MaybeUninit<u8>]) -> Result<&[u8], $crate::EncodeError>,
+        ) -> io::Result<usize> {
+            let mut buf = [MaybeUninit::uninit(); SCRATCH_LEN];
+            let bytes = encode(&mut buf)?;
+            writer.write_all(bytes).await?;
+            Ok(bytes.len())
+        }
+
+        /// Reads a value a byte at a time with a slice decoder, returning it
+        /// with the number of bytes read. At most `max_len` bytes are read.
+        async fn read_with<T>(
+            reader: &mut (impl AsyncRead + Unpin),
+            max_len: usize,
+            decode: impl Fn(&[u8]) -> Result<(T, usize), FromLeb128Error>,
+        ) -> Result<(T, usize), DecodeError> {
+            let mut pending = Pending::new();
+
+            for len in 1..=max_len {
+                let read = match pending.slot() {
+                    Ok(slot) => reader.read_exact(slot).await.map_err(FromLeb128Error::from),
+                    Err(e) => Err(e),
+                };
+                let (result, consumed) = match read {
+                    Ok(_) => (pending.advance(&decode), len),
+                    Err(e) => (Some(Err(e)), len - 1),
+                };
+
+                match result {
+                    Some(Ok(value)) => return Ok((value, len)),
+                    Some(Err(error)) => {
+                        return Err(DecodeError {
+                            error,
+                            consumed,
+                            offset: None,
+                        })
+                    }
+                    None => {}
+                }
+            }
+
+            Err(DecodeError {
+                error: FromLeb128Error::TooLong,
+                consumed: max_len,
+                offset: None,
+            })
+        }
+
+        pub async fn to_leb128u_async<T: ToLeb128u>(
+            value: &T,
+            writer: &mut (impl AsyncWrite + Unpin),
+        ) -> io::Result<usize> {
+            write_uninit(writer, |buf| value.to_leb128u_uninit(buf)).await
+        }
+
+        pub async fn to_leb128i_async<T: ToLeb128i>(
+            value: &T,
+            writer: &mut (impl AsyncWrite + Unpin),
+        ) -> io::Result<usize> {
+            write_uninit(writer, |buf| value.to_leb128i_uninit(buf)).await
+        }
+
+        pub async fn to_leb128_zigzag_async<T: Leb128Zigzag>(
+            value: &T,
+            writer: &mut (impl AsyncWrite + Unpin),
+        ) -> io::Result<usize> {
+            write_uninit(writer, |buf| value.to_leb128_zigzag_uninit(buf)).await
+        }
+
+        /// Reads a value a byte at a time, so nothing past it is consumed;
+        /// wrap unbuffered streams in a `BufReader`.
+        pub async fn from_leb128u_async<T: FromLeb128u>(
+            reader: &mut (impl AsyncRead + Unpin),
+        ) -> Result<T, FromLeb128Error> {
+            Ok(read_with(reader, usize::MAX, T::from_leb128u_slice)
+                .await?
+                .0)
+        }
+
+        /// Reads a value a byte at a time, so nothing past it is consumed;
+        /// wrap unbuffered streams in a `BufReader`.
+        pub async fn from_leb128i_async<T: FromLeb128i>(
+            reader: &mut (impl AsyncRead + Unpin),
+        ) -> Result<T, FromLeb128Error> {
+            Ok(read_with(reader, usize::MAX, T::from_leb128i_slice)
+                .await?
+                .0)
+        }
+
+        pub async fn from_leb128_zigzag_async<T: Leb128Zigzag>(
+            reader: &mut (impl AsyncRead + Unpin),
+        ) -> Result<T, FromLeb128Error> {
+            Ok(read_with(reader, usize::MAX, T::from_leb128_zigzag_slice)
+                .await?
+                .0)
+        }
+
+        /// Like `from_leb128u_async`, also returning the number of bytes read.
+        pub async fn from_leb128u_counted_async<T: FromLeb128u>(
+            reader: &mut (impl AsyncRead + Unpin),
+        ) -> Result<(T, usize), FromLeb128Error> {
+            Ok(read_with(reader, usize::MAX, T::from_leb128u_slice).await?)
+        }
+
+        /// Like `from_leb128i_async`, also returning the number of bytes read.
+        pub async fn from_leb128i_counted_async<T: FromLeb128i>(
+            reader: &mut (impl AsyncRead + Unpin),
+        ) -> Result<(T, usize), FromLeb128Error> {
+            Ok(read_with(reader, usize::MAX, T::from_leb128i_slice).await?)
+        }
+
+        /// Like `from_leb128u_async`, recording how far into the value a
+        /// failure happened.
+        pub async fn from_leb128u_positioned_async<T: FromLeb128u>(
+            reader: &mut (impl AsyncRead + Unpin),
+        ) -> Result<T, DecodeError> {
+            Ok(read_with(reader, usize::MAX, T::from_leb128u_slice)
+                .await?
+                .0)
+        }
+
+        /// Like `from_leb128i_async`, recording how far into the value a
+        /// failure happened.
+        pub async fn from_leb128i_positioned_async<T: FromLeb128i>(
+            reader: &mut (impl AsyncRead + Unpin),
+        ) -> Result<T, DecodeError> {
+            Ok(read_with(reader, usize::MAX, T::from_leb128i_slice)
+                .await?
+                .0)
+        }
+
+        /// Like `from_leb128u_async`, but rejects non-minimal encodings with
+        /// `FromLeb128Error::NonCanonical`.
+        pub async fn from_leb128u_canonical_async<T: FromLeb128u>(
+            reader: &mut (impl AsyncRead + Unpin),
+        ) -> Result<T, FromLeb128Error> {
+            let decode = |bytes: &[u8]| {
+                let (value, len) = T::from_leb128u_slice(bytes)?;
+                if stream::padded_u(&bytes[..len]) {
+                    return Err(FromLeb128Error::NonCanonical);
+                }
+                Ok((value, len))
+            };
+            Ok(read_with(reader, usize::MAX, decode).await?.0)
+        }
+
+        /// Like `from_leb128i_async`, but rejects non-minimal encodings with
+        /// `FromLeb128Error::NonCanonical`.
+        pub async fn from_leb128i_canonical_async<T: FromLeb128i>(
+            reader: &mut (impl AsyncRead + Unpin),
+        ) -> Result<T, FromLeb128Error> {
+            let decode = |bytes: &[u8]| {
+                let (value, len) = T::from_leb128i_slice(bytes)?;
+                if stream::padded_i(&bytes[..len]) {
+                    return Err(FromLeb128Error::NonCanonical);
+                }
+                Ok((value, len))
+            };
+            Ok(read_with(reader, usize::MAX, decode).await?.0)
+        }
+
+        /// Like `from_leb128u_async`, but fails with
+        /// `FromLeb128Error::TooLong` if the encoding is longer than
+        /// `max_len` bytes, reading no more than that.
+        pub async fn from_leb128u_max_async<T: FromLeb128u>(
+            reader: &mut (impl AsyncRead + Unpin),
+            max_len: usize,
+        ) -> Result<T, FromLeb128Error> {
+            Ok(read_with(reader, max_len, T::from_leb128u_slice).await?.0)
+        }
+
+        /// Like `from_leb128i_async`, but fails with
+        /// `FromLeb128Error::TooLong` if the encoding is longer than
+        /// `max_len` bytes, reading no more than that.
+        pub async fn from_leb128i_max_async<T: FromLeb128i>(
+            reader: &mut (impl AsyncRead + Unpin),
+            max_len: usize,
+        ) -> Result<T, FromLeb128Error> {
+            Ok(read_with(reader, max_len, T::from_leb128i_slice).await?.0)
+        }
+
+        /// Like `from_leb128u_async`, but also accepts encodings padded
+        /// beyond the type's maximum length, up to that of a `u128`.
+        pub async fn from_leb128u_lenient_async<T: FromLeb128u + TryFrom<u128>>(
+            reader: &mut (impl AsyncRead + Unpin),
+        ) -> Result<T, FromLeb128Error> {
+            let value = from_leb128u_async::<u128>(reader).await?;
+            T::try_from(value).map_err(|_| FromLeb128Error::Overflow)
+        }
+
+        /// Like `from_leb128i_async`, but also accepts encodings padded
+        /// beyond the type's maximum length, up to that of a `i128`.
+        pub async fn from_leb128i_lenient_async<T: FromLeb128i + TryFrom<i128>>(
+            reader: &mut (impl AsyncRead + Unpin),
+        ) -> Result<T, FromLeb128Error> {
+            let value = from_leb128i_async::<i128>(reader).await?;
+            T::try_from(value).map_err(|_| FromLeb128Error::Overflow)
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+            use $crate::test_util::ready;
+
+            #[test]
+            fn round_trip() {
+                let mut buf = Vec::new();
+                assert_eq!(ready(to_leb128u_async(&300u32, &mut buf)).unwrap(), 2);
+                assert_eq!(
+                    ready(to_leb128i_async(&(-65i64, 1i8), &mut buf)).unwrap(),
+                    3
+                );
+                assert_eq!(ready(to_leb128_zigzag_async(&-2i32, &mut buf)).unwrap(), 1);
+                assert_eq!(buf, [0xac, 0x02, 0xbf, 0x7f, 0x01, 0x03]);
+
+                let mut reader = &buf[..];
+                assert_eq!(
+                    ready(from_leb128u_counted_async::<u32>(&mut reader)).unwrap(),
+                    (300, 2)
+                );
+                assert_eq!(
+                    ready(from_leb128i_async::<(i64, i8)>(&mut reader)).unwrap(),
+                    (-65, 1)
+                );
+                assert_eq!(
+                    ready(from_leb128_zigzag_async::<i32>(&mut reader)).unwrap(),
+                    -2
+                );
+                assert!(matches!(
+                    ready(from_leb128u_async::<u32>(&mut reader)),
+                    Err(FromLeb128Error::UnexpectedEof)
+                ));
+            }
+
+            #[test]
+            fn checked_modes() {
+                let padded = [0x80, 0x00];
+                assert!(matches!(
+                    ready(from_leb128u_canonical_async::<u32>(&mut &padded[..])),
+                    Err(FromLeb128Error::NonCanonical)
+                ));
+                assert!(matches!(
+                    ready(from_leb128i_canonical_async::<i32>(&mut &[0xff, 0x7f][..])),
+                    Err(FromLeb128Error::NonCanonical)
+                ));
+                assert_eq!(
+                    ready(from_leb128u_lenient_async::<u8>(&mut &padded[..])).unwrap(),
+                    0
+                );
+
+                let mut reader = &[0x80, 0x80, 0x01][..];
+                assert!(matches!(
+                    ready(from_leb128u_max_async::<u32>(&mut reader, 2)),
+                    Err(FromLeb128Error::TooLong)
+                ));
+                assert_eq!(reader, [0x01]);
+
+                let e = ready(from_leb128u_positioned_async::<u32>(&mut &[0x80, 0x80][..]))
+                    .unwrap_err();
+                assert!(matches!(e.error, FromLeb128Error::UnexpectedEof));
+                assert_eq!(e.consumed, 2);
+            }
+        }
+    };
+}
+
+pub(crate) use async_io;
@@ -0,0 +1,66 @@
+//! Avro `int` and `long` encoding: zigzag followed by ULEB128.
+//!
+//! A `long` takes at most ten bytes and an `int` five. Longer input fails with
+//! `FromLeb128Error::TooLong`, and a final byte carrying bits beyond the type
+//! with `FromLeb128Error::Overflow`, instead of being silently truncated.
+
+use std::io::{self, Read, Write};
+
+use crate::zigzag::Leb128Zigzag;
+use crate::FromLeb128Error;
+
+/// Writes a `long` and returns the number of bytes written.
+pub fn encode_long(value: i64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    value.to_leb128_zigzag(writer)
+}
+
+pub fn decode_long(reader: &mut (impl Read + ?Sized)) -> Result<i64, FromLeb128Error> {
+    i64::from_leb128_zigzag(reader)
+}
+
+/// Writes an `int` and returns the number of bytes written.
+pub fn encode_int(value: i32, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    value.to_leb128_zigzag(writer)
+}
+
+pub fn decode_int(reader: &mut (impl Read + ?Sized)) -> Result<i32, FromLeb128Error> {
+    i32::from_leb128_zigzag(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spec_examples() {
+        // From the binary encoding table of the Avro specification.
+        for (value, bytes) in [
+            (0, &[0x00][..]),
+            (-1, &[0x01]),
+            (1, &[0x02]),
+            (-64, &[0x7f]),
+            (64, &[0x80, 0x01]),
+        ] {
+            let mut buf = Vec::new();
+            assert_eq!(encode_long(value, &mut buf).unwrap(), bytes.len());
+            assert_eq!(buf, bytes);
+            assert_eq!(decode_long(&mut &buf[..]).unwrap(), value);
+        }
+
+        let mut buf = Vec::new();
+        assert_eq!(encode_long(i64::MIN, &mut buf).unwrap(), 10);
+        assert_eq!(decode_long(&mut &buf[..]).unwrap(), i64::MIN);
+        assert!(matches!(
+            decode_long(&mut &[0x80; 11][..]),
+            Err(FromLeb128Error::TooLong)
+        ));
+        assert!(matches!(
+            decode_long(&mut &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x03][..]),
+            Err(FromLeb128Error::Overflow)
+        ));
+        assert!(matches!(
+            decode_int(&mut &[0xff, 0xff, 0xff, 0xff, 0x1f][..]),
+            Err(FromLeb128Error::Overflow)
+        ));
+    }
+}
@@ -0,0 +1,245 @@
+//! ULEB128 for `num_bigint::BigUint` and SLEB128 for `BigInt`, for formats
+//! that allow values wider than 128 bits.
+//!
+//! There is no upper bound on the length of these encodings, so
+//! `MAX_LEB128_LEN` is `usize::MAX` and decoding reads until the last byte of
+//! the value however long it is.
+
+use alloc::vec::Vec;
+use core::mem::MaybeUninit;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+use num_bigint::{BigInt, BigUint};
+
+use crate::{EncodeError, FromLeb128Error, FromLeb128i, FromLeb128u, ToLeb128i, ToLeb128u};
+
+/// Sets the continuation bit on every group but the last.
+fn finish(mut groups: Vec<u8>) -> Vec<u8> {
+    let last = groups.len() - 1;
+    for group in &mut groups[..last] {
+        *group |= 0b10000000;
+    }
+    groups
+}
+
+fn copy_into<'a>(bytes: &[u8], buf: &'a mut [MaybeUninit<u8>]) -> Result<&'a [u8], EncodeError> {
+    let buf = buf
+        .get_mut(..bytes.len())
+        .ok_or(EncodeError::BufferTooSmall)?;
+    for (slot, &byte) in buf.iter_mut().zip(bytes) {
+        slot.write(byte);
+    }
+    // SAFETY: every byte of `buf` was just written.
+    Ok(unsafe { crate::__private::assume_init(buf, bytes.len()) })
+}
+
+fn copy_to(bytes: &[u8], buf: &mut [u8]) -> Result<usize, EncodeError> {
+    buf.get_mut(..bytes.len())
+        .ok_or(EncodeError::BufferTooSmall)?
+        .copy_from_slice(bytes);
+    Ok(bytes.len())
+}
+
+/// The 7-bit groups of a value, least significant first, up to and including
+/// the byte with the continuation bit clear.
+fn groups(bytes: &[u8]) -> Result<&[u8], FromLeb128Error> {
+    match bytes.iter().position(|b| b & 0b10000000 == 0) {
+        Some(end) => Ok(&bytes[..=end]),
+        None => Err(FromLeb128Error::UnexpectedEof),
+    }
+}
+
+#[cfg(feature = "std")]
+fn read_groups(reader: &mut (impl Read + ?Sized)) -> Result<Vec<u8>, FromLeb128Error> {
+    let mut bytes = Vec::new();
+    loop {
+        let byte = crate::stream::read_byte(reader)?;
+        bytes.push(byte);
+        if byte & 0b10000000 == 0 {
+            return Ok(bytes);
+        }
+    }
+}
+
+fn decode_unsigned(groups: &[u8]) -> BigUint {
+    let digits: Vec<u8> = groups.iter().map(|b| b & 0b01111111).collect();
+    BigUint::from_radix_le(&digits, 128).expect("digits are below the radix")
+}
+
+/// Packs the 7-bit groups into two's complement bytes, sign-extending from
+/// bit 6 of the last group.
+fn decode_signed(groups: &[u8]) -> BigInt {
+    let negative = groups[groups.len() - 1] & 0b01000000 != 0;
+    let mut bytes = Vec::with_capacity(groups.len() * 7 / 8 + 1);
+    let mut acc = 0u32;
+    let mut bits = 0;
+
+    for group in groups {
+        acc |= u32::from(group & 0b01111111) << bits;
+        bits += 7;
+        if bits >= 8 {
+            bytes.push(acc as u8);
+            acc >>= 8;
+            bits -= 8;
+        }
+    }
+    if negative {
+        acc |= !0 << bits;
+    }
+    bytes.push(acc as u8);
+
+    BigInt::from_signed_bytes_le(&bytes)
+}
+
+fn encode_signed(value: &BigInt) -> Vec<u8> {
+    // Two's complement bits needed, including the sign bit.
+    let magnitude = if value.sign() == num_bigint::Sign::Minus {
+        (-value - 1u8).bits()
+    } else {
+        value.bits()
+    };
+    let len = (magnitude as usize + 1).div_ceil(7);
+
+    let bytes = value.to_signed_bytes_le();
+    let fill = if value.sign() == num_bigint::Sign::Minus {
+        0xff
+    } else {
+        0
+    };
+    let bit = |i: usize| (bytes.get(i / 8).copied().unwrap_or(fill) >> (i % 8)) & 1;
+
+    finish(
+        (0..len)
+            .map(|group| (0..7).fold(0, |acc, i| acc | bit(group * 7 + i) << i))
+            .collect(),
+    )
+}
+
+impl ToLeb128u for BigUint {
+    const MAX_LEB128_LEN: usize = usize::MAX;
+
+    fn leb128u_len(&self) -> usize {
+        (self.bits() as usize).max(1).div_ceil(7)
+    }
+
+    #[cfg(feature = "std")]
+    fn to_leb128u(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        let bytes = self.to_leb128u_vec();
+        writer.write_all(&bytes)?;
+        Ok(bytes.len())
+    }
+
+    fn to_leb128u_uninit<'a>(
+        &self,
+        buf: &'a mut [MaybeUninit<u8>],
+    ) -> Result<&'a [u8], EncodeError> {
+        copy_into(&self.to_leb128u_vec(), buf)
+    }
+
+    fn to_leb128u_buf(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        copy_to(&self.to_leb128u_vec(), buf)
+    }
+
+    fn to_leb128u_vec(&self) -> Vec<u8> {
+        finish(self.to_radix_le(128))
+    }
+}
+
+impl FromLeb128u for BigUint {
+    #[cfg(feature = "std")]
+    fn from_leb128u(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+        Ok(decode_unsigned(&read_groups(reader)?))
+    }
+
+    fn from_leb128u_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error> {
+        let groups = groups(bytes)?;
+        Ok((decode_unsigned(groups), groups.len()))
+    }
+}
+
+impl ToLeb128i for BigInt {
+    const MAX_LEB128_LEN: usize = usize::MAX;
+
+    fn leb128i_len(&self) -> usize {
+        encode_signed(self).len()
+    }
+
+    #[cfg(feature = "std")]
+    fn to_leb128i(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        let bytes = encode_signed(self);
+        writer.write_all(&bytes)?;
+        Ok(bytes.len())
+    }
+
+    fn to_leb128i_uninit<'a>(
+        &self,
+        buf: &'a mut [MaybeUninit<u8>],
+    ) -> Result<&'a [u8], EncodeError> {
+        copy_into(&encode_signed(self), buf)
+    }
+
+    fn to_leb128i_buf(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        copy_to(&encode_signed(self), buf)
+    }
+
+    fn to_leb128i_vec(&self) -> Vec<u8> {
+        encode_signed(self)
+    }
+}
+
+impl FromLeb128i for BigInt {
+    #[cfg(feature = "std")]
+    fn from_leb128i(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+        Ok(decode_signed(&read_groups(reader)?))
+    }
+
+    fn from_leb128i_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error> {
+        let groups = groups(bytes)?;
+        Ok((decode_signed(groups), groups.len()))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_primitives() {
+        for v in [0u128, 1, 127, 128, 300, u64::MAX as u128, u128::MAX] {
+            let big = BigUint::from(v);
+            assert_eq!(big.to_leb128u_vec(), v.to_leb128u_vec());
+            assert_eq!(big.leb128u_len(), v.leb128u_len());
+            assert_eq!(
+                BigUint::from_leb128u(&mut &v.to_leb128u_vec()[..]).unwrap(),
+                big
+            );
+        }
+        for v in [0i128, -1, 63, 64, -64, -65, i128::MIN, i128::MAX] {
+            let big = BigInt::from(v);
+            assert_eq!(big.to_leb128i_vec(), v.to_leb128i_vec());
+            assert_eq!(big.leb128i_len(), v.leb128i_len());
+            assert_eq!(
+                BigInt::from_leb128i_slice(&v.to_leb128i_vec()).unwrap(),
+                (big, v.leb128i_len())
+            );
+        }
+    }
+
+    #[test]
+    fn wider_than_128_bits() {
+        let big = BigUint::from(u128::MAX) * 1000u32;
+        let mut buf = Vec::new();
+        assert_eq!(big.to_leb128u(&mut buf).unwrap(), 20);
+        assert_eq!(BigUint::from_leb128u_exact(&buf).unwrap(), big);
+
+        let negative = -BigInt::from(big);
+        let bytes = negative.to_leb128i_vec();
+        assert_eq!(BigInt::from_leb128i(&mut &bytes[..]).unwrap(), negative);
+
+        assert!(matches!(
+            BigUint::from_leb128u_slice(&[0x80; 30]),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+    }
+}
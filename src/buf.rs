@@ -0,0 +1,261 @@
+#[cfg(any(feature = "asynchronous-codec", feature = "tokio-util"))]
+use std::io;
+use std::io::IoSlice;
+use std::mem::MaybeUninit;
+
+#[cfg(any(feature = "asynchronous-codec", feature = "tokio-util"))]
+use bytes::BytesMut;
+use bytes::{Buf, BufMut, Bytes};
+
+use crate::pending::SCRATCH_LEN;
+use crate::{FromLeb128Error, FromLeb128i, FromLeb128u, ToLeb128i, ToLeb128u};
+
+/// Splits a length-prefixed blob off the front of `buf` without copying.
+///
+/// The returned `Bytes` shares the allocation of `buf`. On error `buf` is left
+/// unchanged.
+pub fn read_blob(buf: &mut Bytes) -> Result<Bytes, FromLeb128Error> {
+    let mut reader = &buf[..];
+    let len = u64::from_leb128u(&mut reader)?;
+    let prefix = buf.len() - reader.len();
+
+    match usize::try_from(len) {
+        Ok(len) if len <= reader.len() => {
+            buf.advance(prefix);
+            Ok(buf.split_to(len))
+        }
+        _ => Err(FromLeb128Error::UnexpectedEof),
+    }
+}
+
+/// Writes `blob` prefixed with its length.
+pub fn put_blob(buf: &mut impl BufMut, blob: &[u8]) -> usize {
+    let mut storage = [MaybeUninit::uninit(); u64::MAX_LEB128_LEN];
+    let prefix = (blob.len() as u64).to_leb128u_uninit(&mut storage).unwrap();
+    buf.put_slice(prefix);
+    buf.put_slice(blob);
+    prefix.len() + blob.len()
+}
+
+/// Frame limit of the length-delimited codecs' `Default`, 8 MiB.
+#[cfg(any(feature = "asynchronous-codec", feature = "tokio-util"))]
+pub(crate) const DEFAULT_MAX_FRAME_LEN: usize = 8 << 20;
+
+/// Splits the next length-prefixed frame off the front of `src`, or returns
+/// `None` after reserving room for it if it is not complete yet.
+///
+/// This is the decoding side of the length-delimited codecs.
+#[cfg(any(feature = "asynchronous-codec", feature = "tokio-util"))]
+pub(crate) fn split_frame(
+    src: &mut BytesMut,
+    max_frame_len: usize,
+) -> Result<Option<BytesMut>, FromLeb128Error> {
+    let (len, prefix) = match crate::stream::peek_u64(src)? {
+        Some(header) => header,
+        None => return Ok(None),
+    };
+    let len = check_frame_len(len, max_frame_len)?;
+
+    if src.len() - prefix < len {
+        src.reserve(prefix + len - src.len());
+        return Ok(None);
+    }
+
+    src.advance(prefix);
+    Ok(Some(src.split_to(len)))
+}
+
+/// Appends `payload` to `dst` as one length-prefixed frame.
+#[cfg(any(feature = "asynchronous-codec", feature = "tokio-util"))]
+pub(crate) fn put_frame(
+    dst: &mut BytesMut,
+    payload: &[u8],
+    max_frame_len: usize,
+) -> Result<(), FromLeb128Error> {
+    check_frame_len(payload.len() as u64, max_frame_len)?;
+    dst.reserve(payload.len() + u64::MAX_LEB128_LEN);
+    put_blob(dst, payload);
+    Ok(())
+}
+
+#[cfg(any(feature = "asynchronous-codec", feature = "tokio-util"))]
+fn check_frame_len(len: u64, max_frame_len: usize) -> Result<usize, FromLeb128Error> {
+    match usize::try_from(len) {
+        Ok(len) if len <= max_frame_len => Ok(len),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "frame too large").into()),
+    }
+}
+
+/// The front of `buf` as one slice, gathered into `scratch` when the first
+/// chunk is shorter than both the buffer and the scratch space.
+fn front<'a, B: Buf + ?Sized>(buf: &'a B, scratch: &'a mut [u8; SCRATCH_LEN]) -> &'a [u8] {
+    let chunk = buf.chunk();
+    if chunk.len() >= SCRATCH_LEN || chunk.len() == buf.remaining() {
+        return chunk;
+    }
+
+    let mut slices = [IoSlice::new(&[]); 16];
+    let n = buf.chunks_vectored(&mut slices);
+    let mut len = 0;
+    for slice in &slices[..n] {
+        let take = slice.len().min(SCRATCH_LEN - len);
+        scratch[len..len + take].copy_from_slice(&slice[..take]);
+        len += take;
+    }
+    &scratch[..len]
+}
+
+macro_rules! getters {
+    ($generic:ident: $($name:ident => $t:ty),*) => {
+        $(
+            #[doc = concat!("Reads a `", stringify!($t), "`.")]
+            fn $name(&mut self) -> Result<$t, FromLeb128Error> {
+                self.$generic()
+            }
+        )*
+    };
+}
+
+macro_rules! putters {
+    ($generic:ident: $($name:ident => $t:ty),*) => {
+        $(
+            #[doc = concat!("Writes a `", stringify!($t), "` and returns the number of bytes written.")]
+            fn $name(&mut self, value: $t) -> usize {
+                self.$generic(&value)
+            }
+        )*
+    };
+}
+
+/// LEB128 getters for any [`Buf`], e.g. `buf.get_leb128_u32()`.
+///
+/// A value may span several chunks. On error nothing is consumed.
+pub trait BufLeb128Ext: Buf {
+    fn get_uleb128<T: FromLeb128u>(&mut self) -> Result<T, FromLeb128Error> {
+        let mut scratch = [0; SCRATCH_LEN];
+        let (value, len) = T::from_leb128u_slice(front(self, &mut scratch))?;
+        self.advance(len);
+        Ok(value)
+    }
+
+    fn get_sleb128<T: FromLeb128i>(&mut self) -> Result<T, FromLeb128Error> {
+        let mut scratch = [0; SCRATCH_LEN];
+        let (value, len) = T::from_leb128i_slice(front(self, &mut scratch))?;
+        self.advance(len);
+        Ok(value)
+    }
+
+    /// Whether the buffer holds a complete varint, without consuming it.
+    ///
+    /// Also true once more bytes are buffered than any integer can take,
+    /// since reading will then fail rather than need more input. Codecs can
+    /// use this to decide whether to wait for more data.
+    fn has_leb128(&self) -> bool {
+        let mut scratch = [0; SCRATCH_LEN];
+        let bytes = front(self, &mut scratch);
+        bytes.len() >= u128::MAX_LEB128_LEN || bytes.iter().any(|b| b & 0b10000000 == 0)
+    }
+
+    getters!(get_uleb128: get_leb128_u8 => u8, get_leb128_u16 => u16, get_leb128_u32 => u32, get_leb128_u64 => u64, get_leb128_u128 => u128, get_leb128_usize => usize);
+    getters!(get_sleb128: get_leb128_i8 => i8, get_leb128_i16 => i16, get_leb128_i32 => i32, get_leb128_i64 => i64, get_leb128_i128 => i128, get_leb128_isize => isize);
+}
+
+impl<B: Buf + ?Sized> BufLeb128Ext for B {}
+
+/// LEB128 putters for any [`BufMut`], e.g. `buf.put_leb128_u64(v)`.
+///
+/// Like the other `BufMut` methods, these panic if the buffer runs out of
+/// space.
+pub trait BufMutLeb128Ext: BufMut {
+    fn put_uleb128<T: ToLeb128u + ?Sized>(&mut self, value: &T) -> usize {
+        value
+            .to_leb128u(&mut self.writer())
+            .expect("BufMut writer is infallible")
+    }
+
+    fn put_sleb128<T: ToLeb128i + ?Sized>(&mut self, value: &T) -> usize {
+        value
+            .to_leb128i(&mut self.writer())
+            .expect("BufMut writer is infallible")
+    }
+
+    putters!(put_uleb128: put_leb128_u8 => u8, put_leb128_u16 => u16, put_leb128_u32 => u32, put_leb128_u64 => u64, put_leb128_u128 => u128, put_leb128_usize => usize);
+    putters!(put_sleb128: put_leb128_i8 => i8, put_leb128_i16 => i16, put_leb128_i32 => i32, put_leb128_i64 => i64, put_leb128_i128 => i128, put_leb128_isize => isize);
+}
+
+impl<B: BufMut + ?Sized> BufMutLeb128Ext for B {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BytesMut;
+
+    #[test]
+    fn shares_allocation() {
+        let mut out = BytesMut::new();
+        put_blob(&mut out, &[7; 200]);
+        put_blob(&mut out, b"tail");
+
+        let mut buf = out.freeze();
+        let base = buf.as_ptr();
+        let blob = read_blob(&mut buf).unwrap();
+
+        assert_eq!(blob.len(), 200);
+        assert_eq!(blob.as_ptr(), base.wrapping_add(2));
+        assert_eq!(read_blob(&mut buf).unwrap(), &b"tail"[..]);
+        assert!(buf.is_empty());
+
+        let mut short = Bytes::from_static(&[0x05, 1, 2]);
+        assert!(read_blob(&mut short).is_err());
+        assert_eq!(short.len(), 3);
+    }
+
+    #[test]
+    #[cfg(any(feature = "asynchronous-codec", feature = "tokio-util"))]
+    fn frames_split_input() {
+        let mut wire = BytesMut::new();
+        put_frame(&mut wire, &[1; 300], 1024).unwrap();
+        put_frame(&mut wire, b"hi", 1024).unwrap();
+        assert!(put_frame(&mut wire, &[0; 1025], 1024).is_err());
+
+        let mut src = BytesMut::new();
+        let mut frames = Vec::new();
+        for chunk in wire.chunks(7) {
+            src.extend_from_slice(chunk);
+            while let Some(frame) = split_frame(&mut src, 1024).unwrap() {
+                frames.push(frame);
+            }
+        }
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].len(), 300);
+        assert_eq!(frames[1], &b"hi"[..]);
+        assert!(split_frame(&mut BytesMut::from(&[0x05][..]), 4).is_err());
+    }
+
+    #[test]
+    fn extension_traits() {
+        let mut out = BytesMut::new();
+        assert_eq!(out.put_leb128_u32(300), 2);
+        assert_eq!(out.put_leb128_i64(-65), 2);
+        out.put_uleb128(&(1u8, 2u16));
+        assert_eq!(out, &[0xac, 0x02, 0xbf, 0x7f, 0x01, 0x02][..]);
+
+        // Split the first value across two chunks.
+        let mut buf = (&out[..1]).chain(&out[1..]);
+        assert!(buf.has_leb128());
+        assert_eq!(buf.get_leb128_u32().unwrap(), 300);
+        assert_eq!(buf.get_leb128_i64().unwrap(), -65);
+        assert_eq!(buf.get_uleb128::<(u8, u16)>().unwrap(), (1, 2));
+        assert!(!buf.has_leb128());
+
+        let mut partial = &[0xff, 0xff][..];
+        assert!(!partial.has_leb128());
+        assert!(matches!(
+            partial.get_leb128_u64(),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+        assert_eq!(partial.len(), 2);
+        assert!((&[0x80; 20][..]).has_leb128());
+    }
+}
@@ -0,0 +1,162 @@
+//! Bulk encoding and decoding of `u64` slices.
+//!
+//! On `wasm32` with the `simd128` target feature, runs of single-byte values
+//! are detected 16 at a time and handled without the per-value loop.
+
+use alloc::vec::Vec;
+
+use crate::{FromLeb128Error, FromLeb128u};
+
+/// Appends the ULEB128 encoding of every value to `out`.
+pub fn encode_slice(values: &[u64], out: &mut Vec<u8>) {
+    out.reserve(values.len());
+    let mut rest = values;
+
+    while !rest.is_empty() {
+        let small = kernel::small_prefix(rest);
+        out.extend(rest[..small].iter().map(|&v| v as u8));
+        rest = &rest[small..];
+
+        if let Some((&value, tail)) = rest.split_first() {
+            encode_one(value, out);
+            rest = tail;
+        }
+    }
+}
+
+fn encode_one(mut value: u64, out: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0b01111111) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            out.push(byte);
+            break;
+        }
+
+        out.push(byte | 0b10000000);
+    }
+}
+
+/// Decodes every value in `bytes`, appending them to `out`, and returns how
+/// many were decoded.
+///
+/// On error `out` is left as it was.
+pub fn decode_slice(bytes: &[u8], out: &mut Vec<u64>) -> Result<usize, FromLeb128Error> {
+    out.try_reserve(crate::scan::count_values(bytes))?;
+    let start = out.len();
+
+    if let Err(e) = decode_into(bytes, out) {
+        out.truncate(start);
+        return Err(e);
+    }
+
+    Ok(out.len() - start)
+}
+
+fn decode_into(bytes: &[u8], out: &mut Vec<u64>) -> Result<(), FromLeb128Error> {
+    let mut rest = bytes;
+
+    while !rest.is_empty() {
+        let small = kernel::single_byte_prefix(rest);
+        out.extend(rest[..small].iter().map(|&b| u64::from(b)));
+        rest = &rest[small..];
+
+        if !rest.is_empty() {
+            let (value, len) = u64::from_leb128u_slice(rest)?;
+            out.push(value);
+            rest = &rest[len..];
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+mod kernel {
+    /// Length of the leading run of values below 128, in blocks of 16.
+    pub(super) fn small_prefix(values: &[u64]) -> usize {
+        values
+            .chunks_exact(16)
+            .take_while(|chunk| chunk.iter().fold(0, |acc, v| acc | v) < 0b10000000)
+            .count()
+            * 16
+    }
+
+    /// Length of the leading run of single-byte values, in blocks of 16.
+    pub(super) fn single_byte_prefix(bytes: &[u8]) -> usize {
+        bytes
+            .chunks_exact(16)
+            .take_while(|chunk| chunk.iter().fold(0, |acc, b| acc | b) < 0b10000000)
+            .count()
+            * 16
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod kernel {
+    use core::arch::wasm32::*;
+
+    pub(super) fn small_prefix(values: &[u64]) -> usize {
+        let mut len = 0;
+
+        for chunk in values.chunks_exact(16) {
+            let mut acc = u64x2_splat(0);
+            for pair in chunk.chunks_exact(2) {
+                acc = v128_or(acc, u64x2(pair[0], pair[1]));
+            }
+
+            if v128_any_true(u64x2_shr(acc, 7)) {
+                break;
+            }
+            len += 16;
+        }
+
+        len
+    }
+
+    pub(super) fn single_byte_prefix(bytes: &[u8]) -> usize {
+        let mut len = 0;
+
+        for chunk in bytes.chunks_exact(16) {
+            // SAFETY: `chunk` is 16 bytes long and wasm loads may be unaligned.
+            let v = unsafe { v128_load(chunk.as_ptr().cast()) };
+
+            if u8x16_bitmask(v) != 0 {
+                break;
+            }
+            len += 16;
+        }
+
+        len
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::ToLeb128u;
+
+    #[test]
+    fn round_trip() {
+        let values = (0..1000u64)
+            .map(|i| if i % 37 == 0 { i << 40 } else { i % 128 })
+            .collect::<Vec<_>>();
+        let mut expected = Vec::new();
+        for v in &values {
+            v.to_leb128u(&mut expected).unwrap();
+        }
+
+        let mut bytes = Vec::new();
+        encode_slice(&values, &mut bytes);
+        assert_eq!(bytes, expected);
+
+        let mut decoded = Vec::new();
+        assert_eq!(decode_slice(&bytes, &mut decoded).unwrap(), 1000);
+        assert_eq!(decoded, values);
+
+        bytes.push(0x80);
+        assert!(decode_slice(&bytes, &mut decoded).is_err());
+        assert_eq!(decoded, values);
+    }
+}
@@ -0,0 +1,152 @@
+use std::marker::PhantomData;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::buf;
+use crate::format::{Uleb128, VarintFormat};
+use crate::FromLeb128Error;
+
+/// `tokio-util` codec turning a byte stream into a stream of integers in
+/// format `F`, e.g. `Leb128Codec::<Sleb128>::new()` for `i64`s.
+///
+/// A value split across reads is held back until it is complete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Leb128Codec<F = Uleb128> {
+    format: PhantomData<F>,
+}
+
+impl<F: VarintFormat> Leb128Codec<F> {
+    pub fn new() -> Self {
+        Self {
+            format: PhantomData,
+        }
+    }
+}
+
+impl<F: VarintFormat> Default for Leb128Codec<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: VarintFormat> Encoder<F::Value> for Leb128Codec<F> {
+    type Error = FromLeb128Error;
+
+    fn encode(&mut self, item: F::Value, dst: &mut BytesMut) -> Result<(), FromLeb128Error> {
+        dst.reserve(F::encoded_len(item).unwrap_or(0));
+        F::encode(item, &mut dst.writer())?;
+        Ok(())
+    }
+}
+
+impl<F: VarintFormat> Decoder for Leb128Codec<F> {
+    type Item = F::Value;
+    type Error = FromLeb128Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<F::Value>, FromLeb128Error> {
+        let mut reader = &src[..];
+
+        match F::decode(&mut reader) {
+            Ok(value) => {
+                let len = src.len() - reader.len();
+                src.advance(len);
+                Ok(Some(value))
+            }
+            Err(FromLeb128Error::UnexpectedEof) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Length-delimited framing for `tokio-util`: each frame is its length as
+/// ULEB128 followed by the payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Leb128FrameCodec {
+    max_frame_len: usize,
+}
+
+impl Leb128FrameCodec {
+    /// Frames longer than `max_frame_len` are rejected in both directions.
+    pub fn new(max_frame_len: usize) -> Self {
+        Self { max_frame_len }
+    }
+
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+}
+
+impl Default for Leb128FrameCodec {
+    /// A codec with an 8 MiB frame limit.
+    fn default() -> Self {
+        Self::new(buf::DEFAULT_MAX_FRAME_LEN)
+    }
+}
+
+impl Encoder<&[u8]> for Leb128FrameCodec {
+    type Error = FromLeb128Error;
+
+    fn encode(&mut self, item: &[u8], dst: &mut BytesMut) -> Result<(), FromLeb128Error> {
+        buf::put_frame(dst, item, self.max_frame_len)
+    }
+}
+
+impl Encoder<Bytes> for Leb128FrameCodec {
+    type Error = FromLeb128Error;
+
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> Result<(), FromLeb128Error> {
+        self.encode(&item[..], dst)
+    }
+}
+
+impl Decoder for Leb128FrameCodec {
+    type Item = Bytes;
+    type Error = FromLeb128Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Bytes>, FromLeb128Error> {
+        Ok(buf::split_frame(src, self.max_frame_len)?.map(BytesMut::freeze))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Sleb128;
+
+    #[test]
+    fn values_split_across_reads() {
+        let mut codec = Leb128Codec::<Sleb128>::new();
+        let values = [0, -1, 300, i64::MIN, 64];
+        let mut wire = BytesMut::new();
+        for v in values {
+            codec.encode(v, &mut wire).unwrap();
+        }
+
+        let mut src = BytesMut::new();
+        let mut decoded = Vec::new();
+        for chunk in wire.chunks(3) {
+            src.extend_from_slice(chunk);
+            while let Some(value) = codec.decode(&mut src).unwrap() {
+                decoded.push(value);
+            }
+        }
+
+        assert_eq!(decoded, values);
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn rejects_overlong_value() {
+        let mut codec = Leb128Codec::<Uleb128>::new();
+        let mut src = BytesMut::from(&[0x80; 11][..]);
+        assert!(matches!(
+            codec.decode(&mut src),
+            Err(FromLeb128Error::TooLong)
+        ));
+        assert_eq!(
+            codec.decode(&mut BytesMut::from(&[0x80][..])).unwrap(),
+            None
+        );
+    }
+}
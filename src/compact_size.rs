@@ -0,0 +1,131 @@
+//! Bitcoin's CompactSize integers, used for counts and lengths in transactions
+//! and blocks.
+//!
+//! Values below `0xFD` are a single byte. Larger ones are the marker `0xFD`,
+//! `0xFE` or `0xFF` followed by the value as a little-endian `u16`, `u32` or
+//! `u64`. Consensus code must reject an escape used for a value that fits a
+//! shorter form, so decoding such input fails with
+//! `FromLeb128Error::NonCanonical`.
+
+use std::io::{self, Read, Write};
+
+use crate::FromLeb128Error;
+
+/// Longest encoding, in bytes.
+pub const MAX_LEN: usize = 9;
+
+/// Number of bytes `encode` would write.
+pub fn encoded_len(value: u64) -> usize {
+    match value {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+/// Writes `value` and returns the number of bytes written.
+pub fn encode(value: u64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    let mut buf = [0; MAX_LEN];
+    let len = encoded_len(value);
+    match len {
+        1 => buf[0] = value as u8,
+        3 => {
+            buf[0] = 0xfd;
+            buf[1..3].copy_from_slice(&(value as u16).to_le_bytes());
+        }
+        5 => {
+            buf[0] = 0xfe;
+            buf[1..5].copy_from_slice(&(value as u32).to_le_bytes());
+        }
+        _ => {
+            buf[0] = 0xff;
+            buf[1..9].copy_from_slice(&value.to_le_bytes());
+        }
+    }
+    writer.write_all(&buf[..len])?;
+    Ok(len)
+}
+
+fn finish(first: u8, rest: &[u8]) -> Result<u64, FromLeb128Error> {
+    if rest.is_empty() {
+        return Ok(first.into());
+    }
+    let mut bytes = [0; 8];
+    bytes[..rest.len()].copy_from_slice(rest);
+    let value = u64::from_le_bytes(bytes);
+    if encoded_len(value) != 1 + rest.len() {
+        return Err(FromLeb128Error::NonCanonical);
+    }
+    Ok(value)
+}
+
+/// Number of bytes following a first byte of `first`.
+fn rest_len(first: u8) -> usize {
+    match first {
+        0xfd => 2,
+        0xfe => 4,
+        0xff => 8,
+        _ => 0,
+    }
+}
+
+pub fn decode(reader: &mut (impl Read + ?Sized)) -> Result<u64, FromLeb128Error> {
+    let mut buf = [0; MAX_LEN];
+    reader.read_exact(&mut buf[..1])?;
+    let len = 1 + rest_len(buf[0]);
+    reader.read_exact(&mut buf[1..len])?;
+    finish(buf[0], &buf[1..len])
+}
+
+/// Decodes from the front of `bytes` and returns the value with the number of
+/// bytes consumed.
+pub fn decode_slice(bytes: &[u8]) -> Result<(u64, usize), FromLeb128Error> {
+    let first = *bytes.first().ok_or(FromLeb128Error::UnexpectedEof)?;
+    let len = 1 + rest_len(first);
+    let rest = bytes.get(1..len).ok_or(FromLeb128Error::UnexpectedEof)?;
+    Ok((finish(first, rest)?, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_and_canonical() {
+        for (value, bytes) in [
+            (0, &[0x00][..]),
+            (0xfc, &[0xfc]),
+            (0xfd, &[0xfd, 0xfd, 0x00]),
+            (0xffff, &[0xfd, 0xff, 0xff]),
+            (0x1_0000, &[0xfe, 0x00, 0x00, 0x01, 0x00]),
+            (0x1_0000_0000, &[0xff, 0, 0, 0, 0, 1, 0, 0, 0]),
+            (u64::MAX, &[0xff; 9]),
+        ] {
+            let mut buf = Vec::new();
+            assert_eq!(encode(value, &mut buf).unwrap(), bytes.len());
+            assert_eq!(buf, bytes);
+            assert_eq!(decode(&mut &buf[..]).unwrap(), value);
+            assert_eq!(decode_slice(&buf).unwrap(), (value, bytes.len()));
+        }
+
+        for bytes in [
+            &[0xfd, 0xfc, 0x00][..],
+            &[0xfe, 0xff, 0xff, 0x00, 0x00],
+            &[0xff, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0],
+        ] {
+            assert!(matches!(
+                decode_slice(bytes),
+                Err(FromLeb128Error::NonCanonical)
+            ));
+            assert!(matches!(
+                decode(&mut &bytes[..]),
+                Err(FromLeb128Error::NonCanonical)
+            ));
+        }
+        assert!(matches!(
+            decode_slice(&[0xfe, 0x00]),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+    }
+}
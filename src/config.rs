@@ -0,0 +1,125 @@
+//! Decode options bundled for reuse across an application.
+
+use std::io::Read;
+
+use crate::{stream, FromLeb128Error, FromLeb128i, FromLeb128u};
+
+/// A decode policy: whether non-minimal encodings are rejected, and how many
+/// bytes a value may use.
+///
+/// ```
+/// use leb128_rs::config::Leb128Config;
+///
+/// const STRICT: Leb128Config = Leb128Config::new().canonical(true).max_len(5);
+///
+/// assert_eq!(STRICT.decode_u::<u64>(&mut &[0xac, 0x02][..]).unwrap(), 300);
+/// assert!(STRICT.decode_u::<u64>(&mut &[0x80, 0x00][..]).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Leb128Config {
+    canonical: bool,
+    max_len: Option<usize>,
+}
+
+impl Leb128Config {
+    /// The default policy, matching `from_leb128u`/`from_leb128i`.
+    pub const fn new() -> Self {
+        Self {
+            canonical: false,
+            max_len: None,
+        }
+    }
+
+    /// Rejects non-minimal encodings with `FromLeb128Error::NonCanonical`.
+    pub const fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Rejects encodings longer than `max_len` bytes with
+    /// `FromLeb128Error::TooLong`.
+    pub const fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    pub fn decode_u<T: FromLeb128u>(&self, reader: &mut impl Read) -> Result<T, FromLeb128Error> {
+        self.decode(reader, |r, canonical| {
+            if canonical {
+                T::from_leb128u_canonical(r)
+            } else {
+                T::from_leb128u(r)
+            }
+        })
+    }
+
+    pub fn decode_i<T: FromLeb128i>(&self, reader: &mut impl Read) -> Result<T, FromLeb128Error> {
+        self.decode(reader, |r, canonical| {
+            if canonical {
+                T::from_leb128i_canonical(r)
+            } else {
+                T::from_leb128i(r)
+            }
+        })
+    }
+
+    /// Like [`decode_u`](Self::decode_u), returning `None` on a clean end of
+    /// input.
+    pub fn decode_next_u<T: FromLeb128u>(
+        &self,
+        reader: &mut impl Read,
+    ) -> Result<Option<T>, FromLeb128Error> {
+        stream::read_next(reader, |r| self.decode_u(r))
+    }
+
+    /// Like [`decode_i`](Self::decode_i), returning `None` on a clean end of
+    /// input.
+    pub fn decode_next_i<T: FromLeb128i>(
+        &self,
+        reader: &mut impl Read,
+    ) -> Result<Option<T>, FromLeb128Error> {
+        stream::read_next(reader, |r| self.decode_i(r))
+    }
+
+    fn decode<R: Read, T>(
+        &self,
+        reader: &mut R,
+        decode: impl FnOnce(&mut stream::Limit<&mut R>, bool) -> Result<T, FromLeb128Error>,
+    ) -> Result<T, FromLeb128Error> {
+        let mut reader = stream::Limit::new(reader, self.max_len.unwrap_or(usize::MAX));
+
+        match decode(&mut reader, self.canonical) {
+            Err(FromLeb128Error::UnexpectedEof) if reader.exceeded() => {
+                Err(FromLeb128Error::TooLong)
+            }
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policies() {
+        let lax = Leb128Config::new();
+        let strict = Leb128Config::new().canonical(true).max_len(2);
+        let padded = [0x81, 0x80, 0x00];
+
+        assert_eq!(lax.decode_u::<u32>(&mut &padded[..]).unwrap(), 1);
+        assert!(matches!(
+            strict.decode_u::<u32>(&mut &padded[..]),
+            Err(FromLeb128Error::TooLong)
+        ));
+        assert!(matches!(
+            strict.decode_i::<i32>(&mut &[0xff, 0x7f][..]),
+            Err(FromLeb128Error::NonCanonical)
+        ));
+
+        let mut reader = &[0x7f, 0x01][..];
+        assert_eq!(strict.decode_next_i::<i8>(&mut reader).unwrap(), Some(-1));
+        assert_eq!(strict.decode_next_u::<u8>(&mut reader).unwrap(), Some(1));
+        assert_eq!(strict.decode_next_u::<u8>(&mut reader).unwrap(), None);
+    }
+}
@@ -0,0 +1,85 @@
+//! Decoding from ring buffers whose contents may wrap around.
+
+use std::collections::VecDeque;
+use std::io::Read;
+
+use crate::{FromLeb128Error, FromLeb128i, FromLeb128u};
+
+fn decode_split<T>(
+    front: &[u8],
+    back: &[u8],
+    decode: impl FnOnce(&mut std::io::Chain<&[u8], &[u8]>) -> Result<T, FromLeb128Error>,
+) -> Result<(T, usize), FromLeb128Error> {
+    let mut reader = front.chain(back);
+    let value = decode(&mut reader)?;
+    let (front_rest, back_rest) = reader.into_inner();
+
+    Ok((
+        value,
+        front.len() + back.len() - front_rest.len() - back_rest.len(),
+    ))
+}
+
+/// Decodes a value stored across `front` followed by `back`, returning it with
+/// the number of bytes consumed.
+pub fn from_leb128u_split<T: FromLeb128u>(
+    front: &[u8],
+    back: &[u8],
+) -> Result<(T, usize), FromLeb128Error> {
+    decode_split(front, back, |r| T::from_leb128u(r))
+}
+
+/// Signed counterpart of [`from_leb128u_split`].
+pub fn from_leb128i_split<T: FromLeb128i>(
+    front: &[u8],
+    back: &[u8],
+) -> Result<(T, usize), FromLeb128Error> {
+    decode_split(front, back, |r| T::from_leb128i(r))
+}
+
+/// Decodes a value from the front of `deque` and removes its bytes.
+///
+/// On error the deque is left unchanged, so a value that is not complete yet
+/// can be retried once more bytes arrive.
+pub fn pop_leb128u<T: FromLeb128u>(deque: &mut VecDeque<u8>) -> Result<T, FromLeb128Error> {
+    let (front, back) = deque.as_slices();
+    let (value, len) = from_leb128u_split(front, back)?;
+    deque.drain(..len);
+    Ok(value)
+}
+
+/// Signed counterpart of [`pop_leb128u`].
+pub fn pop_leb128i<T: FromLeb128i>(deque: &mut VecDeque<u8>) -> Result<T, FromLeb128Error> {
+    let (front, back) = deque.as_slices();
+    let (value, len) = from_leb128i_split(front, back)?;
+    deque.drain(..len);
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn straddles_wrap_point() {
+        assert_eq!(
+            from_leb128u_split::<u32>(&[0xac], &[0x02, 0x07]).unwrap(),
+            (300, 2)
+        );
+        assert_eq!(from_leb128i_split::<i32>(&[], &[0x7f]).unwrap(), (-1, 1));
+
+        let mut deque = VecDeque::with_capacity(4);
+        deque.extend([0, 0, 0]);
+        deque.pop_front();
+        deque.pop_front();
+        deque.pop_front();
+        deque.extend([0xac, 0x02, 0x80]);
+        assert!(!deque.as_slices().1.is_empty());
+
+        assert_eq!(pop_leb128u::<u32>(&mut deque).unwrap(), 300);
+        assert!(pop_leb128u::<u32>(&mut deque).is_err());
+        assert_eq!(deque.len(), 1);
+        deque.push_back(0x01);
+        assert_eq!(pop_leb128u::<u32>(&mut deque).unwrap(), 128);
+    }
+}
@@ -0,0 +1,112 @@
+use std::io::{self, Read};
+
+use crate::format::VarintFormat;
+use crate::stream::Counting;
+use crate::FromLeb128Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    A,
+    B,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DivergenceKind<T> {
+    /// Both streams have a value at this index, but they differ.
+    Value { a: T, b: T },
+    /// The given stream ended while the other still had values.
+    Ended(Side),
+    /// The given stream holds a malformed or truncated value.
+    Malformed(Side),
+}
+
+/// The first point at which two streams disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence<T> {
+    /// Index of the value at which the streams diverge.
+    pub index: u64,
+    /// Byte offset of that value in stream `a`.
+    pub offset_a: u64,
+    /// Byte offset of that value in stream `b`.
+    pub offset_b: u64,
+    pub kind: DivergenceKind<T>,
+}
+
+/// Decodes two streams in lockstep and reports where they first diverge.
+///
+/// Returns `Ok(None)` if both streams hold the same values.
+pub fn diff_streams<F>(a: impl Read, b: impl Read) -> io::Result<Option<Divergence<F::Value>>>
+where
+    F: VarintFormat,
+    F::Value: PartialEq,
+{
+    let mut a = Counting::new(a);
+    let mut b = Counting::new(b);
+    let mut index = 0;
+
+    loop {
+        let (offset_a, offset_b) = (a.count(), b.count());
+        let diverged = |kind| {
+            Ok(Some(Divergence {
+                index,
+                offset_a,
+                offset_b,
+                kind,
+            }))
+        };
+
+        let va = match next::<F>(&mut a) {
+            Ok(v) => v,
+            Err(e) => return e.map_or_else(|| diverged(DivergenceKind::Malformed(Side::A)), Err),
+        };
+        let vb = match next::<F>(&mut b) {
+            Ok(v) => v,
+            Err(e) => return e.map_or_else(|| diverged(DivergenceKind::Malformed(Side::B)), Err),
+        };
+
+        match (va, vb) {
+            (None, None) => return Ok(None),
+            (None, Some(_)) => return diverged(DivergenceKind::Ended(Side::A)),
+            (Some(_), None) => return diverged(DivergenceKind::Ended(Side::B)),
+            (Some(va), Some(vb)) if va != vb => {
+                return diverged(DivergenceKind::Value { a: va, b: vb })
+            }
+            _ => index += 1,
+        }
+    }
+}
+
+/// Decodes the next value; a malformed value is reported as `Err(None)` and
+/// any other I/O failure as `Err(Some(e))`.
+fn next<F: VarintFormat>(reader: &mut impl Read) -> Result<Option<F::Value>, Option<io::Error>> {
+    match F::decode_next(reader) {
+        Ok(v) => Ok(v),
+        Err(FromLeb128Error::Io(e)) => Err(Some(e)),
+        Err(_) => Err(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Uleb128;
+
+    #[test]
+    fn reports_first_divergence() {
+        let a = [0x01, 0xac, 0x02, 0x05];
+        let b = [0x01, 0xac, 0x82, 0x00, 0x06];
+        let d = diff_streams::<Uleb128>(&a[..], &b[..]).unwrap().unwrap();
+
+        assert_eq!(d.index, 2);
+        assert_eq!((d.offset_a, d.offset_b), (3, 4));
+        assert_eq!(d.kind, DivergenceKind::Value { a: 5, b: 6 });
+
+        assert_eq!(diff_streams::<Uleb128>(&a[..], &a[..]).unwrap(), None);
+
+        let d = diff_streams::<Uleb128>(&a[..3], &a[..]).unwrap().unwrap();
+        assert_eq!((d.index, d.kind), (2, DivergenceKind::Ended(Side::A)));
+
+        let d = diff_streams::<Uleb128>(&a[..], &a[..2]).unwrap().unwrap();
+        assert_eq!((d.index, d.kind), (1, DivergenceKind::Malformed(Side::B)));
+    }
+}
@@ -0,0 +1,212 @@
+//! EBML variable-size integers (RFC 8794), as used by Matroska and WebM.
+//!
+//! The number of leading zero bits in the first byte gives the length, from 1
+//! to 8 bytes, and the data follows the marker bit big-endian, 7 bits per
+//! byte. Data with every bit set is reserved: as an element size it means the
+//! size is unknown, and the writers here never produce it by accident.
+//!
+//! Element IDs are VINTs too, but are kept whole, marker bit included, so
+//! `0x1A45DFA3` is the EBML header ID as it appears in the specifications.
+
+use std::io::{self, Read, Write};
+
+use crate::FromLeb128Error;
+
+/// Longest VINT, in bytes.
+pub const MAX_LEN: usize = 8;
+
+/// Longest element ID, in bytes.
+pub const MAX_ID_LEN: usize = 4;
+
+/// Largest value that can be written as a VINT.
+pub const MAX_VALUE: u64 = (1 << 56) - 2;
+
+fn all_ones(len: usize) -> u64 {
+    (1 << (7 * len)) - 1
+}
+
+/// Shortest length for `value`, or `None` above [`MAX_VALUE`].
+pub fn vint_len(value: u64) -> Option<usize> {
+    (1..=MAX_LEN).find(|&len| value < all_ones(len))
+}
+
+/// Writes `value` in its shortest form and returns the number of bytes
+/// written.
+///
+/// Fails with `io::ErrorKind::InvalidInput` above [`MAX_VALUE`].
+pub fn write_vint(value: u64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    let len = vint_len(value)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "value above 2^56 - 2"))?;
+    write_vint_with_len(value, len, writer)
+}
+
+/// Writes `value` using exactly `len` bytes, e.g. to leave room for a size
+/// that is patched in later.
+///
+/// Fails with `io::ErrorKind::InvalidInput` if `len` is not between 1 and 8,
+/// or is too short for `value`.
+pub fn write_vint_with_len(
+    value: u64,
+    len: usize,
+    writer: &mut (impl Write + ?Sized),
+) -> io::Result<usize> {
+    if !(1..=MAX_LEN).contains(&len) || value >= all_ones(len) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "value does not fit the length",
+        ));
+    }
+    write_raw(value, len, writer)
+}
+
+fn write_raw(value: u64, len: usize, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    let bytes = (value | 1 << (7 * len)).to_be_bytes();
+    writer.write_all(&bytes[8 - len..])?;
+    Ok(len)
+}
+
+/// Reads a VINT and returns its data with the number of bytes read.
+///
+/// A first byte of zero would mean a VINT longer than 8 bytes and fails with
+/// `FromLeb128Error::TooLong`.
+pub fn read_vint(reader: &mut (impl Read + ?Sized)) -> Result<(u64, usize), FromLeb128Error> {
+    let mut buf = [0; MAX_LEN];
+    reader.read_exact(&mut buf[..1])?;
+    if buf[0] == 0 {
+        return Err(FromLeb128Error::TooLong);
+    }
+    let len = buf[0].leading_zeros() as usize + 1;
+    reader.read_exact(&mut buf[1..len])?;
+
+    let first = u64::from(buf[0]) & 0xff >> len;
+    let value = buf[1..len]
+        .iter()
+        .fold(first, |acc, &b| acc << 8 | u64::from(b));
+    Ok((value, len))
+}
+
+/// Writes an element size, with `None` for the unknown size.
+pub fn write_element_size(
+    size: Option<u64>,
+    writer: &mut (impl Write + ?Sized),
+) -> io::Result<usize> {
+    match size {
+        Some(size) => write_vint(size, writer),
+        None => write_raw(all_ones(1), 1, writer),
+    }
+}
+
+/// Reads an element size, returning `None` for the unknown size at any
+/// length.
+pub fn read_element_size(
+    reader: &mut (impl Read + ?Sized),
+) -> Result<Option<u64>, FromLeb128Error> {
+    let (value, len) = read_vint(reader)?;
+    Ok((value != all_ones(len)).then_some(value))
+}
+
+/// Writes an element ID, which must already carry its marker bit.
+///
+/// Fails with `io::ErrorKind::InvalidInput` if `id` is not a valid 1 to 4
+/// byte VINT.
+pub fn write_element_id(id: u32, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    let len = MAX_ID_LEN - id.leading_zeros() as usize / 8;
+    let marker = 1 << (7 * len);
+    if id == 0 || id.leading_zeros() as usize != 8 * MAX_ID_LEN - 7 * len - 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "ID has no valid marker bit",
+        ));
+    }
+    if u64::from(id ^ marker) == all_ones(len) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "ID data is all ones",
+        ));
+    }
+    writer.write_all(&id.to_be_bytes()[MAX_ID_LEN - len..])?;
+    Ok(len)
+}
+
+/// Reads an element ID, marker bit included.
+///
+/// IDs longer than 4 bytes fail with `FromLeb128Error::TooLong`, and IDs
+/// whose data is all zeros or all ones with `FromLeb128Error::Malformed`.
+pub fn read_element_id(reader: &mut (impl Read + ?Sized)) -> Result<u32, FromLeb128Error> {
+    let mut buf = [0; MAX_ID_LEN];
+    reader.read_exact(&mut buf[..1])?;
+    let len = buf[0].leading_zeros() as usize + 1;
+    if len > MAX_ID_LEN {
+        return Err(FromLeb128Error::TooLong);
+    }
+    reader.read_exact(&mut buf[1..len])?;
+
+    let id = buf[..len].iter().fold(0, |acc, &b| acc << 8 | u32::from(b));
+    let data = u64::from(id ^ 1 << (7 * len));
+    if data == 0 || data == all_ones(len) {
+        return Err(FromLeb128Error::Malformed);
+    }
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vints() {
+        for (value, bytes) in [
+            (0, &[0x80][..]),
+            (1, &[0x81]),
+            (126, &[0xfe]),
+            (127, &[0x40, 0x7f]),
+            (MAX_VALUE, &[0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe]),
+        ] {
+            let mut buf = Vec::new();
+            assert_eq!(write_vint(value, &mut buf).unwrap(), bytes.len());
+            assert_eq!(buf, bytes);
+            assert_eq!(read_vint(&mut &buf[..]).unwrap(), (value, bytes.len()));
+        }
+        assert!(write_vint(MAX_VALUE + 1, &mut Vec::new()).is_err());
+        assert!(write_vint_with_len(127, 1, &mut Vec::new()).is_err());
+        assert!(matches!(
+            read_vint(&mut &[0x00, 0x81][..]),
+            Err(FromLeb128Error::TooLong)
+        ));
+
+        let mut buf = Vec::new();
+        write_vint_with_len(1, 4, &mut buf).unwrap();
+        write_element_size(None, &mut buf).unwrap();
+        buf.extend([0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(buf[..5], [0x10, 0x00, 0x00, 0x01, 0xff]);
+        let mut reader = &buf[..];
+        assert_eq!(read_element_size(&mut reader).unwrap(), Some(1));
+        assert_eq!(read_element_size(&mut reader).unwrap(), None);
+        assert_eq!(read_element_size(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn element_ids() {
+        let mut buf = Vec::new();
+        for id in [0x1a45dfa3, 0x4286, 0xec] {
+            write_element_id(id, &mut buf).unwrap();
+        }
+        assert_eq!(buf, [0x1a, 0x45, 0xdf, 0xa3, 0x42, 0x86, 0xec]);
+        let mut reader = &buf[..];
+        assert_eq!(read_element_id(&mut reader).unwrap(), 0x1a45dfa3);
+        assert_eq!(read_element_id(&mut reader).unwrap(), 0x4286);
+        assert_eq!(read_element_id(&mut reader).unwrap(), 0xec);
+
+        for id in [0, 0x7f, 0x1fff, 0xff] {
+            assert!(write_element_id(id, &mut Vec::new()).is_err());
+        }
+        assert!(matches!(
+            read_element_id(&mut &[0x08, 0, 0, 0, 1][..]),
+            Err(FromLeb128Error::TooLong)
+        ));
+        assert!(matches!(
+            read_element_id(&mut &[0x80][..]),
+            Err(FromLeb128Error::Malformed)
+        ));
+    }
+}
@@ -0,0 +1,198 @@
+//! Encoding and decoding over the `embedded-io` traits, and with the
+//! `embedded-io-async` feature over their async counterparts.
+
+use core::fmt;
+use core::mem::MaybeUninit;
+
+use embedded_io::{Read, ReadExactError, Write};
+#[cfg(feature = "embedded-io-async")]
+use embedded_io_async::{Read as AsyncRead, Write as AsyncWrite};
+
+use crate::pending::{Pending, SCRATCH_LEN};
+use crate::{EncodeError, FromLeb128Error, FromLeb128i, FromLeb128u, ToLeb128i, ToLeb128u};
+
+/// Failure of an `embedded-io` encode or decode, generic over the transport
+/// error `E`.
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error<E> {
+    /// The value's encoding is longer than the scratch buffer.
+    Encode(EncodeError),
+    /// The bytes read are not a valid value.
+    Decode(FromLeb128Error),
+    /// The transport failed.
+    Io(E),
+}
+
+impl<E: fmt::Debug> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Encode(e) => write!(f, "{}", e),
+            Error::Decode(e) => write!(f, "{}", e),
+            Error::Io(e) => write!(f, "transport error: {:?}", e),
+        }
+    }
+}
+
+impl<E: core::error::Error> core::error::Error for Error<E> {}
+
+impl<E> From<ReadExactError<E>> for Error<E> {
+    fn from(value: ReadExactError<E>) -> Self {
+        match value {
+            ReadExactError::UnexpectedEof => Error::Decode(FromLeb128Error::UnexpectedEof),
+            ReadExactError::Other(e) => Error::Io(e),
+        }
+    }
+}
+
+pub fn write_u<T: ToLeb128u, W: Write>(
+    value: &T,
+    writer: &mut W,
+) -> Result<usize, Error<W::Error>> {
+    let mut buf = [MaybeUninit::uninit(); SCRATCH_LEN];
+    let bytes = value.to_leb128u_uninit(&mut buf).map_err(Error::Encode)?;
+    writer.write_all(bytes).map_err(Error::Io)?;
+    Ok(bytes.len())
+}
+
+pub fn write_i<T: ToLeb128i, W: Write>(
+    value: &T,
+    writer: &mut W,
+) -> Result<usize, Error<W::Error>> {
+    let mut buf = [MaybeUninit::uninit(); SCRATCH_LEN];
+    let bytes = value.to_leb128i_uninit(&mut buf).map_err(Error::Encode)?;
+    writer.write_all(bytes).map_err(Error::Io)?;
+    Ok(bytes.len())
+}
+
+pub fn read_u<T: FromLeb128u, R: Read>(reader: &mut R) -> Result<T, Error<R::Error>> {
+    let mut pending = Pending::new();
+
+    loop {
+        reader.read_exact(pending.slot().map_err(Error::Decode)?)?;
+        if let Some(result) = pending.advance(T::from_leb128u_slice) {
+            return result.map_err(Error::Decode);
+        }
+    }
+}
+
+pub fn read_i<T: FromLeb128i, R: Read>(reader: &mut R) -> Result<T, Error<R::Error>> {
+    let mut pending = Pending::new();
+
+    loop {
+        reader.read_exact(pending.slot().map_err(Error::Decode)?)?;
+        if let Some(result) = pending.advance(T::from_leb128i_slice) {
+            return result.map_err(Error::Decode);
+        }
+    }
+}
+
+#[cfg(feature = "embedded-io-async")]
+pub async fn write_u_async<T: ToLeb128u, W: AsyncWrite>(
+    value: &T,
+    writer: &mut W,
+) -> Result<usize, Error<W::Error>> {
+    let mut buf = [MaybeUninit::uninit(); SCRATCH_LEN];
+    let bytes = value.to_leb128u_uninit(&mut buf).map_err(Error::Encode)?;
+    writer.write_all(bytes).await.map_err(Error::Io)?;
+    Ok(bytes.len())
+}
+
+#[cfg(feature = "embedded-io-async")]
+pub async fn write_i_async<T: ToLeb128i, W: AsyncWrite>(
+    value: &T,
+    writer: &mut W,
+) -> Result<usize, Error<W::Error>> {
+    let mut buf = [MaybeUninit::uninit(); SCRATCH_LEN];
+    let bytes = value.to_leb128i_uninit(&mut buf).map_err(Error::Encode)?;
+    writer.write_all(bytes).await.map_err(Error::Io)?;
+    Ok(bytes.len())
+}
+
+#[cfg(feature = "embedded-io-async")]
+pub async fn read_u_async<T: FromLeb128u, R: AsyncRead>(
+    reader: &mut R,
+) -> Result<T, Error<R::Error>> {
+    let mut pending = Pending::new();
+
+    loop {
+        reader
+            .read_exact(pending.slot().map_err(Error::Decode)?)
+            .await?;
+        if let Some(result) = pending.advance(T::from_leb128u_slice) {
+            return result.map_err(Error::Decode);
+        }
+    }
+}
+
+#[cfg(feature = "embedded-io-async")]
+pub async fn read_i_async<T: FromLeb128i, R: AsyncRead>(
+    reader: &mut R,
+) -> Result<T, Error<R::Error>> {
+    let mut pending = Pending::new();
+
+    loop {
+        reader
+            .read_exact(pending.slot().map_err(Error::Decode)?)
+            .await?;
+        if let Some(result) = pending.advance(T::from_leb128i_slice) {
+            return result.map_err(Error::Decode);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut storage = [0; 16];
+        let mut writer = &mut storage[..];
+        assert_eq!(write_u(&300u32, &mut writer).unwrap(), 2);
+        assert_eq!(write_i(&-65i64, &mut writer).unwrap(), 2);
+        assert_eq!(write_u(&(1u8, 128u16), &mut writer).unwrap(), 3);
+        assert_eq!(storage[..7], [0xac, 0x02, 0xbf, 0x7f, 0x01, 0x80, 0x01]);
+
+        let mut reader = &storage[..7];
+        assert_eq!(read_u::<u32, _>(&mut reader).unwrap(), 300);
+        assert_eq!(read_i::<i64, _>(&mut reader).unwrap(), -65);
+        assert_eq!(read_u::<(u8, u16), _>(&mut reader).unwrap(), (1, 128));
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            read_u::<u8, _>(&mut &[0x80][..]),
+            Err(Error::Decode(FromLeb128Error::UnexpectedEof))
+        ));
+        assert!(matches!(
+            read_u::<u8, _>(&mut &[0x80, 0x02][..]),
+            Err(Error::Decode(FromLeb128Error::Overflow))
+        ));
+        assert!(matches!(
+            write_u(&u64::MAX, &mut &mut [0; 4][..]),
+            Err(Error::Io(_))
+        ));
+    }
+
+    #[cfg(feature = "embedded-io-async")]
+    #[test]
+    fn round_trip_async() {
+        use crate::test_util::ready;
+
+        let mut storage = [0; 8];
+        let mut writer = &mut storage[..];
+        assert_eq!(ready(write_u_async(&300u32, &mut writer)).unwrap(), 2);
+        assert_eq!(ready(write_i_async(&-65i64, &mut writer)).unwrap(), 2);
+
+        let mut reader = &storage[..4];
+        assert_eq!(ready(read_u_async::<u32, _>(&mut reader)).unwrap(), 300);
+        assert_eq!(ready(read_i_async::<i64, _>(&mut reader)).unwrap(), -65);
+        assert!(matches!(
+            ready(read_u_async::<u32, _>(&mut reader)),
+            Err(Error::Decode(FromLeb128Error::UnexpectedEof))
+        ));
+    }
+}
@@ -0,0 +1,116 @@
+//! Extension traits putting the LEB128 methods on readers and writers, e.g.
+//! `writer.write_leb128_u32(300)?` and `reader.read_leb128_u32()?`.
+
+use std::io::{self, BufRead, Read, Write};
+
+use crate::{scan, FromLeb128Error, FromLeb128i, FromLeb128u, ToLeb128i, ToLeb128u};
+
+macro_rules! writers {
+    ($generic:ident: $($name:ident => $t:ty),*) => {
+        $(
+            #[doc = concat!("Writes a `", stringify!($t), "` and returns the number of bytes written.")]
+            fn $name(&mut self, value: $t) -> io::Result<usize> {
+                self.$generic(&value)
+            }
+        )*
+    };
+}
+
+/// LEB128 writing for any [`Write`].
+pub trait WriteLeb128Ext: Write {
+    fn write_uleb128<T: ToLeb128u + ?Sized>(&mut self, value: &T) -> io::Result<usize> {
+        value.to_leb128u(self)
+    }
+
+    fn write_sleb128<T: ToLeb128i + ?Sized>(&mut self, value: &T) -> io::Result<usize> {
+        value.to_leb128i(self)
+    }
+
+    writers!(write_uleb128: write_leb128_u8 => u8, write_leb128_u16 => u16, write_leb128_u32 => u32, write_leb128_u64 => u64, write_leb128_u128 => u128, write_leb128_usize => usize);
+    writers!(write_sleb128: write_leb128_i8 => i8, write_leb128_i16 => i16, write_leb128_i32 => i32, write_leb128_i64 => i64, write_leb128_i128 => i128, write_leb128_isize => isize);
+}
+
+impl<W: Write + ?Sized> WriteLeb128Ext for W {}
+
+macro_rules! readers {
+    ($generic:ident: $($name:ident => $t:ty),*) => {
+        $(
+            #[doc = concat!("Reads a `", stringify!($t), "`.")]
+            fn $name(&mut self) -> Result<$t, FromLeb128Error> {
+                self.$generic()
+            }
+        )*
+    };
+}
+
+/// LEB128 reading for any [`Read`].
+pub trait ReadLeb128Ext: Read {
+    fn read_uleb128<T: FromLeb128u>(&mut self) -> Result<T, FromLeb128Error> {
+        T::from_leb128u(self)
+    }
+
+    fn read_sleb128<T: FromLeb128i>(&mut self) -> Result<T, FromLeb128Error> {
+        T::from_leb128i(self)
+    }
+
+    readers!(read_uleb128: read_leb128_u8 => u8, read_leb128_u16 => u16, read_leb128_u32 => u32, read_leb128_u64 => u64, read_leb128_u128 => u128, read_leb128_usize => usize);
+    readers!(read_sleb128: read_leb128_i8 => i8, read_leb128_i16 => i16, read_leb128_i32 => i32, read_leb128_i64 => i64, read_leb128_i128 => i128, read_leb128_isize => isize);
+}
+
+impl<R: Read + ?Sized> ReadLeb128Ext for R {}
+
+/// LEB128 seeking for any [`BufRead`].
+pub trait BufReadLeb128Ext: BufRead {
+    /// Consumes the next `n` values without decoding them and returns the
+    /// number of bytes skipped; see [`scan::skip_values_buf`].
+    fn seek_values(&mut self, n: u64) -> Result<u64, FromLeb128Error> {
+        scan::skip_values_buf(self, n)
+    }
+}
+
+impl<R: BufRead + ?Sized> BufReadLeb128Ext for R {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_ext() {
+        let mut buf = Vec::new();
+        assert_eq!(buf.write_leb128_u32(300).unwrap(), 2);
+        assert_eq!(buf.write_leb128_i64(-65).unwrap(), 2);
+        assert_eq!(buf.write_sleb128(&(1i8, -1i16)).unwrap(), 2);
+
+        let writer: &mut dyn Write = &mut buf;
+        assert_eq!(writer.write_leb128_usize(0).unwrap(), 1);
+        assert_eq!(buf, [0xac, 0x02, 0xbf, 0x7f, 0x01, 0x7f, 0x00]);
+    }
+
+    #[test]
+    fn read_ext() {
+        let mut reader = &[0xac, 0x02, 0xbf, 0x7f, 0x01, 0x7f, 0x00][..];
+        assert_eq!(reader.read_leb128_u32().unwrap(), 300);
+        assert_eq!(reader.read_leb128_i64().unwrap(), -65);
+        assert_eq!(reader.read_sleb128::<(i8, i16)>().unwrap(), (1, -1));
+
+        let reader: &mut dyn Read = &mut reader;
+        assert_eq!(reader.read_leb128_usize().unwrap(), 0);
+        assert!(matches!(
+            reader.read_leb128_u8(),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn seek_values() {
+        let mut reader = io::BufReader::with_capacity(3, &[0x01, 0xac, 0x02, 0x80, 0x01, 0x05][..]);
+        assert_eq!(reader.seek_values(3).unwrap(), 5);
+        assert_eq!(reader.read_leb128_u8().unwrap(), 5);
+
+        let reader: &mut dyn BufRead = &mut reader;
+        assert!(matches!(
+            reader.seek_values(1),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+    }
+}
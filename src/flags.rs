@@ -0,0 +1,161 @@
+use std::io::{self, Read, Write};
+
+use bitflags::Flags;
+
+use crate::{FromLeb128Error, FromLeb128u, ToLeb128u};
+
+/// How bits that do not correspond to a defined flag are handled on decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownBits {
+    /// Fail with `FromLeb128Error::Malformed`.
+    #[default]
+    Reject,
+    /// Drop unknown bits (`from_bits_truncate`).
+    Truncate,
+    /// Keep unknown bits (`from_bits_retain`).
+    Retain,
+}
+
+pub fn to_leb128u<F: Flags>(flags: &F, writer: &mut (impl Write + ?Sized)) -> io::Result<usize>
+where
+    F::Bits: ToLeb128u,
+{
+    flags.bits().to_leb128u(writer)
+}
+
+/// Longest encoding of the flags type `F`.
+pub const fn max_leb128_len<F: Flags>() -> usize
+where
+    F::Bits: ToLeb128u,
+{
+    F::Bits::MAX_LEB128_LEN
+}
+
+pub fn from_leb128u<F: Flags>(
+    reader: &mut (impl Read + ?Sized),
+    unknown: UnknownBits,
+) -> Result<F, FromLeb128Error>
+where
+    F::Bits: FromLeb128u,
+{
+    from_bits(F::Bits::from_leb128u(reader)?, unknown)
+}
+
+/// Decodes from the front of `bytes` and returns the flags with the number
+/// of bytes consumed.
+pub fn from_leb128u_slice<F: Flags>(
+    bytes: &[u8],
+    unknown: UnknownBits,
+) -> Result<(F, usize), FromLeb128Error>
+where
+    F::Bits: FromLeb128u,
+{
+    let (bits, len) = F::Bits::from_leb128u_slice(bytes)?;
+    Ok((from_bits(bits, unknown)?, len))
+}
+
+fn from_bits<F: Flags>(bits: F::Bits, unknown: UnknownBits) -> Result<F, FromLeb128Error> {
+    match unknown {
+        UnknownBits::Reject => F::from_bits(bits).ok_or(FromLeb128Error::Malformed),
+        UnknownBits::Truncate => Ok(F::from_bits_truncate(bits)),
+        UnknownBits::Retain => Ok(F::from_bits_retain(bits)),
+    }
+}
+
+/// Implements `ToLeb128u` and `FromLeb128u` for a bitflags type.
+///
+/// The optional second argument is an `UnknownBits` variant (`Reject` by default).
+#[macro_export]
+macro_rules! impl_leb128_bitflags {
+    ($ty:ty) => {
+        $crate::impl_leb128_bitflags!($ty, Reject);
+    };
+    ($ty:ty, $unknown:ident) => {
+        impl $crate::ToLeb128u for $ty {
+            const MAX_LEB128_LEN: usize = $crate::flags::max_leb128_len::<$ty>();
+
+            fn to_leb128u(
+                &self,
+                writer: &mut (impl ::std::io::Write + ?Sized),
+            ) -> ::std::io::Result<usize> {
+                $crate::flags::to_leb128u(self, writer)
+            }
+
+            fn to_leb128u_uninit<'a>(
+                &self,
+                buf: &'a mut [::std::mem::MaybeUninit<u8>],
+            ) -> ::std::result::Result<&'a [u8], $crate::EncodeError> {
+                $crate::ToLeb128u::to_leb128u_uninit(&self.bits(), buf)
+            }
+        }
+
+        impl $crate::FromLeb128u for $ty {
+            fn from_leb128u(
+                reader: &mut (impl ::std::io::Read + ?Sized),
+            ) -> ::std::result::Result<Self, $crate::FromLeb128Error> {
+                $crate::flags::from_leb128u(reader, $crate::flags::UnknownBits::$unknown)
+            }
+
+            fn from_leb128u_slice(
+                bytes: &[u8],
+            ) -> ::std::result::Result<(Self, usize), $crate::FromLeb128Error> {
+                $crate::flags::from_leb128u_slice(bytes, $crate::flags::UnknownBits::$unknown)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    bitflags::bitflags! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        struct Perm: u16 {
+            const READ = 0b001;
+            const WRITE = 0b010;
+            const EXEC = 0b100;
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Lenient(u32);
+
+    bitflags::bitflags! {
+        impl Lenient: u32 {
+            const A = 1;
+        }
+    }
+
+    crate::impl_leb128_bitflags!(Perm);
+    crate::impl_leb128_bitflags!(Lenient, Truncate);
+
+    #[test]
+    fn policies() {
+        let mut buf = Vec::new();
+        (Perm::READ | Perm::EXEC).to_leb128u(&mut buf).unwrap();
+        assert_eq!(buf, vec![0x05]);
+        assert_eq!(
+            Perm::from_leb128u(&mut &buf[..]).unwrap(),
+            Perm::READ | Perm::EXEC
+        );
+
+        let unknown = [0x88, 0x01];
+        assert!(matches!(
+            Perm::from_leb128u(&mut &unknown[..]),
+            Err(FromLeb128Error::Malformed)
+        ));
+        assert_eq!(
+            from_leb128u::<Perm>(&mut &unknown[..], UnknownBits::Retain)
+                .unwrap()
+                .bits(),
+            0x88
+        );
+        assert_eq!(Lenient::from_leb128u(&mut &[0x03][..]).unwrap(), Lenient::A);
+        assert_eq!(
+            Perm::from_leb128u_slice(&[0x05, 0xff]).unwrap(),
+            (Perm::READ | Perm::EXEC, 1)
+        );
+        assert_eq!(Perm::MAX_LEB128_LEN, 3);
+    }
+}
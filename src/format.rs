@@ -0,0 +1,363 @@
+use std::io::{self, Read, Write};
+
+use crate::{
+    compact_size, ebml, minecraft, multiformats, protobuf, quic, smile, stream, zigzag,
+    FromLeb128Error, FromLeb128i, FromLeb128u, ToLeb128i, ToLeb128u,
+};
+
+/// A variable-length integer encoding.
+///
+/// Generic utilities take a format as a type parameter, e.g. `transcode::<Sleb128, Zigzag>`.
+pub trait VarintFormat {
+    type Value: Copy;
+
+    /// Maximum number of bytes a single value can occupy.
+    const MAX_LEN: usize;
+
+    fn encode(value: Self::Value, writer: &mut (impl Write + ?Sized)) -> io::Result<usize>;
+
+    fn decode(reader: &mut (impl Read + ?Sized)) -> Result<Self::Value, FromLeb128Error>;
+
+    /// Number of bytes `encode` writes for `value`, or `None` if the format
+    /// cannot represent it.
+    fn encoded_len(value: Self::Value) -> Option<usize>;
+
+    /// Decodes the next value, or returns `None` on a clean end of input.
+    fn decode_next(
+        reader: &mut (impl Read + ?Sized),
+    ) -> Result<Option<Self::Value>, FromLeb128Error> {
+        stream::read_next(reader, |r| Self::decode(r))
+    }
+}
+
+/// ULEB128 `u64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Uleb128;
+
+/// SLEB128 `i64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sleb128;
+
+/// Zigzag-mapped ULEB128 `i64`, as used by protobuf `sint64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Zigzag;
+
+/// Two's-complement `i64` as ULEB128 `u64`, as used by protobuf `int64`.
+///
+/// Negative values always take ten bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TwosComplement;
+
+/// ULEB128 `u32`, as used by Kafka `UNSIGNED_VARINT` and Thrift lengths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Uleb128U32;
+
+/// Zigzag-mapped ULEB128 `i32`, as used by Kafka `VARINT`, Avro `int` and
+/// Thrift `i32`.
+///
+/// Kafka `VARLONG`, Avro `long` and Thrift `i64` are [`Zigzag`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Zigzag32;
+
+/// Two's-complement `i32` as ULEB128 `u32`, as used by the Minecraft `VarInt`.
+///
+/// Negative values always take five bytes. The Minecraft `VarLong` is
+/// [`TwosComplement`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TwosComplement32;
+
+/// QUIC variable-length integers; see [`quic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quic;
+
+/// The multiformats unsigned-varint; see [`multiformats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsignedVarint;
+
+/// Bitcoin CompactSize integers; see [`compact_size`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompactSize;
+
+/// EBML variable-size integers; see [`ebml`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EbmlVint;
+
+/// Jackson Smile `VInt`s as `u64`; see [`smile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Smile;
+
+impl VarintFormat for Uleb128 {
+    type Value = u64;
+
+    const MAX_LEN: usize = <u64 as ToLeb128u>::MAX_LEB128_LEN;
+
+    fn encode(value: u64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        value.to_leb128u(writer)
+    }
+
+    fn decode(reader: &mut (impl Read + ?Sized)) -> Result<u64, FromLeb128Error> {
+        u64::from_leb128u(reader)
+    }
+
+    fn encoded_len(value: u64) -> Option<usize> {
+        Some(value.leb128u_len())
+    }
+}
+
+impl VarintFormat for Sleb128 {
+    type Value = i64;
+
+    const MAX_LEN: usize = <i64 as ToLeb128i>::MAX_LEB128_LEN;
+
+    fn encode(value: i64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        value.to_leb128i(writer)
+    }
+
+    fn decode(reader: &mut (impl Read + ?Sized)) -> Result<i64, FromLeb128Error> {
+        i64::from_leb128i(reader)
+    }
+
+    fn encoded_len(value: i64) -> Option<usize> {
+        Some(value.leb128i_len())
+    }
+}
+
+impl VarintFormat for Zigzag {
+    type Value = i64;
+
+    const MAX_LEN: usize = <u64 as ToLeb128u>::MAX_LEB128_LEN;
+
+    fn encode(value: i64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        zigzag::encode_i64(value).to_leb128u(writer)
+    }
+
+    fn decode(reader: &mut (impl Read + ?Sized)) -> Result<i64, FromLeb128Error> {
+        u64::from_leb128u(reader).map(zigzag::decode_i64)
+    }
+
+    fn encoded_len(value: i64) -> Option<usize> {
+        Some(zigzag::encode_i64(value).leb128u_len())
+    }
+}
+
+impl VarintFormat for TwosComplement {
+    type Value = i64;
+
+    const MAX_LEN: usize = <u64 as ToLeb128u>::MAX_LEB128_LEN;
+
+    fn encode(value: i64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        protobuf::encode_int64(value, writer)
+    }
+
+    fn decode(reader: &mut (impl Read + ?Sized)) -> Result<i64, FromLeb128Error> {
+        protobuf::decode_int64(reader)
+    }
+
+    fn encoded_len(value: i64) -> Option<usize> {
+        Some(protobuf::int64_len(value))
+    }
+}
+
+impl VarintFormat for Uleb128U32 {
+    type Value = u32;
+
+    const MAX_LEN: usize = <u32 as ToLeb128u>::MAX_LEB128_LEN;
+
+    fn encode(value: u32, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        value.to_leb128u(writer)
+    }
+
+    fn decode(reader: &mut (impl Read + ?Sized)) -> Result<u32, FromLeb128Error> {
+        u32::from_leb128u(reader)
+    }
+
+    fn encoded_len(value: u32) -> Option<usize> {
+        Some(value.leb128u_len())
+    }
+}
+
+impl VarintFormat for Zigzag32 {
+    type Value = i32;
+
+    const MAX_LEN: usize = <u32 as ToLeb128u>::MAX_LEB128_LEN;
+
+    fn encode(value: i32, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        zigzag::encode_i32(value).to_leb128u(writer)
+    }
+
+    fn decode(reader: &mut (impl Read + ?Sized)) -> Result<i32, FromLeb128Error> {
+        u32::from_leb128u(reader).map(zigzag::decode_i32)
+    }
+
+    fn encoded_len(value: i32) -> Option<usize> {
+        Some(zigzag::encode_i32(value).leb128u_len())
+    }
+}
+
+impl VarintFormat for TwosComplement32 {
+    type Value = i32;
+
+    const MAX_LEN: usize = <u32 as ToLeb128u>::MAX_LEB128_LEN;
+
+    fn encode(value: i32, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        minecraft::write_varint(value, writer)
+    }
+
+    fn decode(reader: &mut (impl Read + ?Sized)) -> Result<i32, FromLeb128Error> {
+        minecraft::read_varint(reader)
+    }
+
+    fn encoded_len(value: i32) -> Option<usize> {
+        Some(minecraft::varint_len(value))
+    }
+}
+
+impl VarintFormat for Quic {
+    type Value = u64;
+
+    const MAX_LEN: usize = 8;
+
+    fn encode(value: u64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        quic::encode(value, writer)
+    }
+
+    fn decode(reader: &mut (impl Read + ?Sized)) -> Result<u64, FromLeb128Error> {
+        quic::decode(reader)
+    }
+
+    fn encoded_len(value: u64) -> Option<usize> {
+        quic::encoded_len(value)
+    }
+}
+
+impl VarintFormat for UnsignedVarint {
+    type Value = u64;
+
+    const MAX_LEN: usize = multiformats::MAX_LEN;
+
+    fn encode(value: u64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        multiformats::encode(value, writer)
+    }
+
+    fn decode(reader: &mut (impl Read + ?Sized)) -> Result<u64, FromLeb128Error> {
+        multiformats::decode(reader)
+    }
+
+    fn encoded_len(value: u64) -> Option<usize> {
+        (value <= multiformats::MAX_VALUE).then(|| value.leb128u_len())
+    }
+}
+
+impl VarintFormat for CompactSize {
+    type Value = u64;
+
+    const MAX_LEN: usize = compact_size::MAX_LEN;
+
+    fn encode(value: u64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        compact_size::encode(value, writer)
+    }
+
+    fn decode(reader: &mut (impl Read + ?Sized)) -> Result<u64, FromLeb128Error> {
+        compact_size::decode(reader)
+    }
+
+    fn encoded_len(value: u64) -> Option<usize> {
+        Some(compact_size::encoded_len(value))
+    }
+}
+
+impl VarintFormat for EbmlVint {
+    type Value = u64;
+
+    const MAX_LEN: usize = ebml::MAX_LEN;
+
+    fn encode(value: u64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        ebml::write_vint(value, writer)
+    }
+
+    fn decode(reader: &mut (impl Read + ?Sized)) -> Result<u64, FromLeb128Error> {
+        ebml::read_vint(reader).map(|(value, _)| value)
+    }
+
+    fn encoded_len(value: u64) -> Option<usize> {
+        ebml::vint_len(value)
+    }
+}
+
+impl VarintFormat for Smile {
+    type Value = u64;
+
+    const MAX_LEN: usize = 1 + (u64::BITS as usize - 6).div_ceil(7);
+
+    fn encode(value: u64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        smile::encode_u64(value, writer)
+    }
+
+    fn decode(reader: &mut (impl Read + ?Sized)) -> Result<u64, FromLeb128Error> {
+        smile::decode_u64(reader)
+    }
+
+    fn encoded_len(value: u64) -> Option<usize> {
+        // Six bits in the last byte, seven in each one before it.
+        Some(1 + (64 - (value >> 6).leading_zeros() as usize).div_ceil(7))
+    }
+}
+
+/// Total size of `values` encoded in format `F`, or `None` if the format
+/// cannot represent one of them.
+pub fn total_encoded_len<F: VarintFormat>(values: &[F::Value]) -> Option<usize> {
+    values.iter().map(|&v| F::encoded_len(v)).sum()
+}
+
+/// Total size of `values` encoded as ULEB128.
+pub fn encoded_len_of_slice(values: &[u64]) -> usize {
+    values.iter().map(|v| v.leb128u_len()).sum()
+}
+
+/// Total size of `values` encoded as SLEB128.
+pub fn encoded_len_of_slice_i64(values: &[i64]) -> usize {
+    values.iter().map(|v| v.leb128i_len()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_len<F: VarintFormat>(values: &[F::Value]) {
+        for &v in values {
+            let mut buf = Vec::new();
+            assert_eq!(Some(F::encode(v, &mut buf).unwrap()), F::encoded_len(v));
+            assert!(buf.len() <= F::MAX_LEN);
+        }
+    }
+
+    #[test]
+    fn encoded_len_matches_encode() {
+        check_len::<Uleb128>(&[0, 127, 128, 16383, 16384, u64::MAX]);
+        check_len::<Sleb128>(&[0, 63, 64, -64, -65, i64::MIN, i64::MAX]);
+        check_len::<Zigzag>(&[0, -1, 63, -64, 64, i64::MIN, i64::MAX]);
+        check_len::<TwosComplement>(&[0, -1, 127, 128, i64::MIN, i64::MAX]);
+        check_len::<Uleb128U32>(&[0, 127, 128, u32::MAX]);
+        check_len::<Zigzag32>(&[0, -1, 63, -64, 64, i32::MIN, i32::MAX]);
+        check_len::<TwosComplement32>(&[0, -1, 127, 128, i32::MIN, i32::MAX]);
+        check_len::<Quic>(&[0, 63, 64, 16383, 16384, quic::MAX_VALUE]);
+        check_len::<UnsignedVarint>(&[0, 127, 128, multiformats::MAX_VALUE]);
+        check_len::<CompactSize>(&[0, 0xfc, 0xfd, 0xffff, 0x10000, u64::MAX]);
+        check_len::<EbmlVint>(&[0, 126, 127, ebml::MAX_VALUE]);
+        check_len::<Smile>(&[0, 63, 64, 8191, 8192, u64::MAX]);
+    }
+
+    #[test]
+    fn slice_len() {
+        assert_eq!(
+            encoded_len_of_slice(&[0, 127, 128, u64::MAX]),
+            1 + 1 + 2 + 10
+        );
+        assert_eq!(
+            encoded_len_of_slice_i64(&[0, -64, 64, i64::MIN]),
+            1 + 1 + 2 + 10
+        );
+        assert_eq!(total_encoded_len::<Zigzag>(&[-64, 64]), Some(1 + 2));
+        assert_eq!(total_encoded_len::<Quic>(&[0, quic::MAX_VALUE + 1]), None);
+    }
+}
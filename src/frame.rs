@@ -0,0 +1,165 @@
+//! Length-delimited message framing: each frame is its length as ULEB128
+//! followed by the payload.
+//!
+//! Both ends take a maximum frame length, so that a corrupt or hostile length
+//! prefix is rejected before any payload is buffered.
+
+use std::io::{self, Read, Write};
+
+use crate::string::{self, write_leb128_bytes};
+use crate::{stream, FromLeb128Error};
+
+/// Writes length-prefixed frames.
+#[derive(Debug)]
+pub struct FrameWriter<W> {
+    writer: W,
+    max_frame_len: usize,
+}
+
+impl<W: Write> FrameWriter<W> {
+    pub fn new(writer: W, max_frame_len: usize) -> Self {
+        Self {
+            writer,
+            max_frame_len,
+        }
+    }
+
+    /// Writes `payload` as one frame and returns the number of bytes written.
+    ///
+    /// Fails with `io::ErrorKind::InvalidInput` if `payload` is longer than the
+    /// maximum frame length.
+    pub fn write_frame(&mut self, payload: &[u8]) -> io::Result<usize> {
+        if payload.len() > self.max_frame_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "frame too large",
+            ));
+        }
+
+        write_leb128_bytes(&mut self.writer, payload)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<W> FrameWriter<W> {
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads length-prefixed frames.
+#[derive(Debug)]
+pub struct FrameReader<R> {
+    reader: R,
+    max_frame_len: usize,
+}
+
+impl<R: Read> FrameReader<R> {
+    pub fn new(reader: R, max_frame_len: usize) -> Self {
+        Self {
+            reader,
+            max_frame_len,
+        }
+    }
+
+    /// Reads the next frame, or returns `None` on a clean end of input.
+    ///
+    /// A length above the maximum fails with an `io::ErrorKind::InvalidData`
+    /// error before the payload is read.
+    pub fn read_frame(&mut self) -> Result<Option<Vec<u8>>, FromLeb128Error> {
+        let mut buf = Vec::new();
+        Ok(self.read_frame_into(&mut buf)?.then_some(buf))
+    }
+
+    /// Reads the next frame into `buf`, replacing its contents but reusing its
+    /// allocation. Returns `false` on a clean end of input.
+    pub fn read_frame_into(&mut self, buf: &mut Vec<u8>) -> Result<bool, FromLeb128Error> {
+        let len = match stream::read_u::<u64>(&mut self.reader)? {
+            Some(len) => len,
+            None => return Ok(false),
+        };
+
+        if len > self.max_frame_len as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "frame too large").into());
+        }
+
+        string::read_exact_into(&mut self.reader, len, buf)?;
+        Ok(true)
+    }
+}
+
+impl<R> FrameReader<R> {
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> Iterator for FrameReader<R> {
+    type Item = Result<Vec<u8>, FromLeb128Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_frame().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut writer = FrameWriter::new(Vec::new(), 1024);
+        assert_eq!(writer.write_frame(&[7; 300]).unwrap(), 302);
+        assert_eq!(writer.write_frame(b"").unwrap(), 1);
+        writer.write_frame(b"hi").unwrap();
+        assert!(writer.write_frame(&[0; 1025]).is_err());
+
+        let wire = writer.into_inner();
+        let frames = FrameReader::new(&wire[..], 1024)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(frames, vec![vec![7; 300], vec![], b"hi".to_vec()]);
+    }
+
+    #[test]
+    fn rejects_large_frames() {
+        let mut reader = FrameReader::new(&[0x05, 1, 2, 3, 4, 5][..], 4);
+        match reader.read_frame() {
+            Err(FromLeb128Error::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::InvalidData),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let mut truncated = FrameReader::new(&[0x03, 1][..], 4);
+        assert!(matches!(
+            truncated.read_frame(),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+    }
+}
@@ -0,0 +1,249 @@
+//! Front-coded dictionaries of sorted byte strings.
+//!
+//! Each entry is stored as the length of the prefix it shares with the
+//! previous entry, the suffix length and the suffix bytes. Every
+//! `bucket_size`-th entry is stored in full so lookups can binary search the
+//! bucket heads and only scan one bucket.
+
+use std::io::{self, Read, Write};
+
+use crate::string;
+use crate::{FromLeb128Error, FromLeb128u, ToLeb128u};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontCodedDict {
+    data: Vec<u8>,
+    buckets: Vec<usize>,
+    len: usize,
+    bucket_size: usize,
+}
+
+impl FrontCodedDict {
+    /// Builds a dictionary from strictly increasing strings.
+    ///
+    /// Fails with `io::ErrorKind::InvalidInput` if the input is not sorted or
+    /// `bucket_size` is zero.
+    pub fn build<I>(strings: I, bucket_size: usize) -> io::Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        if bucket_size == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "bucket size is zero",
+            ));
+        }
+
+        let mut dict = Self {
+            data: Vec::new(),
+            buckets: Vec::new(),
+            len: 0,
+            bucket_size,
+        };
+        let mut prev = Vec::new();
+
+        for s in strings {
+            let s = s.as_ref();
+
+            if dict.len > 0 && s <= &prev[..] {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "strings are not sorted",
+                ));
+            }
+
+            let shared = if dict.len.is_multiple_of(bucket_size) {
+                dict.buckets.push(dict.data.len());
+                0
+            } else {
+                prev.iter().zip(s).take_while(|(a, b)| a == b).count()
+            };
+
+            (shared as u64).to_leb128u(&mut dict.data)?;
+            string::write_leb128_bytes(&mut dict.data, &s[shared..])?;
+
+            prev.clear();
+            prev.extend_from_slice(s);
+            dict.len += 1;
+        }
+
+        Ok(dict)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            data: &self.data,
+            current: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<Vec<u8>> {
+        if index >= self.len {
+            return None;
+        }
+
+        let bucket = index / self.bucket_size;
+        self.bucket_iter(bucket).nth(index % self.bucket_size)
+    }
+
+    /// Returns the index of `key`, if present.
+    pub fn find(&self, key: &[u8]) -> Option<usize> {
+        let bucket = match self
+            .buckets
+            .binary_search_by(|&offset| head(&self.data[offset..]).cmp(key))
+        {
+            Ok(bucket) => return Some(bucket * self.bucket_size),
+            Err(0) => return None,
+            Err(next) => next - 1,
+        };
+
+        self.bucket_iter(bucket)
+            .take(self.bucket_size)
+            .position(|s| s == key)
+            .map(|i| bucket * self.bucket_size + i)
+    }
+
+    fn bucket_iter(&self, bucket: usize) -> Iter<'_> {
+        Iter {
+            data: &self.data[self.buckets[bucket]..],
+            current: Vec::new(),
+        }
+    }
+
+    /// Writes the entry count, bucket size and entry data.
+    pub fn encode(&self, writer: &mut impl Write) -> io::Result<usize> {
+        Ok((self.len as u64).to_leb128u(writer)?
+            + (self.bucket_size as u64).to_leb128u(writer)?
+            + string::write_leb128_bytes(writer, &self.data)?)
+    }
+
+    pub fn decode(reader: &mut impl Read) -> Result<Self, FromLeb128Error> {
+        let len =
+            usize::try_from(u64::from_leb128u(reader)?).map_err(|_| FromLeb128Error::Overflow)?;
+        let bucket_size =
+            usize::try_from(u64::from_leb128u(reader)?).map_err(|_| FromLeb128Error::Overflow)?;
+        let data = string::read_leb128_bytes(reader)?;
+
+        if bucket_size == 0 {
+            return Err(FromLeb128Error::Malformed);
+        }
+
+        let mut buckets = Vec::new();
+        let mut rest = &data[..];
+        let mut prev_len = 0;
+        let mut count = 0usize;
+
+        while !rest.is_empty() {
+            let offset = data.len() - rest.len();
+            let shared = u64::from_leb128u(&mut rest)?;
+            let suffix = u64::from_leb128u(&mut rest)?;
+
+            let at_head = count.is_multiple_of(bucket_size);
+            if at_head && shared != 0 || shared > prev_len || suffix > rest.len() as u64 {
+                return Err(FromLeb128Error::Malformed);
+            }
+            if at_head {
+                buckets.push(offset);
+            }
+
+            rest = &rest[suffix as usize..];
+            prev_len = shared + suffix;
+            count += 1;
+        }
+
+        if count != len {
+            return Err(FromLeb128Error::Malformed);
+        }
+
+        Ok(Self {
+            data,
+            buckets,
+            len,
+            bucket_size,
+        })
+    }
+}
+
+/// The full string stored at the head of a bucket.
+fn head(mut data: &[u8]) -> &[u8] {
+    let _shared = u64::from_leb128u(&mut data).unwrap();
+    let len = u64::from_leb128u(&mut data).unwrap() as usize;
+    &data[..len]
+}
+
+/// Iterator over the strings of a [`FrontCodedDict`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    data: &'a [u8],
+    current: Vec<u8>,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Vec<u8>> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        // The data was validated when the dictionary was built or decoded.
+        let shared = u64::from_leb128u(&mut self.data).unwrap() as usize;
+        let len = u64::from_leb128u(&mut self.data).unwrap() as usize;
+        let (suffix, rest) = self.data.split_at(len);
+
+        self.current.truncate(shared);
+        self.current.extend_from_slice(suffix);
+        self.data = rest;
+
+        Some(self.current.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TERMS: [&str; 7] = [
+        "apple", "applet", "apply", "banana", "band", "bandana", "can",
+    ];
+
+    #[test]
+    fn build_and_search() {
+        let dict = FrontCodedDict::build(TERMS, 3).unwrap();
+
+        assert_eq!(dict.len(), 7);
+        assert_eq!(
+            dict.iter().collect::<Vec<_>>(),
+            TERMS
+                .iter()
+                .map(|t| t.as_bytes().to_vec())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(dict.get(5).unwrap(), b"bandana");
+        for (i, term) in TERMS.iter().enumerate() {
+            assert_eq!(dict.find(term.as_bytes()), Some(i));
+        }
+        assert_eq!(dict.find(b"aardvark"), None);
+        assert_eq!(dict.find(b"bandit"), None);
+        assert_eq!(dict.find(b"zebra"), None);
+
+        let mut buf = Vec::new();
+        dict.encode(&mut buf).unwrap();
+        assert_eq!(FrontCodedDict::decode(&mut &buf[..]).unwrap(), dict);
+    }
+
+    #[test]
+    fn rejects_unsorted() {
+        assert!(FrontCodedDict::build(["b", "a"], 4).is_err());
+        assert!(FrontCodedDict::build(["a", "a"], 4).is_err());
+    }
+}
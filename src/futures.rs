@@ -0,0 +1,6 @@
+//! Encoding and decoding over the `futures` `AsyncRead` and `AsyncWrite`
+//! traits.
+
+use futures_util::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+crate::async_io::async_io!();
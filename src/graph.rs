@@ -0,0 +1,160 @@
+//! Adjacency lists in the style of WebGraph.
+//!
+//! Each list is the node's degree, the first neighbor as a zigzag delta from
+//! the node id, and the remaining neighbors as gaps minus one from the
+//! previous neighbor, all as ULEB128.
+
+use std::io::{self, Read, Write};
+
+use crate::{zigzag, FromLeb128Error, FromLeb128u, ToLeb128u};
+
+/// Encodes the strictly increasing `neighbors` of `node`.
+pub fn encode_neighbors(
+    node: u64,
+    neighbors: &[u64],
+    writer: &mut impl Write,
+) -> io::Result<usize> {
+    let mut count = (neighbors.len() as u64).to_leb128u(writer)?;
+    let mut prev: Option<u64> = None;
+
+    for &n in neighbors {
+        let value = match prev {
+            None => zigzag::encode_i64(n.wrapping_sub(node) as i64),
+            Some(p) if n > p => n - p - 1,
+            Some(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "neighbors are not strictly increasing",
+                ))
+            }
+        };
+        count += value.to_leb128u(writer)?;
+        prev = Some(n);
+    }
+
+    Ok(count)
+}
+
+/// Encodes a whole graph as the node count followed by each node's list.
+pub fn encode_graph(adjacency: &[Vec<u64>], writer: &mut impl Write) -> io::Result<usize> {
+    let mut count = (adjacency.len() as u64).to_leb128u(writer)?;
+
+    for (node, neighbors) in adjacency.iter().enumerate() {
+        count += encode_neighbors(node as u64, neighbors, writer)?;
+    }
+
+    Ok(count)
+}
+
+pub fn decode_graph(reader: &mut impl Read) -> Result<Vec<Vec<u64>>, FromLeb128Error> {
+    let nodes = u64::from_leb128u(reader)?;
+    let mut adjacency = Vec::with_capacity(nodes.min(4096) as usize);
+
+    for node in 0..nodes {
+        let degree = u64::from_leb128u(reader)?;
+        let mut list = Vec::with_capacity(degree.min(4096) as usize);
+        let mut prev = None;
+
+        for _ in 0..degree {
+            let n = next_neighbor(node, prev, u64::from_leb128u(reader)?)?;
+            list.push(n);
+            prev = Some(n);
+        }
+
+        adjacency.push(list);
+    }
+
+    Ok(adjacency)
+}
+
+fn next_neighbor(node: u64, prev: Option<u64>, value: u64) -> Result<u64, FromLeb128Error> {
+    match prev {
+        None => Ok(node.wrapping_add(zigzag::decode_i64(value) as u64)),
+        Some(p) => p
+            .checked_add(value)
+            .and_then(|n| n.checked_add(1))
+            .ok_or(FromLeb128Error::Overflow),
+    }
+}
+
+/// Lazily decodes one node's neighbors from the front of `bytes`.
+pub fn neighbors(node: u64, mut bytes: &[u8]) -> Result<Neighbors<'_>, FromLeb128Error> {
+    let remaining = u64::from_leb128u(&mut bytes)?;
+
+    Ok(Neighbors {
+        bytes,
+        node,
+        remaining,
+        prev: None,
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct Neighbors<'a> {
+    bytes: &'a [u8],
+    node: u64,
+    remaining: u64,
+    prev: Option<u64>,
+}
+
+impl<'a> Neighbors<'a> {
+    /// The input following the neighbors consumed so far; once the iterator is
+    /// exhausted this is where the next node's list starts.
+    pub fn rest(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Number of neighbors not yet yielded.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+}
+
+impl Iterator for Neighbors<'_> {
+    type Item = Result<u64, FromLeb128Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let n = u64::from_leb128u(&mut self.bytes)
+            .and_then(|value| next_neighbor(self.node, self.prev, value));
+        match n {
+            Ok(n) => self.prev = Some(n),
+            Err(_) => self.remaining = 0,
+        }
+
+        Some(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let graph = vec![vec![1, 2, 3], vec![0], vec![], vec![1, 100, 101, u64::MAX]];
+        let mut buf = Vec::new();
+        encode_graph(&graph, &mut buf).unwrap();
+        assert_eq!(decode_graph(&mut &buf[..]).unwrap(), graph);
+
+        let mut list = neighbors(0, &buf[1..]).unwrap();
+        assert_eq!(
+            list.by_ref().collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![1, 2, 3]
+        );
+        let next = neighbors(1, list.rest()).unwrap();
+        assert_eq!(next.collect::<Result<Vec<_>, _>>().unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn compact_gaps() {
+        let mut buf = Vec::new();
+        encode_neighbors(10, &[9, 10, 11, 12], &mut buf).unwrap();
+        assert_eq!(buf, vec![4, 1, 0, 0, 0]);
+        assert!(encode_neighbors(0, &[2, 1], &mut Vec::new()).is_err());
+    }
+}
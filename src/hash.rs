@@ -0,0 +1,74 @@
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+
+use crate::format::VarintFormat;
+use crate::FromLeb128Error;
+
+/// Feeds decoded values, rather than their encoded bytes, into a `Hasher`.
+///
+/// Two streams that encode the same values differently (for example padded
+/// and minimal encodings) produce the same digest.
+#[derive(Debug, Clone, Default)]
+pub struct Leb128Hasher<H> {
+    hasher: H,
+    count: u64,
+}
+
+impl<H: Hasher> Leb128Hasher<H> {
+    pub fn new(hasher: H) -> Self {
+        Self { hasher, count: 0 }
+    }
+
+    /// Hashes every value in `reader`, returning how many were read.
+    pub fn update<F>(&mut self, mut reader: impl Read) -> Result<u64, FromLeb128Error>
+    where
+        F: VarintFormat,
+        F::Value: Hash,
+    {
+        let mut count = 0;
+
+        while let Some(value) = F::decode_next(&mut reader)? {
+            value.hash(&mut self.hasher);
+            count += 1;
+        }
+
+        self.count += count;
+        Ok(count)
+    }
+
+    /// Total number of values hashed so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.hasher.finish()
+    }
+
+    pub fn into_inner(self) -> H {
+        self.hasher
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Uleb128;
+    use std::collections::hash_map::DefaultHasher;
+
+    #[test]
+    fn padded_and_minimal_match() {
+        let minimal = [0xac, 0x02, 0x01];
+        let padded = [0xac, 0x82, 0x80, 0x00, 0x81, 0x00];
+
+        let mut a = Leb128Hasher::new(DefaultHasher::new());
+        let mut b = Leb128Hasher::new(DefaultHasher::new());
+        assert_eq!(a.update::<Uleb128>(&minimal[..]).unwrap(), 2);
+        assert_eq!(b.update::<Uleb128>(&padded[..]).unwrap(), 2);
+        assert_eq!(a.finish(), b.finish());
+
+        let mut c = Leb128Hasher::new(DefaultHasher::new());
+        c.update::<Uleb128>(&[0x01, 0x01][..]).unwrap();
+        assert_ne!(a.finish(), c.finish());
+    }
+}
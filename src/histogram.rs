@@ -0,0 +1,130 @@
+use std::io::{self, Read, Write};
+
+use crate::{FromLeb128Error, FromLeb128u, ToLeb128u};
+
+/// A snapshot of a bucketed histogram.
+///
+/// Encoded as the bucket count, the bucket upper bounds as ULEB128 deltas (the
+/// first one absolute), then the per-bucket counts.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Histogram {
+    /// Strictly increasing inclusive upper bound of each bucket.
+    pub bounds: Vec<u64>,
+    pub counts: Vec<u64>,
+}
+
+impl Histogram {
+    pub fn new() -> Self {
+        Self {
+            bounds: Vec::new(),
+            counts: Vec::new(),
+        }
+    }
+
+    /// Number of buckets.
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    /// Sum of all bucket counts.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().fold(0, |acc, &c| acc.saturating_add(c))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.bounds.iter().copied().zip(self.counts.iter().copied())
+    }
+
+    /// Fails with `io::ErrorKind::InvalidInput` if the bounds are not strictly
+    /// increasing or do not match the number of counts.
+    pub fn encode(&self, writer: &mut impl Write) -> io::Result<usize> {
+        if self.bounds.len() != self.counts.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "bounds and counts differ in length",
+            ));
+        }
+
+        let mut buf = Vec::with_capacity(self.bounds.len() * 3 + 10);
+        (self.bounds.len() as u64).to_leb128u(&mut buf)?;
+
+        let mut prev = None;
+        for &bound in &self.bounds {
+            let delta = match prev {
+                None => bound,
+                Some(p) if bound > p => bound - p,
+                Some(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "bounds are not strictly increasing",
+                    ))
+                }
+            };
+            delta.to_leb128u(&mut buf)?;
+            prev = Some(bound);
+        }
+
+        for count in &self.counts {
+            count.to_leb128u(&mut buf)?;
+        }
+
+        writer.write_all(&buf)?;
+        Ok(buf.len())
+    }
+
+    pub fn decode(reader: &mut impl Read) -> Result<Self, FromLeb128Error> {
+        let len = u64::from_leb128u(reader)?;
+        let capacity = len.min(4096) as usize;
+        let mut bounds = Vec::with_capacity(capacity);
+        let mut counts = Vec::with_capacity(capacity);
+
+        let mut prev: Option<u64> = None;
+        for _ in 0..len {
+            let delta = u64::from_leb128u(reader)?;
+            let bound = match prev {
+                None => delta,
+                Some(_) if delta == 0 => return Err(FromLeb128Error::Malformed),
+                Some(p) => p.checked_add(delta).ok_or(FromLeb128Error::Overflow)?,
+            };
+            bounds.push(bound);
+            prev = Some(bound);
+        }
+
+        for _ in 0..len {
+            counts.push(u64::from_leb128u(reader)?);
+        }
+
+        Ok(Self { bounds, counts })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let h = Histogram {
+            bounds: vec![1, 2, 4, 8, 1 << 20, u64::MAX],
+            counts: vec![0, 17, 300, 1, 0, 2],
+        };
+        let mut buf = Vec::new();
+        let len = h.encode(&mut buf).unwrap();
+
+        assert_eq!(len, buf.len());
+        assert_eq!(&buf[..5], &[6, 1, 1, 2, 4]);
+        let decoded = Histogram::decode(&mut &buf[..]).unwrap();
+        assert_eq!(decoded, h);
+        assert_eq!(decoded.total(), 320);
+
+        let bad = Histogram {
+            bounds: vec![2, 1],
+            counts: vec![0, 0],
+        };
+        assert!(bad.encode(&mut Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,60 @@
+//! Arithmetic on encoded values without re-encoding the surrounding buffer.
+
+use std::io;
+
+use crate::{FromLeb128Error, FromLeb128u};
+
+/// Adds `delta` to the ULEB128 value at the start of `buf`, rewriting it in
+/// place with the same encoded length, and returns the new value.
+///
+/// A result that needs fewer bytes is written with padding continuation
+/// bytes. Fails with `io::ErrorKind::InvalidInput`, leaving `buf` unchanged,
+/// if the result is negative, overflows `u64`, or needs more bytes.
+pub fn increment_in_place(buf: &mut [u8], delta: i64) -> Result<u64, FromLeb128Error> {
+    let mut reader = &buf[..];
+    let value = u64::from_leb128u(&mut reader)?;
+    let len = buf.len() - reader.len();
+
+    let fits = |v: u64| len * 7 >= 64 || v >> (len * 7) == 0;
+    let value = match value.checked_add_signed(delta) {
+        Some(v) if fits(v) => v,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "result does not fit the encoded length",
+            )
+            .into())
+        }
+    };
+
+    for (i, byte) in buf[..len].iter_mut().enumerate() {
+        let bits = (value >> (i * 7)) as u8 & 0b01111111;
+        *byte = if i + 1 < len { bits | 0b10000000 } else { bits };
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bumps_counter() {
+        let mut buf = [0xac, 0x02, 0xff];
+        assert_eq!(increment_in_place(&mut buf, 1).unwrap(), 301);
+        assert_eq!(buf, [0xad, 0x02, 0xff]);
+
+        assert_eq!(increment_in_place(&mut buf, -300).unwrap(), 1);
+        assert_eq!(buf, [0x81, 0x00, 0xff]);
+        assert_eq!(u64::from_leb128u(&mut &buf[..]).unwrap(), 1);
+
+        assert!(increment_in_place(&mut buf, 20000).is_err());
+        assert!(increment_in_place(&mut buf, -2).is_err());
+        assert_eq!(buf, [0x81, 0x00, 0xff]);
+
+        let mut max = [0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        assert_eq!(increment_in_place(&mut max, 1).unwrap(), u64::MAX);
+        assert!(increment_in_place(&mut max, 1).is_err());
+    }
+}
@@ -0,0 +1,245 @@
+use std::io::Read;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+
+use crate::format::VarintFormat;
+use crate::{stream, FromLeb128Error, Leb128};
+
+/// Iterator over the ULEB128 bytes of a sequence of values.
+///
+/// Created by [`encode_iter`]; values are encoded only as bytes are pulled.
+#[derive(Debug, Clone)]
+pub struct EncodeBytes<I> {
+    values: I,
+    pending: Option<u64>,
+}
+
+pub fn encode_iter<I>(values: I) -> EncodeBytes<I::IntoIter>
+where
+    I: IntoIterator,
+    I::Item: Into<u64>,
+{
+    EncodeBytes {
+        values: values.into_iter(),
+        pending: None,
+    }
+}
+
+impl<I> Iterator for EncodeBytes<I>
+where
+    I: Iterator,
+    I::Item: Into<u64>,
+{
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let value = match self.pending.take() {
+            Some(value) => value,
+            None => self.values.next()?.into(),
+        };
+
+        let byte = (value & 0b01111111) as u8;
+        let rest = value >> 7;
+
+        if rest == 0 {
+            Some(byte)
+        } else {
+            self.pending = Some(rest);
+            Some(byte | 0b10000000)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.values.size_hint();
+        let pending = self
+            .pending
+            .map_or(0, |v| (64 - v.leading_zeros() as usize).div_ceil(7));
+
+        (
+            lower.saturating_add(pending),
+            upper.and_then(|u| u.checked_mul(10)?.checked_add(pending)),
+        )
+    }
+}
+
+/// Reader adapter yielding at most `n` values in format `F`.
+///
+/// Created by [`take_values`]. Like `Read::take`, but counted in decoded values:
+/// after `n` values it reports a clean end even if `reader` has more input.
+/// After an error the iterator is finished.
+#[derive(Debug)]
+pub struct TakeValues<F, R> {
+    reader: R,
+    remaining: u64,
+    done: bool,
+    format: PhantomData<F>,
+}
+
+pub fn take_values<F: VarintFormat, R: Read>(reader: R, n: u64) -> TakeValues<F, R> {
+    TakeValues {
+        reader,
+        remaining: n,
+        done: false,
+        format: PhantomData,
+    }
+}
+
+impl<F, R> TakeValues<F, R> {
+    /// Number of values still allowed; nonzero after the iterator ends means
+    /// the input ended early or a value failed to decode.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<F: VarintFormat, R: Read> Iterator for TakeValues<F, R> {
+    type Item = Result<F::Value, FromLeb128Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.remaining == 0 {
+            return None;
+        }
+
+        let result = F::decode_next(&mut self.reader).transpose();
+        match result {
+            Some(Ok(_)) => self.remaining -= 1,
+            _ => self.done = true,
+        }
+        result
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            return (0, Some(0));
+        }
+        (0, usize::try_from(self.remaining).ok())
+    }
+}
+
+impl<F: VarintFormat, R: Read> FusedIterator for TakeValues<F, R> {}
+
+/// Iterator decoding values of type `T` from a reader until a clean end of
+/// input, e.g. `Leb128Iter::<u64, _>::new(reader)`.
+///
+/// Unsigned types are read as ULEB128 and signed types as SLEB128. Input that
+/// ends in the middle of a value yields `Err(FromLeb128Error::UnexpectedEof)`;
+/// after any error the iterator is finished.
+#[derive(Debug)]
+pub struct Leb128Iter<T, R> {
+    reader: R,
+    done: bool,
+    value: PhantomData<T>,
+}
+
+impl<T: Leb128, R: Read> Leb128Iter<T, R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            done: false,
+            value: PhantomData,
+        }
+    }
+}
+
+impl<T, R> Leb128Iter<T, R> {
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<T: Leb128, R: Read> Iterator for Leb128Iter<T, R> {
+    type Item = Result<T, FromLeb128Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let result = stream::read_next(&mut self.reader, |r| T::decode(r)).transpose();
+        self.done = !matches!(result, Some(Ok(_)));
+        result
+    }
+}
+
+impl<T: Leb128, R: Read> FusedIterator for Leb128Iter<T, R> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Uleb128;
+    use crate::ToLeb128u;
+
+    #[test]
+    fn matches_eager_encoding() {
+        let values = [0u64, 127, 128, 300, u64::MAX];
+        let mut expected = Vec::new();
+        for v in values {
+            v.to_leb128u(&mut expected).unwrap();
+        }
+
+        assert_eq!(encode_iter(values).collect::<Vec<_>>(), expected);
+        assert_eq!(
+            encode_iter([1u8, 200]).collect::<Vec<_>>(),
+            vec![1, 0xc8, 0x01]
+        );
+    }
+
+    #[test]
+    fn take_values_stops_after_n() {
+        let input = [1, 2, 0xac, 0x02, 4];
+        let mut reader = &input[..];
+
+        let mut section = take_values::<Uleb128, _>(&mut reader, 3);
+        assert_eq!(
+            section.by_ref().collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![1, 2, 300]
+        );
+        assert_eq!(section.remaining(), 0);
+        assert_eq!(reader, &[4]);
+
+        let mut short = take_values::<Uleb128, _>(&[7][..], 2);
+        assert_eq!(short.by_ref().count(), 1);
+        assert_eq!(short.remaining(), 1);
+
+        let input = [
+            1, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f, 2,
+        ];
+        let mut bad = take_values::<Uleb128, _>(&input[..], 3);
+        assert_eq!(bad.next().unwrap().unwrap(), 1);
+        assert!(bad.next().unwrap().is_err());
+        assert_eq!(bad.remaining(), 2);
+        assert!(bad.next().is_none());
+    }
+
+    #[test]
+    fn leb128_iter() {
+        let values = Leb128Iter::<i32, _>::new(&[0x01, 0xbf, 0x7f, 0x00][..])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values, [1, -65, 0]);
+
+        let mut truncated = Leb128Iter::<u64, _>::new(&[0xac, 0x02, 0x80][..]);
+        assert_eq!(truncated.next().unwrap().unwrap(), 300);
+        assert!(matches!(
+            truncated.next(),
+            Some(Err(FromLeb128Error::UnexpectedEof))
+        ));
+        assert!(truncated.next().is_none());
+    }
+}
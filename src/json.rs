@@ -0,0 +1,72 @@
+//! Machine-readable dump of a ULEB128 stream.
+
+use std::io::{Read, Write};
+
+use crate::stream::{self, Counting};
+use crate::FromLeb128Error;
+
+/// Writes every value of `reader` as a JSON array of
+/// `{"offset":0,"len":2,"value":300}` objects.
+///
+/// The array is streamed as values are decoded. Returns the number of values
+/// written; on malformed input the output is left unterminated.
+pub fn dump_to_json(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+) -> Result<u64, FromLeb128Error> {
+    let mut reader = Counting::new(reader);
+    let mut count = 0;
+
+    writer.write_all(b"[")?;
+
+    loop {
+        let offset = reader.count();
+        let value = match stream::read_u::<u64>(&mut reader)? {
+            Some(value) => value,
+            None => break,
+        };
+
+        if count > 0 {
+            writer.write_all(b",")?;
+        }
+        write!(
+            writer,
+            r#"{{"offset":{},"len":{},"value":{}}}"#,
+            offset,
+            reader.count() - offset,
+            value
+        )?;
+        count += 1;
+    }
+
+    writer.write_all(b"]")?;
+    Ok(count)
+}
+
+/// Like [`dump_to_json`], for a stream already in memory.
+pub fn dump_slice_to_json(bytes: &[u8], writer: &mut impl Write) -> Result<u64, FromLeb128Error> {
+    dump_to_json(&mut &bytes[..], writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dump() {
+        let mut out = Vec::new();
+        assert_eq!(
+            dump_slice_to_json(&[0xac, 0x02, 0x00], &mut out).unwrap(),
+            2
+        );
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"[{"offset":0,"len":2,"value":300},{"offset":2,"len":1,"value":0}]"#
+        );
+
+        let mut out = Vec::new();
+        dump_slice_to_json(&[], &mut out).unwrap();
+        assert_eq!(out, b"[]");
+        assert!(dump_slice_to_json(&[0x80], &mut Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+//! The varint flavours of the Kafka wire protocol.
+//!
+//! `VARINT` and `VARLONG` are zigzag-mapped ULEB128 `i32` and `i64`.
+//! `UNSIGNED_VARINT`, used for tagged fields and compact lengths, is plain
+//! ULEB128 `u32`. Each is limited to the length of its type, so overlong
+//! input fails with `FromLeb128Error::TooLong` as it does in Kafka's clients.
+
+use std::io::{self, Read, Write};
+
+use crate::zigzag::Leb128Zigzag;
+use crate::{FromLeb128Error, FromLeb128u, ToLeb128u};
+
+pub fn write_varint(value: i32, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    value.to_leb128_zigzag(writer)
+}
+
+pub fn read_varint(reader: &mut (impl Read + ?Sized)) -> Result<i32, FromLeb128Error> {
+    i32::from_leb128_zigzag(reader)
+}
+
+pub fn write_varlong(value: i64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    value.to_leb128_zigzag(writer)
+}
+
+pub fn read_varlong(reader: &mut (impl Read + ?Sized)) -> Result<i64, FromLeb128Error> {
+    i64::from_leb128_zigzag(reader)
+}
+
+pub fn write_unsigned_varint(value: u32, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    value.to_leb128u(writer)
+}
+
+pub fn read_unsigned_varint(reader: &mut (impl Read + ?Sized)) -> Result<u32, FromLeb128Error> {
+    u32::from_leb128u(reader)
+}
+
+/// Writes the length of a compact string, bytes or array field: `len + 1`,
+/// or 0 for null.
+///
+/// Fails with `io::ErrorKind::InvalidInput` if `len + 1` does not fit a `u32`.
+pub fn write_compact_length(
+    len: Option<usize>,
+    writer: &mut (impl Write + ?Sized),
+) -> io::Result<usize> {
+    let value = match len {
+        None => 0,
+        Some(len) => len
+            .checked_add(1)
+            .and_then(|n| u32::try_from(n).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "length too large"))?,
+    };
+    write_unsigned_varint(value, writer)
+}
+
+/// Reads the length of a compact field; `None` means null.
+pub fn read_compact_length(
+    reader: &mut (impl Read + ?Sized),
+) -> Result<Option<usize>, FromLeb128Error> {
+    Ok(match read_unsigned_varint(reader)? {
+        0 => None,
+        n => Some(n as usize - 1),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kafka_flavours() {
+        let mut buf = Vec::new();
+        write_varint(-1, &mut buf).unwrap();
+        write_varlong(300, &mut buf).unwrap();
+        write_unsigned_varint(300, &mut buf).unwrap();
+        write_compact_length(None, &mut buf).unwrap();
+        write_compact_length(Some(3), &mut buf).unwrap();
+        assert_eq!(buf, [0x01, 0xd8, 0x04, 0xac, 0x02, 0x00, 0x04]);
+
+        let mut reader = &buf[..];
+        assert_eq!(read_varint(&mut reader).unwrap(), -1);
+        assert_eq!(read_varlong(&mut reader).unwrap(), 300);
+        assert_eq!(read_unsigned_varint(&mut reader).unwrap(), 300);
+        assert_eq!(read_compact_length(&mut reader).unwrap(), None);
+        assert_eq!(read_compact_length(&mut reader).unwrap(), Some(3));
+
+        assert!(matches!(
+            read_varint(&mut &[0x80, 0x80, 0x80, 0x80, 0x80, 0x01][..]),
+            Err(FromLeb128Error::TooLong)
+        ));
+    }
+}
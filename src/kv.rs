@@ -0,0 +1,125 @@
+use std::io::{self, Read, Write};
+
+use crate::string::{self, write_leb128_bytes};
+use crate::{stream, FromLeb128Error, FromLeb128u};
+
+/// An owned key and value.
+pub type Record = (Vec<u8>, Vec<u8>);
+
+/// Writes a record framed as key length, key, value length, value.
+pub fn write_record(writer: &mut impl Write, key: &[u8], value: &[u8]) -> io::Result<usize> {
+    Ok(write_leb128_bytes(writer, key)? + write_leb128_bytes(writer, value)?)
+}
+
+/// Reads the next record, or returns `None` on a clean end of input.
+pub fn read_record(reader: &mut impl Read) -> Result<Option<Record>, FromLeb128Error> {
+    let key_len = match stream::read_u::<u64>(reader)? {
+        Some(len) => len,
+        None => return Ok(None),
+    };
+
+    let mut key = Vec::new();
+    string::read_exact_into(reader, key_len, &mut key)?;
+    let value = string::read_leb128_bytes(reader)?;
+
+    Ok(Some((key, value)))
+}
+
+/// Iterator over the records of a reader.
+#[derive(Debug)]
+pub struct Records<R> {
+    reader: R,
+}
+
+impl<R: Read> Records<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+}
+
+impl<R: Read> Iterator for Records<R> {
+    type Item = Result<Record, FromLeb128Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        read_record(&mut self.reader).transpose()
+    }
+}
+
+/// Iterator over the records of a slice, borrowing keys and values from it.
+///
+/// Stops after the first error.
+#[derive(Debug, Clone)]
+pub struct SliceRecords<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> SliceRecords<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn field(&mut self) -> Result<&'a [u8], FromLeb128Error> {
+        let len = u64::from_leb128u(&mut self.bytes)?;
+
+        match usize::try_from(len) {
+            Ok(len) if len <= self.bytes.len() => {
+                let (field, rest) = self.bytes.split_at(len);
+                self.bytes = rest;
+                Ok(field)
+            }
+            _ => Err(FromLeb128Error::UnexpectedEof),
+        }
+    }
+}
+
+impl<'a> Iterator for SliceRecords<'a> {
+    type Item = Result<(&'a [u8], &'a [u8]), FromLeb128Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+
+        let record = self.field().and_then(|key| Ok((key, self.field()?)));
+        if record.is_err() {
+            self.bytes = &[];
+        }
+
+        Some(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut buf = Vec::new();
+        write_record(&mut buf, b"a", b"1").unwrap();
+        write_record(&mut buf, b"key", &[0; 200]).unwrap();
+
+        let records = Records::new(&buf[..])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records[0], (b"a".to_vec(), b"1".to_vec()));
+        assert_eq!(records[1].1.len(), 200);
+
+        let borrowed = SliceRecords::new(&buf)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(borrowed[1].0, b"key");
+    }
+
+    #[test]
+    fn truncated() {
+        let mut buf = Vec::new();
+        write_record(&mut buf, b"key", b"value").unwrap();
+        buf.pop();
+
+        assert!(Records::new(&buf[..]).next().unwrap().is_err());
+        let mut records = SliceRecords::new(&buf);
+        assert!(records.next().unwrap().is_err());
+        assert!(records.next().is_none());
+    }
+}
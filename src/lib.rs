@@ -1,14 +1,315 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "nightly", feature(read_buf, core_io_borrowed_buf))]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::collections::TryReserveError;
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+use core::mem::MaybeUninit;
+use core::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+#[cfg(feature = "std")]
 use std::io::{self, Read, Write};
 
+#[cfg(feature = "std")]
+pub mod adaptive;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "asynchronous-codec")]
+pub mod async_codec;
+#[cfg(any(feature = "futures", feature = "tokio"))]
+mod async_io;
+#[cfg(feature = "std")]
+pub mod avro;
+#[cfg(feature = "num-bigint")]
+mod bigint;
+#[cfg(feature = "bytes")]
+pub mod buf;
+#[cfg(feature = "alloc")]
+pub mod bulk;
+#[cfg(feature = "tokio-util")]
+pub mod codec;
+#[cfg(feature = "std")]
+pub mod compact_size;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+pub mod deque;
+#[cfg(feature = "std")]
+pub mod diff;
+#[cfg(feature = "std")]
+pub mod ebml;
+#[cfg(feature = "embedded-io")]
+pub mod embedded;
+#[cfg(feature = "std")]
+pub mod ext;
+#[cfg(feature = "bitflags")]
+pub mod flags;
+#[cfg(feature = "std")]
+pub mod format;
+#[cfg(feature = "std")]
+pub mod frame;
+#[cfg(feature = "std")]
+pub mod frontcode;
+#[cfg(feature = "futures")]
+pub mod futures;
+#[cfg(feature = "std")]
+pub mod graph;
+#[cfg(feature = "std")]
+pub mod hash;
+#[cfg(feature = "std")]
+pub mod histogram;
+#[cfg(feature = "std")]
+pub mod inplace;
+#[cfg(feature = "std")]
+pub mod iter;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "std")]
+pub mod kafka;
+#[cfg(feature = "std")]
+pub mod kv;
+#[cfg(feature = "std")]
+pub mod log;
+#[cfg(feature = "std")]
+mod macros;
+#[cfg(feature = "std")]
+pub mod minecraft;
+#[cfg(feature = "std")]
+pub mod multiformats;
+#[cfg(feature = "std")]
+pub mod mvt;
+#[cfg(feature = "std")]
+pub mod net;
+#[cfg(feature = "nightly")]
+pub mod nightly;
+#[cfg(feature = "nom")]
+pub mod nom;
+#[cfg(feature = "num-traits")]
+pub mod num;
+mod pending;
+#[cfg(feature = "std")]
+pub mod plan;
+#[cfg(feature = "std")]
+pub mod position;
+#[cfg(feature = "std")]
+pub mod progress;
+#[cfg(feature = "std")]
+pub mod protobuf;
+pub mod push;
+#[cfg(feature = "std")]
+pub mod quic;
+#[cfg(feature = "std")]
+pub mod range;
+#[cfg(feature = "std")]
+pub mod read;
+#[cfg(feature = "std")]
+pub mod ring;
+pub mod scan;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(feature = "std")]
+pub mod smile;
+#[cfg(feature = "std")]
+pub mod sourcemap;
+#[cfg(feature = "std")]
+pub mod sparse;
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "std")]
+pub mod string;
+#[cfg(all(
+    test,
+    any(feature = "embedded-io-async", feature = "futures", feature = "tokio")
+))]
+mod test_util;
+#[cfg(feature = "std")]
+pub mod text;
+#[cfg(feature = "std")]
+pub mod thrift;
+#[cfg(feature = "std")]
+pub mod timestamp;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+#[cfg(feature = "tokio-serde")]
+pub mod tokio_serde;
+#[cfg(feature = "std")]
+pub mod transcode;
+#[cfg(feature = "std")]
+pub mod vclock;
+#[cfg(feature = "winnow")]
+pub mod winnow;
+#[cfg(feature = "std")]
+pub mod write;
+pub mod zigzag;
+
+#[cfg(feature = "derive")]
+pub use leb128_rs_derive::{FromLeb128, ToLeb128};
+
+// Support for the code generated by `leb128-rs-derive`; not public API.
+#[doc(hidden)]
+pub mod __private {
+    use core::mem::MaybeUninit;
+
+    pub const fn max(values: &[usize]) -> usize {
+        let mut max = 0;
+        let mut i = 0;
+        while i < values.len() {
+            if values[i] > max {
+                max = values[i];
+            }
+            i += 1;
+        }
+        max
+    }
+
+    /// # Safety
+    ///
+    /// The first `len` bytes of `buf` must be initialized.
+    pub unsafe fn assume_init(buf: &[MaybeUninit<u8>], len: usize) -> &[u8] {
+        assert!(len <= buf.len());
+        unsafe { core::slice::from_raw_parts(buf.as_ptr().cast(), len) }
+    }
+
+    /// Zeroes the first `len` bytes of `buf`, or all of it if shorter, and
+    /// returns them as initialized bytes.
+    pub fn zeroed(buf: &mut [MaybeUninit<u8>], len: usize) -> &mut [u8] {
+        let len = len.min(buf.len());
+        let buf = &mut buf[..len];
+        buf.fill(MaybeUninit::new(0));
+        // SAFETY: every byte was just initialized, and `[u8]` has the same layout.
+        unsafe { &mut *(buf as *mut [MaybeUninit<u8>] as *mut [u8]) }
+    }
+}
+
+/// Expands to its input only when the `std` feature is enabled, so that
+/// derived impls match the trait methods of this build.
+#[doc(hidden)]
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! __if_std {
+    ($($item:tt)*) => { $($item)* };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "std"))]
+#[macro_export]
+macro_rules! __if_std {
+    ($($item:tt)*) => {};
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EncodeError {
+    /// The output buffer cannot hold the whole encoding.
+    BufferTooSmall,
+}
+
+impl core::fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EncodeError::BufferTooSmall => write!(f, "buffer too small"),
+        }
+    }
+}
+
+impl core::error::Error for EncodeError {}
+
+#[cfg(feature = "std")]
+impl From<EncodeError> for io::Error {
+    fn from(value: EncodeError) -> Self {
+        match value {
+            EncodeError::BufferTooSmall => io::Error::new(io::ErrorKind::WriteZero, value),
+        }
+    }
+}
+
+/// ULEB128 encoding.
+///
+/// Implementations provide `MAX_LEB128_LEN` and `to_leb128u_uninit`; every
+/// other method has a default whichever features are enabled. The defaults
+/// encode through a scratch buffer, so types with encodings longer than four
+/// `u128`s must override them.
 pub trait ToLeb128u {
-    fn to_leb128u(&self, writer: &mut impl Write) -> io::Result<usize>;
+    /// Longest encoding of any value of the type, in bytes.
+    const MAX_LEB128_LEN: usize;
+
+    #[cfg(feature = "std")]
+    fn to_leb128u(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        let mut buf = [0; pending::SCRATCH_LEN];
+        let len = self.to_leb128u_buf(&mut buf)?;
+        writer.write_all(&buf[..len])?;
+        Ok(len)
+    }
+
+    /// Encodes into a possibly uninitialized buffer and returns the written prefix.
+    ///
+    /// Fails with `EncodeError::BufferTooSmall` if `buf` is too small.
+    fn to_leb128u_uninit<'a>(
+        &self,
+        buf: &'a mut [MaybeUninit<u8>],
+    ) -> Result<&'a [u8], EncodeError>;
+
+    /// Number of bytes `to_leb128u_uninit` would write.
+    fn leb128u_len(&self) -> usize {
+        self.to_leb128u_buf(&mut [0; pending::SCRATCH_LEN])
+            .expect("scratch buffer fits the encoding")
+    }
+
+    /// Encodes into the front of `buf` and returns the number of bytes written.
+    fn to_leb128u_buf(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        const {
+            assert!(
+                Self::MAX_LEB128_LEN <= pending::SCRATCH_LEN,
+                "encoding too long for the default `to_leb128u_buf`"
+            )
+        };
+        // Never lend `buf` out as `MaybeUninit`: an implementation could
+        // write uninitialized bytes into it.
+        let mut scratch = [MaybeUninit::uninit(); pending::SCRATCH_LEN];
+        let bytes = self.to_leb128u_uninit(&mut scratch)?;
+        buf.get_mut(..bytes.len())
+            .ok_or(EncodeError::BufferTooSmall)?
+            .copy_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    /// Returns the encoding as a new `Vec`.
+    #[cfg(feature = "alloc")]
+    fn to_leb128u_vec(&self) -> Vec<u8> {
+        let mut buf = vec![0; Self::MAX_LEB128_LEN];
+        let len = self
+            .to_leb128u_buf(&mut buf)
+            .expect("buffer fits the longest encoding");
+        buf.truncate(len);
+        buf
+    }
 }
 
 macro_rules! impl_to_leb128u {
     ($($ty:ty),*) => {
         $(
             impl ToLeb128u for $ty {
-                fn to_leb128u(&self, writer: &mut impl Write) -> io::Result<usize> {
+                const MAX_LEB128_LEN: usize = (<$ty>::BITS as usize).div_ceil(7);
+
+                fn leb128u_len(&self) -> usize {
+                    ((<$ty>::BITS - self.leading_zeros()) as usize).max(1).div_ceil(7)
+                }
+
+                #[cfg(feature = "std")]
+                fn to_leb128u(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+                    let mut buf = [MaybeUninit::uninit(); <$ty as ToLeb128u>::MAX_LEB128_LEN];
+                    let bytes = self.to_leb128u_uninit(&mut buf)?;
+                    writer.write_all(bytes)?;
+                    Ok(bytes.len())
+                }
+
+                fn to_leb128u_uninit<'a>(&self, buf: &'a mut [MaybeUninit<u8>]) -> Result<&'a [u8], EncodeError> {
                     let mut value = *self;
                     let mut count = 0;
 
@@ -16,32 +317,117 @@ macro_rules! impl_to_leb128u {
                         let byte = (value & 0b01111111) as u8;
                         value >>= 7;
 
+                        let slot = buf.get_mut(count).ok_or(EncodeError::BufferTooSmall)?;
+                        count += 1;
+
                         if value == 0 {
-                            count += writer.write(&[byte])?;
+                            slot.write(byte);
                             break;
                         }
 
-                        count += writer.write(&[byte | 0b10000000])?;
+                        slot.write(byte | 0b10000000);
                     }
 
-                    Ok(count)
+                    // SAFETY: the first `count` bytes were initialized above.
+                    Ok(unsafe { core::slice::from_raw_parts(buf.as_ptr().cast(), count) })
+                }
+
+                fn to_leb128u_buf(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+                    // SAFETY: `[u8]` and `[MaybeUninit<u8>]` have the same layout,
+                    // and the encoder above only writes initialized bytes.
+                    let uninit = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
+                    self.to_leb128u_uninit(uninit).map(|bytes| bytes.len())
                 }
             }
         )*
     };
 }
 
-impl_to_leb128u!(u8, u16, u32, u64);
+impl_to_leb128u!(u8, u16, u32, u64, u128);
 
+/// SLEB128 encoding.
+///
+/// Implementations provide `MAX_LEB128_LEN` and `to_leb128i_uninit`; every
+/// other method has a default whichever features are enabled. The defaults
+/// encode through a scratch buffer, so types with encodings longer than four
+/// `u128`s must override them.
 pub trait ToLeb128i {
-    fn to_leb128i(&self, writer: &mut impl Write) -> io::Result<usize>;
+    /// Longest encoding of any value of the type, in bytes.
+    const MAX_LEB128_LEN: usize;
+
+    #[cfg(feature = "std")]
+    fn to_leb128i(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        let mut buf = [0; pending::SCRATCH_LEN];
+        let len = self.to_leb128i_buf(&mut buf)?;
+        writer.write_all(&buf[..len])?;
+        Ok(len)
+    }
+
+    /// Encodes into a possibly uninitialized buffer and returns the written prefix.
+    ///
+    /// Fails with `EncodeError::BufferTooSmall` if `buf` is too small.
+    fn to_leb128i_uninit<'a>(
+        &self,
+        buf: &'a mut [MaybeUninit<u8>],
+    ) -> Result<&'a [u8], EncodeError>;
+
+    /// Number of bytes `to_leb128i_uninit` would write.
+    fn leb128i_len(&self) -> usize {
+        self.to_leb128i_buf(&mut [0; pending::SCRATCH_LEN])
+            .expect("scratch buffer fits the encoding")
+    }
+
+    /// Encodes into the front of `buf` and returns the number of bytes written.
+    fn to_leb128i_buf(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        const {
+            assert!(
+                Self::MAX_LEB128_LEN <= pending::SCRATCH_LEN,
+                "encoding too long for the default `to_leb128i_buf`"
+            )
+        };
+        // Never lend `buf` out as `MaybeUninit`: an implementation could
+        // write uninitialized bytes into it.
+        let mut scratch = [MaybeUninit::uninit(); pending::SCRATCH_LEN];
+        let bytes = self.to_leb128i_uninit(&mut scratch)?;
+        buf.get_mut(..bytes.len())
+            .ok_or(EncodeError::BufferTooSmall)?
+            .copy_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    /// Returns the encoding as a new `Vec`.
+    #[cfg(feature = "alloc")]
+    fn to_leb128i_vec(&self) -> Vec<u8> {
+        let mut buf = vec![0; Self::MAX_LEB128_LEN];
+        let len = self
+            .to_leb128i_buf(&mut buf)
+            .expect("buffer fits the longest encoding");
+        buf.truncate(len);
+        buf
+    }
 }
 
 macro_rules! impl_to_leb128i {
     ($($ty:ty),*) => {
         $(
             impl ToLeb128i for $ty {
-                fn to_leb128i(&self, writer: &mut impl Write) -> io::Result<usize> {
+                const MAX_LEB128_LEN: usize = (<$ty>::BITS as usize).div_ceil(7);
+
+                fn leb128i_len(&self) -> usize {
+                    // One bit more than the magnitude is needed for the sign.
+                    let magnitude = *self ^ (*self >> (<$ty>::BITS - 1));
+                    (<$ty>::BITS - magnitude.leading_zeros()) as usize / 7 + 1
+                }
+
+                #[cfg(feature = "std")]
+                fn to_leb128i(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+                    let mut buf = [MaybeUninit::uninit(); <$ty as ToLeb128i>::MAX_LEB128_LEN];
+                    let bytes = self.to_leb128i_uninit(&mut buf)?;
+                    writer.write_all(bytes)?;
+                    Ok(bytes.len())
+                }
+
+                fn to_leb128i_uninit<'a>(&self, buf: &'a mut [MaybeUninit<u8>]) -> Result<&'a [u8], EncodeError> {
                     let mut value = *self;
                     let mut count = 0;
 
@@ -49,148 +435,978 @@ macro_rules! impl_to_leb128i {
                         let byte = (value & 0b01111111) as u8;
                         value >>= 7;
 
+                        let slot = buf.get_mut(count).ok_or(EncodeError::BufferTooSmall)?;
+                        count += 1;
+
                         if value == 0 && (byte & 0b01000000) == 0 || value == -1 && (byte & 0b01000000) != 0 {
-                            count += writer.write(&[byte])?;
+                            slot.write(byte);
                             break;
                         }
 
-                        count += writer.write(&[byte | 0b10000000])?;
+                        slot.write(byte | 0b10000000);
                     }
 
-                    Ok(count)
+                    // SAFETY: the first `count` bytes were initialized above.
+                    Ok(unsafe { core::slice::from_raw_parts(buf.as_ptr().cast(), count) })
+                }
+
+                fn to_leb128i_buf(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+                    // SAFETY: `[u8]` and `[MaybeUninit<u8>]` have the same layout,
+                    // and the encoder above only writes initialized bytes.
+                    let uninit = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
+                    self.to_leb128i_uninit(uninit).map(|bytes| bytes.len())
                 }
             }
         )*
     };
 }
 
-impl_to_leb128i!(i8, i16, i32, i64);
+impl_to_leb128i!(i8, i16, i32, i64, i128);
 
 #[derive(Debug)]
 pub enum FromLeb128Error {
+    /// The input is not valid for the format being decoded.
     Malformed,
+    /// The value is valid but does not fit the target type.
+    Overflow,
+    /// The encoding is longer than the maximum for the target type.
+    TooLong,
+    /// A canonical decode found padding bytes that a minimal encoding would
+    /// not have.
+    NonCanonical,
+    /// The input ended in the middle of a value.
+    UnexpectedEof,
+    /// A `NonZero*` type decoded a zero.
+    Zero,
+    /// A `char` decoded a surrogate or a value above `char::MAX`.
+    InvalidChar(u32),
+    /// The value ended before the end of the input; holds the offset of the
+    /// first unconsumed byte, saturated at `u32::MAX`.
+    TrailingBytes(u32),
+    #[cfg(feature = "std")]
     Io(io::Error),
+    /// Reserving space for a length or count read from the input failed.
+    #[cfg(feature = "alloc")]
+    Alloc,
+}
+
+impl FromLeb128Error {
+    // Kept out of line so the error branches do not bloat the decode loops.
+    #[cold]
+    #[inline(never)]
+    fn err<T>(self) -> Result<T, Self> {
+        Err(self)
+    }
+
+    pub(crate) fn trailing(offset: usize) -> Self {
+        Self::TrailingBytes(u32::try_from(offset).unwrap_or(u32::MAX))
+    }
 }
 
-impl std::fmt::Display for FromLeb128Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for FromLeb128Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             FromLeb128Error::Malformed => write!(f, "malformed bytes"),
+            FromLeb128Error::Overflow => write!(f, "value out of range for the target type"),
+            FromLeb128Error::TooLong => write!(f, "encoding too long for the target type"),
+            FromLeb128Error::NonCanonical => write!(f, "non-minimal encoding"),
+            FromLeb128Error::UnexpectedEof => write!(f, "unexpected end of input"),
+            FromLeb128Error::Zero => write!(f, "zero value for a nonzero type"),
+            FromLeb128Error::InvalidChar(value) => {
+                write!(f, "invalid unicode scalar value {:#x}", value)
+            }
+            FromLeb128Error::TrailingBytes(offset) => {
+                write!(f, "trailing bytes at offset {}", offset)
+            }
+            #[cfg(feature = "std")]
             FromLeb128Error::Io(e) => write!(f, "{}", e),
+            #[cfg(feature = "alloc")]
+            FromLeb128Error::Alloc => write!(f, "memory allocation failed"),
         }
     }
 }
 
-impl std::error::Error for FromLeb128Error {}
+impl core::error::Error for FromLeb128Error {}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for FromLeb128Error {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            FromLeb128Error::Malformed => defmt::write!(f, "malformed bytes"),
+            FromLeb128Error::Overflow => defmt::write!(f, "value out of range for the target type"),
+            FromLeb128Error::TooLong => defmt::write!(f, "encoding too long for the target type"),
+            FromLeb128Error::NonCanonical => defmt::write!(f, "non-minimal encoding"),
+            FromLeb128Error::UnexpectedEof => defmt::write!(f, "unexpected end of input"),
+            FromLeb128Error::Zero => defmt::write!(f, "zero value for a nonzero type"),
+            FromLeb128Error::InvalidChar(value) => {
+                defmt::write!(f, "invalid unicode scalar value {=u32:#x}", value)
+            }
+            FromLeb128Error::TrailingBytes(offset) => {
+                defmt::write!(f, "trailing bytes at offset {=u32}", offset)
+            }
+            #[cfg(feature = "std")]
+            FromLeb128Error::Io(e) => defmt::write!(f, "{}", defmt::Display2Format(e)),
+            #[cfg(feature = "alloc")]
+            FromLeb128Error::Alloc => defmt::write!(f, "memory allocation failed"),
+        }
+    }
+}
 
+#[cfg(feature = "std")]
 impl From<io::Error> for FromLeb128Error {
+    #[cold]
     fn from(value: io::Error) -> Self {
+        if value.kind() == io::ErrorKind::UnexpectedEof {
+            return Self::UnexpectedEof;
+        }
         Self::Io(value)
     }
 }
 
+#[cfg(feature = "alloc")]
+impl From<TryReserveError> for FromLeb128Error {
+    #[cold]
+    fn from(_: TryReserveError) -> Self {
+        Self::Alloc
+    }
+}
+
+// The decode loops are shared between the reader and slice entry points;
+// `$next` evaluates to the next input byte or returns early.
+
+macro_rules! decode_unsigned {
+    ($ty:ty, $next:expr) => {{
+        let bit = <$ty>::BITS as usize;
+        let mut result: $ty = 0;
+        let mut shift = 0;
+
+        loop {
+            let byte: u8 = $next;
+            let b = (byte & 0b01111111) as $ty;
+
+            if shift >= bit {
+                return FromLeb128Error::TooLong.err();
+            }
+            // Only `bit - shift` bits of the last byte fit in the type.
+            if shift + 7 > bit && b >> (bit - shift) != 0 {
+                return FromLeb128Error::Overflow.err();
+            }
+
+            result |= b << shift;
+            shift += 7;
+
+            if byte & 0b10000000 == 0 {
+                break;
+            }
+        }
+
+        result
+    }};
+}
+
+macro_rules! decode_signed {
+    ($ty:ty, $next:expr) => {{
+        let bit = <$ty>::BITS as usize;
+        let mut result: $ty = 0;
+        let mut shift = 0;
+
+        loop {
+            let byte: u8 = $next;
+            let b = (byte & 0b01111111) as $ty;
+
+            // The bits of the last byte from the type's sign bit up
+            // must all be copies of it.
+            if shift >= bit {
+                return FromLeb128Error::TooLong.err();
+            }
+            if shift + 7 > bit {
+                let sign = b >> (bit - shift - 1);
+                if sign != 0 && sign != 0b01111111 >> (bit - shift - 1) {
+                    return FromLeb128Error::Overflow.err();
+                }
+            }
+
+            result |= b << shift;
+            shift += 7;
+
+            if byte & 0b10000000 == 0 {
+                let is_negative = (b & 0b01000000) != 0;
+
+                if is_negative && shift < bit {
+                    result |= !0 << shift;
+                }
+                break;
+            }
+        }
+
+        result
+    }};
+}
+
+/// ULEB128 decoding.
+///
+/// Implementations provide `from_leb128u_slice`; every other method has a
+/// default whichever features are enabled. The default `from_leb128u` reads
+/// a byte at a time into a scratch buffer, so types with encodings longer
+/// than four `u128`s must override it.
 pub trait FromLeb128u {
-    fn from_leb128u(reader: &mut impl Read) -> Result<Self, FromLeb128Error>
+    #[cfg(feature = "std")]
+    fn from_leb128u(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error>
+    where
+        Self: Sized,
+    {
+        pending::read_with(reader, Self::from_leb128u_slice)
+    }
+
+    /// Like `from_leb128u`, also returning the number of bytes read.
+    #[cfg(feature = "std")]
+    fn from_leb128u_counted(
+        reader: &mut (impl Read + ?Sized),
+    ) -> Result<(Self, usize), FromLeb128Error>
+    where
+        Self: Sized,
+    {
+        let mut reader = stream::Counting::new(reader);
+        let value = Self::from_leb128u(&mut reader)?;
+        Ok((value, reader.count() as usize))
+    }
+
+    /// Like `from_leb128u`, recording how far into the value a failure
+    /// happened.
+    #[cfg(feature = "std")]
+    fn from_leb128u_positioned(
+        reader: &mut (impl Read + ?Sized),
+    ) -> Result<Self, position::DecodeError>
+    where
+        Self: Sized,
+    {
+        position::decode_positioned(reader, |r| Self::from_leb128u(r))
+    }
+
+    /// Like `from_leb128u`, but rejects non-minimal encodings such as
+    /// `0x80 0x00` with `FromLeb128Error::NonCanonical`.
+    #[cfg(feature = "std")]
+    fn from_leb128u_canonical(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error>
+    where
+        Self: Sized,
+    {
+        let mut reader = stream::Tail::new(reader);
+        let value = Self::from_leb128u(&mut reader)?;
+
+        if stream::padded_u(reader.tail()) {
+            return Err(FromLeb128Error::NonCanonical);
+        }
+
+        Ok(value)
+    }
+
+    /// Like `from_leb128u`, but fails with `FromLeb128Error::TooLong` if the
+    /// encoding is longer than `max_len` bytes.
+    ///
+    /// No more than `max_len` bytes are read from `reader`.
+    #[cfg(feature = "std")]
+    fn from_leb128u_max(
+        reader: &mut (impl Read + ?Sized),
+        max_len: usize,
+    ) -> Result<Self, FromLeb128Error>
+    where
+        Self: Sized,
+    {
+        let mut reader = stream::Limit::new(reader, max_len);
+
+        match Self::from_leb128u(&mut reader) {
+            Err(FromLeb128Error::UnexpectedEof) if reader.exceeded() => {
+                Err(FromLeb128Error::TooLong)
+            }
+            result => result,
+        }
+    }
+
+    /// Like `from_leb128u`, but also accepts encodings padded beyond the
+    /// type's maximum length, up to that of a `u128`.
+    #[cfg(feature = "std")]
+    fn from_leb128u_lenient(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error>
+    where
+        Self: Sized + TryFrom<u128>,
+    {
+        Self::try_from(u128::from_leb128u(reader)?).map_err(|_| FromLeb128Error::Overflow)
+    }
+
+    /// Decodes a value from the front of `bytes`, returning it with the number
+    /// of bytes consumed.
+    fn from_leb128u_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error>
     where
         Self: Sized;
+
+    /// Decodes a value that must span all of `bytes`.
+    ///
+    /// Fails with `FromLeb128Error::TrailingBytes` if any input is left over.
+    fn from_leb128u_exact(bytes: &[u8]) -> Result<Self, FromLeb128Error>
+    where
+        Self: Sized,
+    {
+        let (value, len) = Self::from_leb128u_slice(bytes)?;
+
+        if len != bytes.len() {
+            return Err(FromLeb128Error::trailing(len));
+        }
+
+        Ok(value)
+    }
 }
 
 macro_rules! impl_from_leb128u {
     ($($ty:ty),*) => {
         $(
             impl FromLeb128u for $ty {
-                fn from_leb128u(reader: &mut impl Read) -> Result<Self, FromLeb128Error> {
-                    let bit = std::mem::size_of::<$ty>() * 8;
-                    let mut result = 0;
-                    let mut shift = 0;
-                    let mut buf = [0; 1];
-
-                    loop {
-                        reader.read_exact(&mut buf)?;
-                        let b = (buf[0] & 0b01111111) as $ty;
-
-                        if (shift >= bit - (bit % 7)) && (b >= (1 << (bit % 7))) {
-                            return Err(FromLeb128Error::Malformed);
-                        }
+                #[cfg(feature = "std")]
+                fn from_leb128u(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+                    Ok(decode_unsigned!($ty, stream::read_byte(reader)?))
+                }
 
-                        result |= b << shift;
-                        shift += 7;
+                fn from_leb128u_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error> {
+                    let mut pos = 0;
 
-                        if buf[0] & 0b10000000 == 0 {
-                            break;
-                        }
-                    }
+                    let value = decode_unsigned!($ty, {
+                        let Some(&byte) = bytes.get(pos) else {
+                            return FromLeb128Error::UnexpectedEof.err();
+                        };
+                        pos += 1;
+                        byte
+                    });
 
-                    Ok(result)
+                    Ok((value, pos))
                 }
             }
         )*
     };
 }
 
-impl_from_leb128u!(u8, u16, u32, u64);
+impl_from_leb128u!(u8, u16, u32, u64, u128);
 
+/// SLEB128 decoding.
+///
+/// Implementations provide `from_leb128i_slice`; every other method has a
+/// default whichever features are enabled. The default `from_leb128i` reads
+/// a byte at a time into a scratch buffer, so types with encodings longer
+/// than four `u128`s must override it.
 pub trait FromLeb128i {
-    fn from_leb128i(reader: &mut impl Read) -> Result<Self, FromLeb128Error>
+    #[cfg(feature = "std")]
+    fn from_leb128i(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error>
+    where
+        Self: Sized,
+    {
+        pending::read_with(reader, Self::from_leb128i_slice)
+    }
+
+    /// Like `from_leb128i`, also returning the number of bytes read.
+    #[cfg(feature = "std")]
+    fn from_leb128i_counted(
+        reader: &mut (impl Read + ?Sized),
+    ) -> Result<(Self, usize), FromLeb128Error>
+    where
+        Self: Sized,
+    {
+        let mut reader = stream::Counting::new(reader);
+        let value = Self::from_leb128i(&mut reader)?;
+        Ok((value, reader.count() as usize))
+    }
+
+    /// Like `from_leb128i`, recording how far into the value a failure
+    /// happened.
+    #[cfg(feature = "std")]
+    fn from_leb128i_positioned(
+        reader: &mut (impl Read + ?Sized),
+    ) -> Result<Self, position::DecodeError>
+    where
+        Self: Sized,
+    {
+        position::decode_positioned(reader, |r| Self::from_leb128i(r))
+    }
+
+    /// Like `from_leb128i`, but rejects non-minimal encodings such as
+    /// `0x80 0x00` with `FromLeb128Error::NonCanonical`.
+    #[cfg(feature = "std")]
+    fn from_leb128i_canonical(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error>
+    where
+        Self: Sized,
+    {
+        let mut reader = stream::Tail::new(reader);
+        let value = Self::from_leb128i(&mut reader)?;
+
+        if stream::padded_i(reader.tail()) {
+            return Err(FromLeb128Error::NonCanonical);
+        }
+
+        Ok(value)
+    }
+
+    /// Like `from_leb128i`, but fails with `FromLeb128Error::TooLong` if the
+    /// encoding is longer than `max_len` bytes.
+    ///
+    /// No more than `max_len` bytes are read from `reader`.
+    #[cfg(feature = "std")]
+    fn from_leb128i_max(
+        reader: &mut (impl Read + ?Sized),
+        max_len: usize,
+    ) -> Result<Self, FromLeb128Error>
+    where
+        Self: Sized,
+    {
+        let mut reader = stream::Limit::new(reader, max_len);
+
+        match Self::from_leb128i(&mut reader) {
+            Err(FromLeb128Error::UnexpectedEof) if reader.exceeded() => {
+                Err(FromLeb128Error::TooLong)
+            }
+            result => result,
+        }
+    }
+
+    /// Like `from_leb128i`, but also accepts encodings padded beyond the
+    /// type's maximum length, up to that of a `i128`.
+    #[cfg(feature = "std")]
+    fn from_leb128i_lenient(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error>
+    where
+        Self: Sized + TryFrom<i128>,
+    {
+        Self::try_from(i128::from_leb128i(reader)?).map_err(|_| FromLeb128Error::Overflow)
+    }
+
+    /// Decodes a value from the front of `bytes`, returning it with the number
+    /// of bytes consumed.
+    fn from_leb128i_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error>
     where
         Self: Sized;
+
+    /// Decodes a value that must span all of `bytes`.
+    ///
+    /// Fails with `FromLeb128Error::TrailingBytes` if any input is left over.
+    fn from_leb128i_exact(bytes: &[u8]) -> Result<Self, FromLeb128Error>
+    where
+        Self: Sized,
+    {
+        let (value, len) = Self::from_leb128i_slice(bytes)?;
+
+        if len != bytes.len() {
+            return Err(FromLeb128Error::trailing(len));
+        }
+
+        Ok(value)
+    }
 }
 
 macro_rules! impl_from_leb128i {
     ($($ty:ty),*) => {
         $(
             impl FromLeb128i for $ty {
-                fn from_leb128i(reader: &mut impl Read) -> Result<Self, FromLeb128Error> {
-                    let bit = std::mem::size_of::<$ty>() * 8;
-                    let mut result = 0;
-                    let mut shift = 0;
-                    let mut buf = [0; 1];
+                #[cfg(feature = "std")]
+                fn from_leb128i(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+                    Ok(decode_signed!($ty, stream::read_byte(reader)?))
+                }
 
-                    loop {
-                        reader.read_exact(&mut buf)?;
-                        let b = (buf[0] & 0b01111111) as $ty;
-
-                        if shift >= bit - (bit % 7) {
-                            let is_positive = (b & 0b01000000) == 0;
-
-                            if is_positive {
-                                if b >= (1 << (bit % 7)) {
-                                    return Err(FromLeb128Error::Malformed);
-                                }
-                            } else {
-                                let mask = (!0 << (bit % 7)) & 0b01111111;
-                                if b & mask != mask {
-                                    return Err(FromLeb128Error::Malformed);
-                                }
-                            }
-                        }
+                fn from_leb128i_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error> {
+                    let mut pos = 0;
 
-                        result |= b << shift;
-                        shift += 7;
+                    let value = decode_signed!($ty, {
+                        let Some(&byte) = bytes.get(pos) else {
+                            return FromLeb128Error::UnexpectedEof.err();
+                        };
+                        pos += 1;
+                        byte
+                    });
 
-                        if buf[0] & 0b10000000 == 0 {
-                            let is_negative = (b & 0b01000000) != 0;
+                    Ok((value, pos))
+                }
+            }
+        )*
+    };
+}
 
-                            if is_negative && shift <= bit {
-                                result |= !0 << shift;
-                            }
-                            break;
-                        }
-                    }
+impl_from_leb128i!(i8, i16, i32, i64, i128);
 
-                    Ok(result)
+// `usize` and `isize` are encoded as 64-bit values so that the bytes do not
+// depend on the platform; decoding fails with `Overflow` where they do not fit.
+
+impl ToLeb128u for usize {
+    const MAX_LEB128_LEN: usize = u64::MAX_LEB128_LEN;
+
+    fn leb128u_len(&self) -> usize {
+        (*self as u64).leb128u_len()
+    }
+
+    #[cfg(feature = "std")]
+    fn to_leb128u(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        (*self as u64).to_leb128u(writer)
+    }
+
+    fn to_leb128u_uninit<'a>(
+        &self,
+        buf: &'a mut [MaybeUninit<u8>],
+    ) -> Result<&'a [u8], EncodeError> {
+        (*self as u64).to_leb128u_uninit(buf)
+    }
+
+    fn to_leb128u_buf(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        (*self as u64).to_leb128u_buf(buf)
+    }
+}
+
+impl FromLeb128u for usize {
+    #[cfg(feature = "std")]
+    fn from_leb128u(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+        usize::try_from(u64::from_leb128u(reader)?).map_err(|_| FromLeb128Error::Overflow)
+    }
+
+    fn from_leb128u_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error> {
+        let (value, len) = u64::from_leb128u_slice(bytes)?;
+        let value = usize::try_from(value).map_err(|_| FromLeb128Error::Overflow)?;
+        Ok((value, len))
+    }
+}
+
+impl ToLeb128i for isize {
+    const MAX_LEB128_LEN: usize = i64::MAX_LEB128_LEN;
+
+    fn leb128i_len(&self) -> usize {
+        (*self as i64).leb128i_len()
+    }
+
+    #[cfg(feature = "std")]
+    fn to_leb128i(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        (*self as i64).to_leb128i(writer)
+    }
+
+    fn to_leb128i_uninit<'a>(
+        &self,
+        buf: &'a mut [MaybeUninit<u8>],
+    ) -> Result<&'a [u8], EncodeError> {
+        (*self as i64).to_leb128i_uninit(buf)
+    }
+
+    fn to_leb128i_buf(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        (*self as i64).to_leb128i_buf(buf)
+    }
+}
+
+impl FromLeb128i for isize {
+    #[cfg(feature = "std")]
+    fn from_leb128i(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+        isize::try_from(i64::from_leb128i(reader)?).map_err(|_| FromLeb128Error::Overflow)
+    }
+
+    fn from_leb128i_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error> {
+        let (value, len) = i64::from_leb128i_slice(bytes)?;
+        let value = isize::try_from(value).map_err(|_| FromLeb128Error::Overflow)?;
+        Ok((value, len))
+    }
+}
+
+macro_rules! impl_nonzero {
+    (
+        $to:ident $to_fn:ident $to_uninit:ident $to_buf:ident $len_fn:ident
+        $from:ident $from_fn:ident $slice_fn:ident:
+        $($ty:ty => $inner:ty),*
+    ) => {
+        $(
+            impl $to for $ty {
+                const MAX_LEB128_LEN: usize = <$inner as $to>::MAX_LEB128_LEN;
+
+                fn $len_fn(&self) -> usize {
+                    self.get().$len_fn()
+                }
+
+                #[cfg(feature = "std")]
+                fn $to_fn(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+                    self.get().$to_fn(writer)
+                }
+
+                fn $to_uninit<'a>(&self, buf: &'a mut [MaybeUninit<u8>]) -> Result<&'a [u8], EncodeError> {
+                    self.get().$to_uninit(buf)
+                }
+
+                fn $to_buf(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+                    self.get().$to_buf(buf)
+                }
+            }
+
+            impl $from for $ty {
+                #[cfg(feature = "std")]
+                fn $from_fn(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+                    <$ty>::new($from::$from_fn(reader)?).ok_or(FromLeb128Error::Zero)
+                }
+
+                fn $slice_fn(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error> {
+                    let (value, len) = <$inner as $from>::$slice_fn(bytes)?;
+                    Ok((<$ty>::new(value).ok_or(FromLeb128Error::Zero)?, len))
                 }
             }
         )*
     };
 }
 
-impl_from_leb128i!(i8, i16, i32, i64);
+impl_nonzero!(
+    ToLeb128u to_leb128u to_leb128u_uninit to_leb128u_buf leb128u_len
+    FromLeb128u from_leb128u from_leb128u_slice:
+    NonZeroU8 => u8, NonZeroU16 => u16, NonZeroU32 => u32, NonZeroU64 => u64,
+    NonZeroU128 => u128, NonZeroUsize => usize
+);
+impl_nonzero!(
+    ToLeb128i to_leb128i to_leb128i_uninit to_leb128i_buf leb128i_len
+    FromLeb128i from_leb128i from_leb128i_slice:
+    NonZeroI8 => i8, NonZeroI16 => i16, NonZeroI32 => i32, NonZeroI64 => i64,
+    NonZeroI128 => i128, NonZeroIsize => isize
+);
+
+impl ToLeb128u for char {
+    const MAX_LEB128_LEN: usize = 3;
+
+    fn leb128u_len(&self) -> usize {
+        u32::from(*self).leb128u_len()
+    }
+
+    #[cfg(feature = "std")]
+    fn to_leb128u(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        u32::from(*self).to_leb128u(writer)
+    }
+
+    fn to_leb128u_uninit<'a>(
+        &self,
+        buf: &'a mut [MaybeUninit<u8>],
+    ) -> Result<&'a [u8], EncodeError> {
+        u32::from(*self).to_leb128u_uninit(buf)
+    }
+
+    fn to_leb128u_buf(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        u32::from(*self).to_leb128u_buf(buf)
+    }
+}
+
+impl FromLeb128u for char {
+    #[cfg(feature = "std")]
+    fn from_leb128u(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+        let value = u32::from_leb128u(reader)?;
+        char::from_u32(value).ok_or(FromLeb128Error::InvalidChar(value))
+    }
+
+    fn from_leb128u_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error> {
+        let (value, len) = u32::from_leb128u_slice(bytes)?;
+        let c = char::from_u32(value).ok_or(FromLeb128Error::InvalidChar(value))?;
+        Ok((c, len))
+    }
+}
+
+/// Encoding into a stack array sized for the type's longest encoding.
+pub trait ToLeb128uArray: ToLeb128u {
+    /// `[u8; N]` where `N` is the longest encoding of the type.
+    type Array: AsRef<[u8]>;
+
+    /// Returns the encoding in a fixed-size array along with its length.
+    fn to_leb128u_array(&self) -> (Self::Array, usize);
+}
+
+/// Signed counterpart of [`ToLeb128uArray`].
+pub trait ToLeb128iArray: ToLeb128i {
+    /// `[u8; N]` where `N` is the longest encoding of the type.
+    type Array: AsRef<[u8]>;
 
-#[cfg(test)]
+    /// Returns the encoding in a fixed-size array along with its length.
+    fn to_leb128i_array(&self) -> (Self::Array, usize);
+}
+
+macro_rules! impl_array {
+    ($trait:ident $base:ident $array_fn:ident $buf_fn:ident: $($ty:ty),*) => {
+        $(
+            impl $trait for $ty {
+                type Array = [u8; <$ty as $base>::MAX_LEB128_LEN];
+
+                fn $array_fn(&self) -> (Self::Array, usize) {
+                    let mut buf = [0; <$ty as $base>::MAX_LEB128_LEN];
+                    let len = self.$buf_fn(&mut buf).expect("array fits the longest encoding");
+                    (buf, len)
+                }
+            }
+        )*
+    };
+}
+
+impl_array!(
+    ToLeb128uArray ToLeb128u to_leb128u_array to_leb128u_buf:
+    u8, u16, u32, u64, u128, usize, char
+);
+impl_array!(
+    ToLeb128iArray ToLeb128i to_leb128i_array to_leb128i_buf:
+    i8, i16, i32, i64, i128, isize
+);
+
+macro_rules! impl_tuple {
+    ($(($($name:ident $idx:tt),+)),*) => {
+        $(
+            impl<$($name: ToLeb128u),+> ToLeb128u for ($($name,)+) {
+                const MAX_LEB128_LEN: usize = 0 $(+ $name::MAX_LEB128_LEN)+;
+
+                fn leb128u_len(&self) -> usize {
+                    0 $(+ self.$idx.leb128u_len())+
+                }
+
+                #[cfg(feature = "std")]
+                fn to_leb128u(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+                    let mut count = 0;
+                    $(count += self.$idx.to_leb128u(writer)?;)+
+                    Ok(count)
+                }
+
+                fn to_leb128u_uninit<'a>(&self, buf: &'a mut [MaybeUninit<u8>]) -> Result<&'a [u8], EncodeError> {
+                    let buf = __private::zeroed(buf, Self::MAX_LEB128_LEN);
+                    let count = self.to_leb128u_buf(buf)?;
+                    buf.get(..count).ok_or(EncodeError::BufferTooSmall)
+                }
+
+                // The fields encode into initialized bytes, so a field that
+                // misreports its length cannot expose uninitialized memory.
+                fn to_leb128u_buf(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+                    let mut count = 0;
+                    $(count += self.$idx.to_leb128u_buf(buf.get_mut(count..).ok_or(EncodeError::BufferTooSmall)?)?;)+
+                    Ok(count)
+                }
+            }
+
+            impl<$($name: ToLeb128i),+> ToLeb128i for ($($name,)+) {
+                const MAX_LEB128_LEN: usize = 0 $(+ $name::MAX_LEB128_LEN)+;
+
+                fn leb128i_len(&self) -> usize {
+                    0 $(+ self.$idx.leb128i_len())+
+                }
+
+                #[cfg(feature = "std")]
+                fn to_leb128i(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+                    let mut count = 0;
+                    $(count += self.$idx.to_leb128i(writer)?;)+
+                    Ok(count)
+                }
+
+                fn to_leb128i_uninit<'a>(&self, buf: &'a mut [MaybeUninit<u8>]) -> Result<&'a [u8], EncodeError> {
+                    let buf = __private::zeroed(buf, Self::MAX_LEB128_LEN);
+                    let count = self.to_leb128i_buf(buf)?;
+                    buf.get(..count).ok_or(EncodeError::BufferTooSmall)
+                }
+
+                // The fields encode into initialized bytes, so a field that
+                // misreports its length cannot expose uninitialized memory.
+                fn to_leb128i_buf(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+                    let mut count = 0;
+                    $(count += self.$idx.to_leb128i_buf(buf.get_mut(count..).ok_or(EncodeError::BufferTooSmall)?)?;)+
+                    Ok(count)
+                }
+            }
+
+            impl<$($name: FromLeb128u),+> FromLeb128u for ($($name,)+) {
+                #[cfg(feature = "std")]
+                fn from_leb128u(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+                    Ok(($($name::from_leb128u(reader)?,)+))
+                }
+
+                #[cfg(feature = "std")]
+                fn from_leb128u_canonical(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+                    Ok(($($name::from_leb128u_canonical(reader)?,)+))
+                }
+
+                fn from_leb128u_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error> {
+                    let mut count = 0;
+                    let value = ($({
+                        let (field, len) = $name::from_leb128u_slice(&bytes[count..])?;
+                        count += len;
+                        field
+                    },)+);
+                    Ok((value, count))
+                }
+            }
+
+            impl<$($name: FromLeb128i),+> FromLeb128i for ($($name,)+) {
+                #[cfg(feature = "std")]
+                fn from_leb128i(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+                    Ok(($($name::from_leb128i(reader)?,)+))
+                }
+
+                #[cfg(feature = "std")]
+                fn from_leb128i_canonical(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+                    Ok(($($name::from_leb128i_canonical(reader)?,)+))
+                }
+
+                fn from_leb128i_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error> {
+                    let mut count = 0;
+                    let value = ($({
+                        let (field, len) = $name::from_leb128i_slice(&bytes[count..])?;
+                        count += len;
+                        field
+                    },)+);
+                    Ok((value, count))
+                }
+            }
+        )*
+    };
+}
+
+impl_tuple!((A 0), (A 0, B 1), (A 0, B 1, C 2), (A 0, B 1, C 2, D 3));
+
+/// Single bound for code that encodes and decodes values without caring how:
+/// unsigned types use ULEB128 and signed types SLEB128.
+///
+/// [`ToLeb128u`], [`ToLeb128i`], [`FromLeb128u`] and [`FromLeb128i`] remain
+/// for choosing the encoding explicitly.
+///
+/// `encode` and `decode` have defaults, so the required methods are the same
+/// whichever features are enabled.
+pub trait Leb128: Sized {
+    /// Number of bytes `encode` would write.
+    fn encoded_len(&self) -> usize;
+
+    #[cfg(feature = "std")]
+    fn encode(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        let mut buf = [MaybeUninit::uninit(); pending::SCRATCH_LEN];
+        let bytes = self.encode_uninit(&mut buf)?;
+        writer.write_all(bytes)?;
+        Ok(bytes.len())
+    }
+
+    /// Encodes into a possibly uninitialized buffer and returns the written prefix.
+    fn encode_uninit<'a>(&self, buf: &'a mut [MaybeUninit<u8>]) -> Result<&'a [u8], EncodeError>;
+
+    #[cfg(feature = "std")]
+    fn decode(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+        pending::read_with(reader, Self::decode_slice)
+    }
+
+    /// Decodes from the front of `bytes` and returns the value with the number
+    /// of bytes consumed.
+    fn decode_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error>;
+}
+
+macro_rules! impl_leb128 {
+    ($to:ident $from:ident $len_fn:ident $to_fn:ident $to_uninit:ident $from_fn:ident $slice_fn:ident: $($ty:ty),*) => {
+        $(
+            impl Leb128 for $ty {
+                fn encoded_len(&self) -> usize {
+                    $to::$len_fn(self)
+                }
+
+                #[cfg(feature = "std")]
+                fn encode(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+                    $to::$to_fn(self, writer)
+                }
+
+                fn encode_uninit<'a>(&self, buf: &'a mut [MaybeUninit<u8>]) -> Result<&'a [u8], EncodeError> {
+                    $to::$to_uninit(self, buf)
+                }
+
+                #[cfg(feature = "std")]
+                fn decode(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+                    <$ty as $from>::$from_fn(reader)
+                }
+
+                fn decode_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error> {
+                    <$ty as $from>::$slice_fn(bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_leb128!(
+    ToLeb128u FromLeb128u leb128u_len to_leb128u to_leb128u_uninit from_leb128u from_leb128u_slice:
+    u8, u16, u32, u64, u128, usize, char,
+    NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize
+);
+impl_leb128!(
+    ToLeb128i FromLeb128i leb128i_len to_leb128i to_leb128i_uninit from_leb128i from_leb128i_slice:
+    i8, i16, i32, i64, i128, isize,
+    NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize
+);
+
+/// Encodes every value as ULEB128 and returns the total number of bytes
+/// written.
+///
+/// Encodings are gathered in a stack buffer and written in large chunks, so
+/// unbuffered writers see few calls.
+#[cfg(feature = "std")]
+pub fn encode_all<I>(writer: &mut (impl Write + ?Sized), values: I) -> io::Result<usize>
+where
+    I: IntoIterator,
+    I::Item: ToLeb128u,
+{
+    let mut buf = [0; 4096];
+    let mut len = 0;
+    let mut total = 0;
+
+    for value in values {
+        if let Ok(n) = value.to_leb128u_buf(&mut buf[len..]) {
+            len += n;
+            continue;
+        }
+
+        writer.write_all(&buf[..len])?;
+        total += len;
+        len = 0;
+
+        match value.to_leb128u_buf(&mut buf) {
+            Ok(n) => len = n,
+            // Longer than the whole buffer.
+            Err(_) => total += value.to_leb128u(writer)?,
+        }
+    }
+
+    writer.write_all(&buf[..len])?;
+    Ok(total + len)
+}
+
+/// Decodes ULEB128 values until the end of input.
+///
+/// Input ending between values is success; ending in the middle of one is
+/// `FromLeb128Error::UnexpectedEof`. Values are read a few bytes at a time,
+/// so wrap unbuffered readers in a `BufReader`. Running out of memory is
+/// `FromLeb128Error::Alloc`.
+#[cfg(feature = "std")]
+pub fn decode_all<T: FromLeb128u>(
+    reader: &mut (impl Read + ?Sized),
+) -> Result<Vec<T>, FromLeb128Error> {
+    decode_all_with_progress(reader, &mut progress::silent())
+}
+
+/// Like [`decode_all`], reporting progress in input bytes and values.
+#[cfg(feature = "std")]
+pub fn decode_all_with_progress<T: FromLeb128u>(
+    reader: &mut (impl Read + ?Sized),
+    progress: &mut progress::ProgressReporter<impl FnMut(progress::Progress)>,
+) -> Result<Vec<T>, FromLeb128Error> {
+    let mut reader = stream::Counting::new(reader);
+    let mut values = Vec::new();
+    while let Some(value) = stream::read_u(&mut reader)? {
+        values.try_reserve(1)?;
+        values.push(value);
+        progress.value_at(reader.count());
+    }
+    progress.finish(reader.count());
+    Ok(values)
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
+    // Returns bytes it never wrote to `buf`.
+    struct Canned;
+
+    impl ToLeb128u for Canned {
+        const MAX_LEB128_LEN: usize = 2;
+
+        fn to_leb128u_uninit<'a>(
+            &self,
+            _: &'a mut [MaybeUninit<u8>],
+        ) -> Result<&'a [u8], EncodeError> {
+            Ok(&[0x80, 0x01])
+        }
+    }
+
     #[test]
     fn to_leb_128u() {
         let mut buf = Vec::new();
@@ -225,6 +1441,78 @@ mod tests {
         assert_eq!(buf, vec![0xbf, 0x7f]);
     }
 
+    #[test]
+    fn to_leb_128_uninit() {
+        let mut buf = [MaybeUninit::uninit(); 4];
+
+        assert_eq!(300u32.to_leb128u_uninit(&mut buf).unwrap(), &[0xac, 0x02]);
+        assert_eq!((-65i32).to_leb128i_uninit(&mut buf).unwrap(), &[0xbf, 0x7f]);
+        assert_eq!(
+            u32::MAX.to_leb128u_uninit(&mut buf).unwrap_err(),
+            EncodeError::BufferTooSmall
+        );
+    }
+
+    #[test]
+    fn error_is_small() {
+        // `io::Error` only has a null niche, so with more than one other
+        // variant the tag needs a word of its own; every other payload must
+        // fit in that word.
+        assert!(
+            std::mem::size_of::<FromLeb128Error>()
+                <= std::mem::size_of::<io::Error>() + std::mem::size_of::<usize>()
+        );
+        assert!(std::mem::size_of::<Result<u64, FromLeb128Error>>() <= 16);
+    }
+
+    #[test]
+    fn from_leb_128_exact() {
+        assert_eq!(u32::from_leb128u_exact(&[0xac, 0x02]).unwrap(), 300);
+        assert_eq!(i32::from_leb128i_exact(&[0x7f]).unwrap(), -1);
+        assert!(matches!(
+            u32::from_leb128u_exact(&[0xac, 0x02, 0x00]),
+            Err(FromLeb128Error::TrailingBytes(2))
+        ));
+        assert!(matches!(
+            i8::from_leb128i_exact(&[0x00, 0x00]),
+            Err(FromLeb128Error::TrailingBytes(1))
+        ));
+    }
+
+    #[test]
+    fn tuples() {
+        let mut buf = Vec::new();
+
+        assert_eq!((300u32, 1u64).to_leb128u(&mut buf).unwrap(), 3);
+        assert_eq!(buf, vec![0xac, 0x02, 0x01]);
+        assert_eq!(<(u32, u64)>::from_leb128u(&mut &buf[..]).unwrap(), (300, 1));
+
+        buf.clear();
+        (-1i16, 64i16, 0i32).to_leb128i(&mut buf).unwrap();
+        assert_eq!(buf, vec![0x7f, 0xc0, 0x00, 0x00]);
+        assert_eq!(
+            <(i16, i16, i32)>::from_leb128i(&mut &buf[..]).unwrap(),
+            (-1, 64, 0)
+        );
+
+        let mut uninit = [MaybeUninit::uninit(); 3];
+        assert_eq!(
+            (1u8, 2u8, 3u8).to_leb128u_uninit(&mut uninit).unwrap(),
+            &[1, 2, 3]
+        );
+        assert!((1u8, 2u8, 3u8, 4u8).to_leb128u_uninit(&mut uninit).is_err());
+
+        // Only bytes a field actually produced end up in the output.
+        let mut uninit = [MaybeUninit::uninit(); 8];
+        assert_eq!(
+            (1u8, Canned, 2u8).to_leb128u_uninit(&mut uninit).unwrap(),
+            &[0x01, 0x80, 0x01, 0x02]
+        );
+        assert!((Canned, Canned)
+            .to_leb128u_uninit(&mut uninit[..3])
+            .is_err());
+    }
+
     #[test]
     fn from_leb_128u() {
         let mut buf = Vec::new();
@@ -258,4 +1546,440 @@ mod tests {
             assert_eq!(i, i16::from_leb128i(&mut &buf[..]).unwrap());
         }
     }
+
+    #[test]
+    fn u128_i128() {
+        let mut buf = Vec::new();
+
+        for v in [0, 1 << 64, u64::MAX as u128 + 1, u128::MAX] {
+            buf.clear();
+            v.to_leb128u(&mut buf).unwrap();
+            assert_eq!(u128::from_leb128u_exact(&buf).unwrap(), v);
+        }
+        assert_eq!(buf.len(), 19);
+
+        for v in [0, -1, i64::MIN as i128 - 1, i128::MIN, i128::MAX] {
+            buf.clear();
+            v.to_leb128i(&mut buf).unwrap();
+            assert_eq!(i128::from_leb128i_exact(&buf).unwrap(), v);
+        }
+
+        let mut too_big = vec![0xff; 18];
+        too_big.push(0x04);
+        assert!(matches!(
+            u128::from_leb128u(&mut &too_big[..]),
+            Err(FromLeb128Error::Overflow)
+        ));
+    }
+
+    #[test]
+    fn decode_errors() {
+        for input in [&[0x80, 0x01][..], &[0x80, 0x7e]] {
+            assert!(matches!(
+                i8::from_leb128i(&mut &input[..]),
+                Err(FromLeb128Error::Overflow)
+            ));
+        }
+        assert_eq!(i8::from_leb128i(&mut &[0x80, 0x7f][..]).unwrap(), i8::MIN);
+        assert!(matches!(
+            i8::from_leb128i(&mut &[0x80, 0x80, 0x00][..]),
+            Err(FromLeb128Error::TooLong)
+        ));
+        assert!(matches!(
+            u8::from_leb128u(&mut &[0x80, 0x02][..]),
+            Err(FromLeb128Error::Overflow)
+        ));
+        assert!(matches!(
+            u64::from_leb128u(&mut &[0x80; 11][..]),
+            Err(FromLeb128Error::TooLong)
+        ));
+        assert!(matches!(
+            u32::from_leb128u(&mut &[0x80][..]),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn usize_isize() {
+        let mut buf = Vec::new();
+        300usize.to_leb128u(&mut buf).unwrap();
+        (-300isize).to_leb128i(&mut buf).unwrap();
+        assert_eq!(buf, vec![0xac, 0x02, 0xd4, 0x7d]);
+
+        let mut reader = &buf[..];
+        assert_eq!(usize::from_leb128u(&mut reader).unwrap(), 300);
+        assert_eq!(isize::from_leb128i(&mut reader).unwrap(), -300);
+
+        #[cfg(target_pointer_width = "32")]
+        assert!(matches!(
+            usize::from_leb128u(&mut &[0x80, 0x80, 0x80, 0x80, 0x10][..]),
+            Err(FromLeb128Error::Overflow)
+        ));
+    }
+
+    #[test]
+    fn nonzero() {
+        let id = NonZeroU32::new(300).unwrap();
+        let mut buf = Vec::new();
+        id.to_leb128u(&mut buf).unwrap();
+        NonZeroI16::new(-1).unwrap().to_leb128i(&mut buf).unwrap();
+        assert_eq!(buf, vec![0xac, 0x02, 0x7f]);
+
+        let mut reader = &buf[..];
+        assert_eq!(NonZeroU32::from_leb128u(&mut reader).unwrap(), id);
+        assert_eq!(NonZeroI16::from_leb128i(&mut reader).unwrap().get(), -1);
+        assert!(matches!(
+            NonZeroU64::from_leb128u(&mut &[0x00][..]),
+            Err(FromLeb128Error::Zero)
+        ));
+        assert!(matches!(
+            NonZeroI8::from_leb128i(&mut &[0x80, 0x00][..]),
+            Err(FromLeb128Error::Zero)
+        ));
+    }
+
+    #[test]
+    fn chars() {
+        let mut buf = Vec::new();
+        for c in ['a', 'é', '\u{10ffff}'] {
+            buf.clear();
+            c.to_leb128u(&mut buf).unwrap();
+            assert_eq!(char::from_leb128u_exact(&buf).unwrap(), c);
+        }
+        assert_eq!(buf, vec![0xff, 0xff, 0x43]);
+
+        assert!(matches!(
+            char::from_leb128u(&mut &[0x80, 0xb0, 0x03][..]),
+            Err(FromLeb128Error::InvalidChar(0xd800))
+        ));
+        assert!(matches!(
+            char::from_leb128u(&mut &[0x80, 0x80, 0x44][..]),
+            Err(FromLeb128Error::InvalidChar(0x110000))
+        ));
+    }
+
+    #[test]
+    fn from_leb_128_slice() {
+        assert_eq!(
+            u32::from_leb128u_slice(&[0xac, 0x02, 0xff]).unwrap(),
+            (300, 2)
+        );
+        assert_eq!(i8::from_leb128i_slice(&[0x7f]).unwrap(), (-1, 1));
+        assert_eq!(
+            <(u8, u16)>::from_leb128u_slice(&[1, 0x80, 0x01, 9]).unwrap(),
+            ((1, 128), 3)
+        );
+        assert!(u16::from_leb128u_slice(&[0x80]).is_err());
+    }
+
+    #[test]
+    fn to_leb_128_buf() {
+        let mut buf = [0; 4];
+        assert_eq!(300u32.to_leb128u_buf(&mut buf).unwrap(), 2);
+        assert_eq!(buf[..2], [0xac, 0x02]);
+        assert_eq!((-1i64, 2i8).to_leb128i_buf(&mut buf).unwrap(), 2);
+        assert_eq!(buf[..2], [0x7f, 0x02]);
+        assert_eq!(
+            u64::MAX.to_leb128u_buf(&mut buf),
+            Err(EncodeError::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn required_items_only() {
+        // Decodes through the default `from_leb128u`.
+        #[derive(Debug, PartialEq)]
+        struct Id(u16);
+
+        impl FromLeb128u for Id {
+            fn from_leb128u_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error> {
+                u16::from_leb128u_slice(bytes).map(|(id, len)| (Id(id), len))
+            }
+        }
+
+        let mut buf = Vec::new();
+        assert_eq!(Canned.to_leb128u(&mut buf).unwrap(), 2);
+        assert_eq!(Canned.leb128u_len(), 2);
+        assert_eq!(Canned.to_leb128u_vec(), buf);
+        assert_eq!(Id::from_leb128u(&mut &buf[..]).unwrap(), Id(128));
+        assert!(matches!(
+            Id::from_leb128u_canonical(&mut &[0x80, 0x00][..]),
+            Err(FromLeb128Error::NonCanonical)
+        ));
+        assert!(matches!(
+            Id::from_leb128u(&mut &[0x80][..]),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn to_leb_128_buf_copies() {
+        let mut buf = [0; 3];
+        assert_eq!(Canned.to_leb128u_buf(&mut buf).unwrap(), 2);
+        assert_eq!(buf, [0x80, 0x01, 0x00]);
+        assert_eq!(
+            Canned.to_leb128u_buf(&mut buf[..1]),
+            Err(EncodeError::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn to_leb_128_vec() {
+        assert_eq!(300u32.to_leb128u_vec(), vec![0xac, 0x02]);
+        assert_eq!((-65i32).to_leb128i_vec(), vec![0xbf, 0x7f]);
+        assert_eq!((1u8, u64::MAX).to_leb128u_vec().len(), 11);
+    }
+
+    #[test]
+    fn trait_objects() {
+        let mut buf = Vec::new();
+        let writer: &mut dyn Write = &mut buf;
+        assert_eq!(300u32.to_leb128u(writer).unwrap(), 2);
+        assert_eq!((-65i64, 1i8).to_leb128i(writer).unwrap(), 3);
+
+        let mut bytes = &buf[..];
+        let reader: &mut dyn Read = &mut bytes;
+        assert_eq!(u32::from_leb128u(reader).unwrap(), 300);
+        assert_eq!(i64::from_leb128i_canonical(reader).unwrap(), -65);
+        assert_eq!(i8::from_leb128i_positioned(reader).unwrap(), 1);
+    }
+
+    #[test]
+    fn unified_trait() {
+        fn round_trip<T: Leb128 + PartialEq + core::fmt::Debug>(value: T, expected: &[u8]) {
+            let mut buf = Vec::new();
+            assert_eq!(value.encode(&mut buf).unwrap(), expected.len());
+            assert_eq!(buf, expected);
+            assert_eq!(value.encoded_len(), expected.len());
+            assert_eq!(T::decode(&mut &buf[..]).unwrap(), value);
+            assert_eq!(T::decode_slice(&buf).unwrap(), (value, expected.len()));
+        }
+
+        round_trip(300u32, &[0xac, 0x02]);
+        round_trip(-65i64, &[0xbf, 0x7f]);
+        round_trip(127u8, &[0x7f]);
+        round_trip(127i8, &[0xff, 0x00]);
+        round_trip(NonZeroI16::MIN, &[0x80, 0x80, 0x7e]);
+    }
+
+    #[test]
+    fn unified_trait_defaults() {
+        #[derive(Debug, PartialEq)]
+        struct Small(u8);
+
+        impl Leb128 for Small {
+            fn encoded_len(&self) -> usize {
+                self.0.leb128u_len()
+            }
+
+            fn encode_uninit<'a>(
+                &self,
+                buf: &'a mut [MaybeUninit<u8>],
+            ) -> Result<&'a [u8], EncodeError> {
+                self.0.to_leb128u_uninit(buf)
+            }
+
+            fn decode_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error> {
+                u8::from_leb128u_slice(bytes).map(|(v, len)| (Small(v), len))
+            }
+        }
+
+        let mut buf = Vec::new();
+        assert_eq!(Small(200).encode(&mut buf).unwrap(), 2);
+        assert_eq!(buf, [0xc8, 0x01]);
+        assert_eq!(Small::decode(&mut &buf[..]).unwrap(), Small(200));
+    }
+
+    #[test]
+    fn encode_all_values() {
+        let mut buf = Vec::new();
+        assert_eq!(encode_all(&mut buf, [1u32, 300, 0]).unwrap(), 4);
+        assert_eq!(buf, [0x01, 0xac, 0x02, 0x00]);
+
+        let values: Vec<u64> = (0..2000).map(|i| i << 20).collect();
+        let mut expected = Vec::new();
+        for v in &values {
+            v.to_leb128u(&mut expected).unwrap();
+        }
+        let mut buf = Vec::new();
+        assert_eq!(
+            encode_all(&mut buf, values.iter().copied()).unwrap(),
+            expected.len()
+        );
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn decode_all_values() {
+        let values = decode_all::<u32>(&mut &[0x01, 0xac, 0x02, 0x00][..]).unwrap();
+        assert_eq!(values, [1, 300, 0]);
+        assert!(decode_all::<u8>(&mut &[][..]).unwrap().is_empty());
+        assert!(matches!(
+            decode_all::<u32>(&mut &[0x01, 0xac][..]),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn decode_all_progress() {
+        let mut reports = Vec::new();
+        let mut progress = progress::ProgressReporter::new(2, |p: progress::Progress| {
+            reports.push((p.bytes, p.values))
+        });
+        let values =
+            decode_all_with_progress::<u32>(&mut &[0x01, 0xac, 0x02, 0x00][..], &mut progress)
+                .unwrap();
+
+        assert_eq!(values, [1, 300, 0]);
+        assert_eq!(reports, [(3, 2), (4, 3)]);
+    }
+
+    #[test]
+    fn to_leb_128_array() {
+        let (buf, len) = 300u32.to_leb128u_array();
+        assert_eq!(&buf[..len], &[0xac, 0x02]);
+        assert_eq!(u128::MAX.to_leb128u_array().1, 19);
+        assert_eq!(i8::MIN.to_leb128i_array(), ([0x80, 0x7f], 2));
+        assert_eq!('\u{10ffff}'.to_leb128u_array(), ([0xff, 0xff, 0x43], 3));
+    }
+
+    #[test]
+    fn max_leb128_len() {
+        assert_eq!(u8::MAX_LEB128_LEN, 2);
+        assert_eq!(u32::MAX_LEB128_LEN, 5);
+        assert_eq!(<u64 as ToLeb128u>::MAX_LEB128_LEN, 10);
+        assert_eq!(<i64 as ToLeb128i>::MAX_LEB128_LEN, 10);
+        assert_eq!(u128::MAX_LEB128_LEN, 19);
+        assert_eq!(<(u8, u32)>::MAX_LEB128_LEN, 7);
+
+        let mut buf = Vec::new();
+        assert_eq!(u32::MAX.to_leb128u(&mut buf).unwrap(), u32::MAX_LEB128_LEN);
+        assert_eq!(
+            i128::MIN.to_leb128i(&mut buf).unwrap(),
+            <i128 as ToLeb128i>::MAX_LEB128_LEN
+        );
+    }
+
+    #[test]
+    fn leb128_len() {
+        let mut buf = Vec::new();
+
+        for v in [0u64, 127, 128, 16383, 16384, u64::MAX] {
+            assert_eq!(v.leb128u_len(), v.to_leb128u(&mut buf).unwrap());
+        }
+        for v in [0i64, 63, 64, -64, -65, i64::MIN, i64::MAX] {
+            assert_eq!(v.leb128i_len(), v.to_leb128i(&mut buf).unwrap());
+        }
+        for v in i8::MIN..=i8::MAX {
+            assert_eq!(v.leb128i_len(), v.to_leb128i(&mut buf).unwrap());
+        }
+        assert_eq!((1u8, 300u16).leb128u_len(), 3);
+        assert_eq!(NonZeroU32::MAX.leb128u_len(), 5);
+    }
+
+    #[test]
+    fn from_leb_128_counted() {
+        let mut reader = io::BufReader::new(&[0xac, 0x02, 0x7f, 1][..]);
+        assert_eq!(u64::from_leb128u_counted(&mut reader).unwrap(), (300, 2));
+        assert_eq!(i32::from_leb128i_counted(&mut reader).unwrap(), (-1, 1));
+        assert_eq!(
+            <(u8,)>::from_leb128u_counted(&mut reader).unwrap(),
+            ((1,), 1)
+        );
+    }
+
+    #[test]
+    fn short_writes() {
+        let mut out = [0; 1];
+        let e = 300u32.to_leb128u(&mut &mut out[..]).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::WriteZero);
+
+        // One byte per call is retried until the whole value is written.
+        struct Trickle(Vec<u8>);
+
+        impl Write for Trickle {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.extend_from_slice(&buf[..buf.len().min(1)]);
+                Ok(buf.len().min(1))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut w = Trickle(Vec::new());
+        assert_eq!((-300i64).to_leb128i(&mut w).unwrap(), 2);
+        assert_eq!(w.0, vec![0xd4, 0x7d]);
+    }
+
+    #[test]
+    fn canonical_and_lenient() {
+        assert_eq!(u32::from_leb128u(&mut &[0x80, 0x00][..]).unwrap(), 0);
+        assert!(matches!(
+            u32::from_leb128u_canonical(&mut &[0x80, 0x00][..]),
+            Err(FromLeb128Error::NonCanonical)
+        ));
+        assert_eq!(u32::from_leb128u_canonical(&mut &[0x00][..]).unwrap(), 0);
+        assert_eq!(
+            u32::from_leb128u_canonical(&mut &[0x80, 0x01][..]).unwrap(),
+            128
+        );
+
+        for input in [&[0xff, 0x7f][..], &[0x80, 0x00], &[0xc0, 0x7f]] {
+            assert!(matches!(
+                i32::from_leb128i_canonical(&mut &input[..]),
+                Err(FromLeb128Error::NonCanonical)
+            ));
+        }
+        assert_eq!(
+            i32::from_leb128i_canonical(&mut &[0xc0, 0x00][..]).unwrap(),
+            64
+        );
+        assert_eq!(
+            i32::from_leb128i_canonical(&mut &[0xbf, 0x7f][..]).unwrap(),
+            -65
+        );
+
+        let padded = [0x81, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00];
+        assert!(matches!(
+            u32::from_leb128u(&mut &padded[..]),
+            Err(FromLeb128Error::TooLong)
+        ));
+        assert_eq!(u32::from_leb128u_lenient(&mut &padded[..]).unwrap(), 1);
+        assert!(matches!(
+            <(u8, u8)>::from_leb128u_canonical(&mut &[0x80, 0x00, 0x01][..]),
+            Err(FromLeb128Error::NonCanonical)
+        ));
+        assert!(matches!(
+            u8::from_leb128u_lenient(&mut &[0x80, 0x02][..]),
+            Err(FromLeb128Error::Overflow)
+        ));
+        assert_eq!(
+            i8::from_leb128i_lenient(&mut &[0xff, 0xff, 0xff, 0x7f][..]).unwrap(),
+            -1
+        );
+    }
+
+    #[test]
+    fn max_len() {
+        let five = [0xff, 0xff, 0xff, 0xff, 0x0f];
+        assert_eq!(
+            u64::from_leb128u_max(&mut &five[..], 5).unwrap(),
+            0xffff_ffff
+        );
+
+        let mut reader = &[0x80, 0x80, 0x80, 0x80, 0x80, 0x01][..];
+        assert!(matches!(
+            u64::from_leb128u_max(&mut reader, 5),
+            Err(FromLeb128Error::TooLong)
+        ));
+        assert_eq!(reader, &[0x01]);
+
+        assert!(matches!(
+            i64::from_leb128i_max(&mut &[0x80][..], 5),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+        assert_eq!(i64::from_leb128i_max(&mut &[0x7f][..], 1).unwrap(), -1);
+    }
 }
@@ -0,0 +1,243 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{stream, string, FromLeb128Error, ToLeb128u};
+
+/// Append-only file of records, each prefixed with its length as ULEB128.
+#[derive(Debug)]
+pub struct Leb128Log {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    sync: bool,
+    poisoned: bool,
+}
+
+impl Leb128Log {
+    /// Opens (or creates) a log, truncating a torn trailing record first.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        recover_file(&mut file)?;
+        let len = file.seek(SeekFrom::End(0))?;
+
+        Ok(Self {
+            path,
+            file,
+            len,
+            sync: false,
+            poisoned: false,
+        })
+    }
+
+    /// When enabled, every `append` is followed by `fsync`.
+    pub fn set_sync(&mut self, sync: bool) {
+        self.sync = sync;
+    }
+
+    /// Appends a record and returns the offset it was written at.
+    ///
+    /// If the write fails the file is truncated back to its previous length,
+    /// so no torn record is left in the middle of the log. If that also
+    /// fails, every later `append` fails too.
+    pub fn append(&mut self, record: &[u8]) -> io::Result<u64> {
+        if self.poisoned {
+            return Err(io::Error::other("log poisoned by a failed append"));
+        }
+
+        let mut buf = Vec::with_capacity(record.len() + 10);
+        (record.len() as u64).to_leb128u(&mut buf)?;
+        buf.extend_from_slice(record);
+
+        if let Err(e) = self.write(&buf) {
+            if self.rollback().is_err() {
+                self.poisoned = true;
+            }
+            return Err(e);
+        }
+
+        let offset = self.len;
+        self.len += buf.len() as u64;
+        Ok(offset)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.file.write_all(buf)?;
+        if self.sync {
+            self.file.sync_data()?;
+        }
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> io::Result<()> {
+        self.file.set_len(self.len)?;
+        self.file.seek(SeekFrom::Start(self.len))?;
+        Ok(())
+    }
+
+    pub fn sync(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    /// Size of the log in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterates the records from the start of the log.
+    pub fn records(&self) -> io::Result<LogReader<BufReader<File>>> {
+        Ok(LogReader::new(BufReader::new(File::open(&self.path)?)))
+    }
+}
+
+/// Truncates a torn trailing record from the log at `path`.
+///
+/// Returns the number of bytes removed. Only a record cut short by the end of
+/// the file counts as torn; a malformed length prefix before that is reported
+/// as an `io::ErrorKind::InvalidData` error and the file is left untouched.
+pub fn recover(path: impl AsRef<Path>) -> io::Result<u64> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    recover_file(&mut file)
+}
+
+fn recover_file(file: &mut File) -> io::Result<u64> {
+    let total = file.seek(SeekFrom::End(0))?;
+    file.seek(SeekFrom::Start(0))?;
+
+    let mut reader = stream::Counting::new(BufReader::new(&mut *file));
+    let mut valid = 0;
+
+    loop {
+        match read_record(&mut reader) {
+            Ok(Some(_)) => valid = reader.count(),
+            Ok(None) => break,
+            Err(FromLeb128Error::UnexpectedEof) => break,
+            Err(FromLeb128Error::Io(e)) => return Err(e),
+            Err(e) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("corrupt record at offset {}: {}", valid, e),
+                ))
+            }
+        }
+    }
+
+    if valid < total {
+        file.set_len(valid)?;
+        file.sync_all()?;
+    }
+
+    Ok(total - valid)
+}
+
+/// Reads length-prefixed records until a clean end of input.
+#[derive(Debug)]
+pub struct LogReader<R> {
+    reader: R,
+}
+
+impl<R: Read> LogReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+}
+
+impl<R: Read> Iterator for LogReader<R> {
+    type Item = Result<Vec<u8>, FromLeb128Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        read_record(&mut self.reader).transpose()
+    }
+}
+
+fn read_record(reader: &mut impl Read) -> Result<Option<Vec<u8>>, FromLeb128Error> {
+    let len = match stream::read_u::<u64>(reader)? {
+        Some(len) => len,
+        None => return Ok(None),
+    };
+    let mut record = Vec::new();
+    string::read_exact_into(reader, len, &mut record)?;
+    Ok(Some(record))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_and_recover() {
+        let path = std::env::temp_dir().join(format!("leb128-log-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut log = Leb128Log::open(&path).unwrap();
+        assert_eq!(log.append(b"hello").unwrap(), 0);
+        assert_eq!(log.append(&[7; 200]).unwrap(), 6);
+        drop(log);
+
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[0x90, 0x01, 1, 2, 3]).unwrap();
+        drop(file);
+
+        assert_eq!(recover(&path).unwrap(), 5);
+
+        let mut log = Leb128Log::open(&path).unwrap();
+        log.append(b"!").unwrap();
+        let records = log
+            .records()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            records,
+            vec![b"hello".to_vec(), vec![7; 200], b"!".to_vec()]
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn failed_append_poisons() {
+        let path = std::env::temp_dir().join(format!("leb128-log-ro-{}", std::process::id()));
+        std::fs::write(&path, b"").unwrap();
+
+        // Writing and truncating both fail through a read-only handle.
+        let mut log = Leb128Log {
+            path: path.clone(),
+            file: File::open(&path).unwrap(),
+            len: 0,
+            sync: false,
+            poisoned: false,
+        };
+        assert!(log.append(b"hello").is_err());
+        assert!(log.poisoned);
+        assert!(log.append(b"").is_err());
+        assert_eq!(log.len(), 0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn recover_rejects_corruption() {
+        let path = std::env::temp_dir().join(format!("leb128-log-bad-{}", std::process::id()));
+        let mut bytes = vec![0x01, b'a'];
+        bytes.extend_from_slice(&[0xff; 11]);
+        bytes.extend_from_slice(&[0x01, b'b']);
+        std::fs::write(&path, &bytes).unwrap();
+
+        let e = recover(&path).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), bytes.len() as u64);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
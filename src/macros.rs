@@ -0,0 +1,91 @@
+/// Declares a struct together with `to_leb128` / `from_leb128` methods that
+/// encode its fields in order.
+///
+/// Each field names its encoding: `as unsigned` for ULEB128, `as signed` for
+/// SLEB128.
+///
+/// ```
+/// leb128_rs::leb128_struct! {
+///     #[derive(Debug, PartialEq)]
+///     pub struct Header {
+///         pub version: u32 as unsigned,
+///         pub offset: i64 as signed,
+///     }
+/// }
+///
+/// let header = Header { version: 1, offset: -2 };
+/// let mut buf = Vec::new();
+/// header.to_leb128(&mut buf).unwrap();
+/// assert_eq!(buf, [0x01, 0x7e]);
+/// assert_eq!(Header::from_leb128(&mut &buf[..]).unwrap(), header);
+/// ```
+#[macro_export]
+macro_rules! leb128_struct {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($field_vis:vis $field:ident : $ty:ty as $enc:ident),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($field_vis $field: $ty),*
+        }
+
+        impl $name {
+            #[allow(dead_code)]
+            $vis fn to_leb128(&self, writer: &mut impl ::std::io::Write) -> ::std::io::Result<usize> {
+                let mut count = 0;
+                $(count += $crate::leb128_struct!(@encode $enc, &self.$field, writer);)*
+                Ok(count)
+            }
+
+            #[allow(dead_code)]
+            $vis fn from_leb128(
+                reader: &mut impl ::std::io::Read,
+            ) -> ::std::result::Result<Self, $crate::FromLeb128Error> {
+                Ok(Self {
+                    $($field: $crate::leb128_struct!(@decode $enc, $ty, reader),)*
+                })
+            }
+        }
+    };
+    (@encode unsigned, $value:expr, $writer:ident) => {
+        $crate::ToLeb128u::to_leb128u($value, $writer)?
+    };
+    (@encode signed, $value:expr, $writer:ident) => {
+        $crate::ToLeb128i::to_leb128i($value, $writer)?
+    };
+    (@decode unsigned, $ty:ty, $reader:ident) => {
+        <$ty as $crate::FromLeb128u>::from_leb128u($reader)?
+    };
+    (@decode signed, $ty:ty, $reader:ident) => {
+        <$ty as $crate::FromLeb128i>::from_leb128i($reader)?
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    crate::leb128_struct! {
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct Record {
+            id: u64 as unsigned,
+            delta: i32 as signed,
+            flags: u8 as unsigned,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let record = Record {
+            id: 300,
+            delta: -65,
+            flags: 0x80,
+        };
+        let mut buf = Vec::new();
+
+        assert_eq!(record.to_leb128(&mut buf).unwrap(), 6);
+        assert_eq!(buf, vec![0xac, 0x02, 0xbf, 0x7f, 0x80, 0x01]);
+        assert_eq!(Record::from_leb128(&mut &buf[..]).unwrap(), record);
+    }
+}
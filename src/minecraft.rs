@@ -0,0 +1,66 @@
+//! The Minecraft protocol's `VarInt` and `VarLong`.
+//!
+//! Both are the two's complement of the value written as ULEB128, so a
+//! negative `VarInt` always takes five bytes and a negative `VarLong` ten.
+//! Longer input fails with `FromLeb128Error::TooLong`.
+
+use std::io::{self, Read, Write};
+
+use crate::{FromLeb128Error, FromLeb128u, ToLeb128u};
+
+pub fn write_varint(value: i32, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    (value as u32).to_leb128u(writer)
+}
+
+pub fn read_varint(reader: &mut (impl Read + ?Sized)) -> Result<i32, FromLeb128Error> {
+    u32::from_leb128u(reader).map(|value| value as i32)
+}
+
+/// Number of bytes `write_varint` would write.
+pub fn varint_len(value: i32) -> usize {
+    (value as u32).leb128u_len()
+}
+
+pub fn write_varlong(value: i64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    (value as u64).to_leb128u(writer)
+}
+
+pub fn read_varlong(reader: &mut (impl Read + ?Sized)) -> Result<i64, FromLeb128Error> {
+    u64::from_leb128u(reader).map(|value| value as i64)
+}
+
+/// Number of bytes `write_varlong` would write.
+pub fn varlong_len(value: i64) -> usize {
+    (value as u64).leb128u_len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wiki_examples() {
+        for (value, bytes) in [
+            (0, &[0x00][..]),
+            (255, &[0xff, 0x01]),
+            (2147483647, &[0xff, 0xff, 0xff, 0xff, 0x07]),
+            (-1, &[0xff, 0xff, 0xff, 0xff, 0x0f]),
+            (-2147483648, &[0x80, 0x80, 0x80, 0x80, 0x08]),
+        ] {
+            let mut buf = Vec::new();
+            assert_eq!(write_varint(value, &mut buf).unwrap(), bytes.len());
+            assert_eq!(buf, bytes);
+            assert_eq!(varint_len(value), bytes.len());
+            assert_eq!(read_varint(&mut &buf[..]).unwrap(), value);
+        }
+
+        let mut buf = Vec::new();
+        assert_eq!(write_varlong(-1, &mut buf).unwrap(), 10);
+        assert_eq!(varlong_len(-1), 10);
+        assert_eq!(read_varlong(&mut &buf[..]).unwrap(), -1);
+        assert!(matches!(
+            read_varint(&mut &[0x80, 0x80, 0x80, 0x80, 0x80, 0x01][..]),
+            Err(FromLeb128Error::TooLong)
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+//! The multiformats unsigned-varint, used by multiaddr, multicodec and
+//! multihash.
+//!
+//! It is ULEB128 restricted to minimal encodings of at most nine bytes, so
+//! values are below 2^63. Decoding reports a non-minimal encoding as
+//! `FromLeb128Error::NonCanonical` and one longer than nine bytes as
+//! `FromLeb128Error::TooLong`.
+
+use std::io::{self, Read, Write};
+
+use crate::{stream, FromLeb128Error, FromLeb128u, ToLeb128u};
+
+/// Longest encoding the spec allows.
+pub const MAX_LEN: usize = 9;
+
+/// Largest value the spec allows.
+pub const MAX_VALUE: u64 = (1 << 63) - 1;
+
+/// Writes `value` and returns the number of bytes written.
+///
+/// Fails with `io::ErrorKind::InvalidInput` if `value` is above
+/// [`MAX_VALUE`].
+pub fn encode(value: u64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    if value > MAX_VALUE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "value above 2^63 - 1",
+        ));
+    }
+    value.to_leb128u(writer)
+}
+
+/// Reads one value.
+///
+/// At most [`MAX_LEN`] bytes are read, so a tenth byte is never consumed.
+pub fn decode(reader: &mut (impl Read + ?Sized)) -> Result<u64, FromLeb128Error> {
+    let mut buf = [0; MAX_LEN];
+
+    for len in 1..=MAX_LEN {
+        buf[len - 1] = stream::read_byte(reader)?;
+        if buf[len - 1] & 0b10000000 == 0 {
+            return decode_slice(&buf[..len]).map(|(value, _)| value);
+        }
+    }
+
+    Err(FromLeb128Error::TooLong)
+}
+
+/// Decodes from the front of `bytes` and returns the value with the number of
+/// bytes consumed.
+pub fn decode_slice(bytes: &[u8]) -> Result<(u64, usize), FromLeb128Error> {
+    let (value, len) = u64::from_leb128u_slice(bytes)?;
+    if len > MAX_LEN {
+        return Err(FromLeb128Error::TooLong);
+    }
+    if len > 1 && bytes[len - 1] == 0 {
+        return Err(FromLeb128Error::NonCanonical);
+    }
+    Ok((value, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spec_rules() {
+        let mut buf = Vec::new();
+        assert_eq!(encode(300, &mut buf).unwrap(), 2);
+        assert_eq!(encode(MAX_VALUE, &mut buf).unwrap(), 9);
+        assert!(encode(MAX_VALUE + 1, &mut buf).is_err());
+
+        assert_eq!(decode_slice(&buf).unwrap(), (300, 2));
+        assert_eq!(decode(&mut &buf[2..]).unwrap(), MAX_VALUE);
+
+        for padded in [&[0x81, 0x00][..], &[0x80, 0x80, 0x00]] {
+            assert!(matches!(
+                decode_slice(padded),
+                Err(FromLeb128Error::NonCanonical)
+            ));
+            assert!(matches!(
+                decode(&mut &padded[..]),
+                Err(FromLeb128Error::NonCanonical)
+            ));
+        }
+        assert_eq!(decode_slice(&[0x00]).unwrap(), (0, 1));
+
+        let ten = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+        assert!(matches!(decode_slice(&ten), Err(FromLeb128Error::TooLong)));
+        let mut reader = &ten[..];
+        assert!(matches!(decode(&mut reader), Err(FromLeb128Error::TooLong)));
+        assert_eq!(reader, [0x00]);
+        assert!(matches!(
+            decode(&mut &(1u64 << 63).to_leb128u_vec()[..]),
+            Err(FromLeb128Error::TooLong)
+        ));
+    }
+}
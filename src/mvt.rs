@@ -0,0 +1,188 @@
+//! Mapbox Vector Tile geometry encoding.
+//!
+//! A geometry is a sequence of ULEB128 `u32`s: command integers (command id
+//! and repeat count) each followed by zigzag-encoded coordinate deltas.
+
+use std::io::{self, Write};
+
+use crate::{zigzag, FromLeb128Error, FromLeb128u, ToLeb128u};
+
+pub const MOVE_TO: u32 = 1;
+pub const LINE_TO: u32 = 2;
+pub const CLOSE_PATH: u32 = 7;
+
+/// A drawing operation with absolute tile coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeometryOp {
+    MoveTo(i32, i32),
+    LineTo(i32, i32),
+    ClosePath,
+}
+
+impl GeometryOp {
+    fn id(&self) -> u32 {
+        match self {
+            GeometryOp::MoveTo(..) => MOVE_TO,
+            GeometryOp::LineTo(..) => LINE_TO,
+            GeometryOp::ClosePath => CLOSE_PATH,
+        }
+    }
+}
+
+pub fn command_integer(id: u32, count: u32) -> u32 {
+    (id & 0b111) | (count << 3)
+}
+
+/// Splits a command integer into its id and count.
+pub fn parse_command_integer(value: u32) -> (u32, u32) {
+    (value & 0b111, value >> 3)
+}
+
+/// Encodes `ops`, merging consecutive `MoveTo`s and `LineTo`s into one command.
+pub fn encode_geometry(ops: &[GeometryOp], writer: &mut impl Write) -> io::Result<usize> {
+    let mut count = 0;
+    let mut cursor = (0i32, 0i32);
+    let mut rest = ops;
+
+    while let Some(first) = rest.first() {
+        let run = match first {
+            GeometryOp::ClosePath => 1,
+            _ => rest.iter().take_while(|op| op.id() == first.id()).count(),
+        };
+        let run = run.min(u32::MAX as usize >> 3);
+
+        count += command_integer(first.id(), run as u32).to_leb128u(writer)?;
+
+        for op in &rest[..run] {
+            if let GeometryOp::MoveTo(x, y) | GeometryOp::LineTo(x, y) = *op {
+                count += zigzag::encode_i32(x.wrapping_sub(cursor.0)).to_leb128u(writer)?;
+                count += zigzag::encode_i32(y.wrapping_sub(cursor.1)).to_leb128u(writer)?;
+                cursor = (x, y);
+            }
+        }
+
+        rest = &rest[run..];
+    }
+
+    Ok(count)
+}
+
+/// Iterates the operations of an encoded geometry.
+pub fn decode_geometry(bytes: &[u8]) -> GeometryIter<'_> {
+    GeometryIter {
+        bytes,
+        cursor: (0, 0),
+        command: 0,
+        remaining: 0,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GeometryIter<'a> {
+    bytes: &'a [u8],
+    cursor: (i32, i32),
+    command: u32,
+    remaining: u32,
+}
+
+impl GeometryIter<'_> {
+    fn read_op(&mut self) -> Result<GeometryOp, FromLeb128Error> {
+        while self.remaining == 0 {
+            let (id, count) = parse_command_integer(u32::from_leb128u(&mut self.bytes)?);
+            match id {
+                CLOSE_PATH if count != 1 => return Err(FromLeb128Error::Malformed),
+                // Each point takes at least two bytes.
+                MOVE_TO | LINE_TO if count as usize > self.bytes.len() / 2 => {
+                    return Err(FromLeb128Error::UnexpectedEof)
+                }
+                MOVE_TO | LINE_TO | CLOSE_PATH => {}
+                _ => return Err(FromLeb128Error::Malformed),
+            }
+            self.command = id;
+            self.remaining = count;
+        }
+        self.remaining -= 1;
+
+        if self.command == CLOSE_PATH {
+            return Ok(GeometryOp::ClosePath);
+        }
+
+        let dx = zigzag::decode_i32(u32::from_leb128u(&mut self.bytes)?);
+        let dy = zigzag::decode_i32(u32::from_leb128u(&mut self.bytes)?);
+        self.cursor = (
+            self.cursor.0.wrapping_add(dx),
+            self.cursor.1.wrapping_add(dy),
+        );
+
+        if self.command == MOVE_TO {
+            Ok(GeometryOp::MoveTo(self.cursor.0, self.cursor.1))
+        } else {
+            Ok(GeometryOp::LineTo(self.cursor.0, self.cursor.1))
+        }
+    }
+}
+
+impl Iterator for GeometryIter<'_> {
+    type Item = Result<GeometryOp, FromLeb128Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 && self.bytes.is_empty() {
+            return None;
+        }
+
+        let op = self.read_op();
+        if op.is_err() {
+            self.bytes = &[];
+            self.remaining = 0;
+        }
+
+        Some(op)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polygon_from_spec() {
+        // The polygon example from the vector tile specification.
+        let ops = [
+            GeometryOp::MoveTo(3, 6),
+            GeometryOp::LineTo(8, 12),
+            GeometryOp::LineTo(20, 34),
+            GeometryOp::ClosePath,
+        ];
+        let mut buf = Vec::new();
+        encode_geometry(&ops, &mut buf).unwrap();
+
+        assert_eq!(buf, vec![9, 6, 12, 18, 10, 12, 24, 44, 15]);
+        let decoded = decode_geometry(&buf)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, ops);
+    }
+
+    #[test]
+    fn unknown_command() {
+        let bytes = [command_integer(3, 1) as u8, 0, 0];
+        let mut ops = decode_geometry(&bytes);
+        assert!(ops.next().unwrap().is_err());
+        assert!(ops.next().is_none());
+    }
+
+    #[test]
+    fn bounded_counts() {
+        let close = [0xff, 0xff, 0xff, 0xff, 0x0f];
+        assert!(matches!(
+            decode_geometry(&close).next(),
+            Some(Err(FromLeb128Error::Malformed))
+        ));
+
+        let line_to = [command_integer(LINE_TO, 3) as u8, 0, 0, 0, 0];
+        assert!(matches!(
+            decode_geometry(&line_to).next(),
+            Some(Err(FromLeb128Error::UnexpectedEof))
+        ));
+    }
+}
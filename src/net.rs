@@ -0,0 +1,97 @@
+//! Encoding of IP addresses and socket addresses.
+//!
+//! An `IpAddr` is a family tag byte (4 or 6) followed by the address octets; a
+//! `SocketAddr` additionally has its port as ULEB128. The IPv6 flow label and
+//! scope id are not stored.
+
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use crate::{FromLeb128Error, FromLeb128u, ToLeb128u};
+
+const TAG_V4: u8 = 4;
+const TAG_V6: u8 = 6;
+
+pub fn encode_ipv4(addr: Ipv4Addr, writer: &mut impl Write) -> io::Result<usize> {
+    writer.write_all(&addr.octets())?;
+    Ok(4)
+}
+
+pub fn decode_ipv4(reader: &mut impl Read) -> Result<Ipv4Addr, FromLeb128Error> {
+    let mut octets = [0; 4];
+    reader.read_exact(&mut octets)?;
+    Ok(Ipv4Addr::from(octets))
+}
+
+pub fn encode_ipv6(addr: Ipv6Addr, writer: &mut impl Write) -> io::Result<usize> {
+    writer.write_all(&addr.octets())?;
+    Ok(16)
+}
+
+pub fn decode_ipv6(reader: &mut impl Read) -> Result<Ipv6Addr, FromLeb128Error> {
+    let mut octets = [0; 16];
+    reader.read_exact(&mut octets)?;
+    Ok(Ipv6Addr::from(octets))
+}
+
+pub fn encode_ip(addr: IpAddr, writer: &mut impl Write) -> io::Result<usize> {
+    match addr {
+        IpAddr::V4(addr) => {
+            writer.write_all(&[TAG_V4])?;
+            Ok(1 + encode_ipv4(addr, writer)?)
+        }
+        IpAddr::V6(addr) => {
+            writer.write_all(&[TAG_V6])?;
+            Ok(1 + encode_ipv6(addr, writer)?)
+        }
+    }
+}
+
+/// Fails with `FromLeb128Error::Malformed` on an unknown family tag.
+pub fn decode_ip(reader: &mut impl Read) -> Result<IpAddr, FromLeb128Error> {
+    let mut tag = [0; 1];
+    reader.read_exact(&mut tag)?;
+
+    match tag[0] {
+        TAG_V4 => decode_ipv4(reader).map(IpAddr::V4),
+        TAG_V6 => decode_ipv6(reader).map(IpAddr::V6),
+        _ => Err(FromLeb128Error::Malformed),
+    }
+}
+
+pub fn encode_socket_addr(addr: SocketAddr, writer: &mut impl Write) -> io::Result<usize> {
+    Ok(encode_ip(addr.ip(), writer)? + addr.port().to_leb128u(writer)?)
+}
+
+pub fn decode_socket_addr(reader: &mut impl Read) -> Result<SocketAddr, FromLeb128Error> {
+    let ip = decode_ip(reader)?;
+    let port = u16::from_leb128u(reader)?;
+    Ok(SocketAddr::new(ip, port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let v4: SocketAddr = "192.168.1.2:8080".parse().unwrap();
+        let v6: SocketAddr = "[2001:db8::1]:443".parse().unwrap();
+
+        let mut buf = Vec::new();
+        assert_eq!(encode_socket_addr(v4, &mut buf).unwrap(), 7);
+        assert_eq!(buf, vec![4, 192, 168, 1, 2, 0x90, 0x3f]);
+        assert_eq!(encode_socket_addr(v6, &mut buf).unwrap(), 19);
+
+        let mut reader = &buf[..];
+        assert_eq!(decode_socket_addr(&mut reader).unwrap(), v4);
+        assert_eq!(decode_socket_addr(&mut reader).unwrap(), v6);
+        assert!(reader.is_empty());
+
+        assert!(matches!(
+            decode_ip(&mut &[5, 0, 0, 0, 0][..]),
+            Err(FromLeb128Error::Malformed)
+        ));
+        assert!(decode_socket_addr(&mut &[4, 1, 2, 3, 4, 0x80, 0x80, 0x04][..]).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use std::io::{self, BorrowedBuf, Read};
+use std::mem::MaybeUninit;
+
+use crate::{FromLeb128Error, FromLeb128u};
+
+/// Decodes every value in `reader` and appends them to `out`, returning how
+/// many were decoded.
+///
+/// Input is read in blocks through `Read::read_buf`, so the internal buffer is
+/// never zero-initialized. A value split across blocks is carried over. On
+/// error `out` is left as it was.
+pub fn decode_buf<T: FromLeb128u>(
+    reader: &mut impl Read,
+    out: &mut Vec<T>,
+) -> Result<usize, FromLeb128Error> {
+    let start = out.len();
+
+    if let Err(e) = decode_into(reader, out) {
+        out.truncate(start);
+        return Err(e);
+    }
+
+    Ok(out.len() - start)
+}
+
+fn decode_into<T: FromLeb128u>(
+    reader: &mut impl Read,
+    out: &mut Vec<T>,
+) -> Result<(), FromLeb128Error> {
+    let mut storage = vec![MaybeUninit::<u8>::uninit(); 4096];
+    let mut carry = Vec::new();
+
+    loop {
+        // A value longer than the block would leave no room to read into.
+        if carry.len() == storage.len() {
+            storage.resize(storage.len() * 2, MaybeUninit::uninit());
+        }
+
+        let mut buf = BorrowedBuf::from(&mut storage[..]);
+        buf.unfilled().append(&carry);
+
+        match reader.read_buf(buf.unfilled()) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {
+                carry.clear();
+                carry.extend_from_slice(buf.filled());
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        let eof = buf.len() == carry.len();
+        let filled = buf.filled();
+        let mut pos = 0;
+
+        while pos < filled.len() {
+            match T::from_leb128u_slice(&filled[pos..]) {
+                Ok((value, len)) => {
+                    out.push(value);
+                    pos += len;
+                }
+                Err(FromLeb128Error::UnexpectedEof) if !eof => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        if eof {
+            return Ok(());
+        }
+
+        carry.clear();
+        carry.extend_from_slice(&filled[pos..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToLeb128u;
+
+    #[test]
+    fn decodes_across_blocks() {
+        let values = (0..5000u64).map(|i| i * 1_000_003).collect::<Vec<_>>();
+        let mut buf = Vec::new();
+        for v in &values {
+            v.to_leb128u(&mut buf).unwrap();
+        }
+
+        let mut out = Vec::new();
+        assert_eq!(decode_buf::<u64>(&mut &buf[..], &mut out).unwrap(), 5000);
+        assert_eq!(out, values);
+
+        buf.push(0x80);
+        assert!(decode_buf::<u64>(&mut &buf[..], &mut out).is_err());
+        assert_eq!(out, values);
+    }
+
+    #[test]
+    fn decodes_tuples_across_blocks() {
+        let values = (0..3000u32).map(|i| (i * 40_009, i)).collect::<Vec<_>>();
+        let mut buf = Vec::new();
+        for v in &values {
+            v.to_leb128u(&mut buf).unwrap();
+        }
+
+        let mut out = Vec::new();
+        assert_eq!(
+            decode_buf::<(u32, u32)>(&mut &buf[..], &mut out).unwrap(),
+            3000
+        );
+        assert_eq!(out, values);
+    }
+}
@@ -0,0 +1,133 @@
+//! Parsers for the `nom` combinator library.
+//!
+//! The parsers at the top level treat their input as complete, so running out
+//! of bytes mid-value is an `ErrorKind::Eof` error. The ones in [`streaming`]
+//! return `Err::Incomplete` instead. Other decode failures are reported
+//! through `FromExternalError` with the original [`FromLeb128Error`].
+
+use nom::error::{ErrorKind, FromExternalError, ParseError};
+use nom::{Err, IResult, Needed};
+
+use crate::push::DecodeFn;
+use crate::{FromLeb128Error, FromLeb128i, FromLeb128u};
+
+fn parse<'a, T, E>(input: &'a [u8], decode: DecodeFn<T>, partial: bool) -> IResult<&'a [u8], T, E>
+where
+    E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], FromLeb128Error>,
+{
+    match decode(input) {
+        Ok((value, len)) => Ok((&input[len..], value)),
+        Err(FromLeb128Error::UnexpectedEof) if partial => Err(Err::Incomplete(Needed::Unknown)),
+        Err(FromLeb128Error::UnexpectedEof) => {
+            Err(Err::Error(E::from_error_kind(input, ErrorKind::Eof)))
+        }
+        Err(e) => {
+            let kind = match e {
+                FromLeb128Error::Overflow | FromLeb128Error::TooLong => ErrorKind::TooLarge,
+                _ => ErrorKind::Verify,
+            };
+            Err(Err::Error(E::from_external_error(input, kind, e)))
+        }
+    }
+}
+
+/// Parses a ULEB128 value of any [`FromLeb128u`] type.
+pub fn uleb128<'a, T, E>(input: &'a [u8]) -> IResult<&'a [u8], T, E>
+where
+    T: FromLeb128u,
+    E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], FromLeb128Error>,
+{
+    parse(input, T::from_leb128u_slice, false)
+}
+
+/// Parses an SLEB128 value of any [`FromLeb128i`] type.
+pub fn sleb128<'a, T, E>(input: &'a [u8]) -> IResult<&'a [u8], T, E>
+where
+    T: FromLeb128i,
+    E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], FromLeb128Error>,
+{
+    parse(input, T::from_leb128i_slice, false)
+}
+
+macro_rules! parsers {
+    ($generic:ident: $($name:ident => $t:ty),*) => {
+        $(
+            #[doc = concat!("Parses a `", stringify!($t), "`.")]
+            pub fn $name<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], $t, E>
+            where
+                E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], FromLeb128Error>,
+            {
+                $generic(input)
+            }
+        )*
+    };
+}
+
+parsers!(uleb128: leb128_u8 => u8, leb128_u16 => u16, leb128_u32 => u32, leb128_u64 => u64, leb128_u128 => u128, leb128_usize => usize);
+parsers!(sleb128: leb128_i8 => i8, leb128_i16 => i16, leb128_i32 => i32, leb128_i64 => i64, leb128_i128 => i128, leb128_isize => isize);
+
+/// Parsers for partial input, returning `Err::Incomplete` when a value is
+/// cut off.
+pub mod streaming {
+    use nom::error::{FromExternalError, ParseError};
+    use nom::IResult;
+
+    use super::parse;
+    use crate::{FromLeb128Error, FromLeb128i, FromLeb128u};
+
+    /// Parses a ULEB128 value of any [`FromLeb128u`] type.
+    pub fn uleb128<'a, T, E>(input: &'a [u8]) -> IResult<&'a [u8], T, E>
+    where
+        T: FromLeb128u,
+        E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], FromLeb128Error>,
+    {
+        parse(input, T::from_leb128u_slice, true)
+    }
+
+    /// Parses an SLEB128 value of any [`FromLeb128i`] type.
+    pub fn sleb128<'a, T, E>(input: &'a [u8]) -> IResult<&'a [u8], T, E>
+    where
+        T: FromLeb128i,
+        E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], FromLeb128Error>,
+    {
+        parse(input, T::from_leb128i_slice, true)
+    }
+
+    parsers!(uleb128: leb128_u8 => u8, leb128_u16 => u16, leb128_u32 => u32, leb128_u64 => u64, leb128_u128 => u128, leb128_usize => usize);
+    parsers!(sleb128: leb128_i8 => i8, leb128_i16 => i16, leb128_i32 => i32, leb128_i64 => i64, leb128_i128 => i128, leb128_isize => isize);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::error::Error;
+    use nom::Parser;
+
+    #[test]
+    fn parses_values() {
+        let input = [0xac, 0x02, 0xbf, 0x7f, 0x01, 0x02, 0xff];
+        let (rest, (a, b)) = (leb128_u32::<Error<_>>, leb128_i64)
+            .parse(&input[..])
+            .unwrap();
+        assert_eq!((a, b), (300, -65));
+
+        let (rest, pair) = (leb128_u8::<Error<_>>, leb128_u8).parse(rest).unwrap();
+        assert_eq!(pair, (1, 2));
+
+        assert_eq!(
+            leb128_u8::<Error<_>>(rest),
+            Err(Err::Error(Error::new(rest, ErrorKind::Eof)))
+        );
+        assert_eq!(
+            streaming::leb128_u8::<Error<_>>(rest),
+            Err(Err::Incomplete(Needed::Unknown))
+        );
+        assert_eq!(
+            leb128_u8::<Error<_>>(&[0x80, 0x02]),
+            Err(Err::Error(Error::new(
+                &[0x80, 0x02][..],
+                ErrorKind::TooLarge
+            )))
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Generic encoding and decoding for any `num_traits::PrimInt`.
+//!
+//! Unsigned types (`PrimInt + Unsigned`) use ULEB128 and signed types
+//! (`PrimInt + Signed`) SLEB128, with the same overflow and length checks as
+//! the built-in implementations. Encodings longer than a `u128`'s do not fit
+//! the scratch buffer of the writer-based functions.
+
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+use core::mem::MaybeUninit;
+
+use num_traits::{PrimInt, Signed, Unsigned};
+
+#[cfg(feature = "std")]
+use crate::ToLeb128u;
+use crate::{EncodeError, FromLeb128Error};
+
+fn bits<T: PrimInt>() -> usize {
+    T::zero().count_zeros() as usize
+}
+
+fn low7<T: PrimInt>(value: T) -> u8 {
+    (value & T::from(0b01111111).unwrap()).to_u8().unwrap()
+}
+
+fn push(buf: &mut [MaybeUninit<u8>], len: &mut usize, byte: u8) -> Result<(), EncodeError> {
+    buf.get_mut(*len)
+        .ok_or(EncodeError::BufferTooSmall)?
+        .write(byte);
+    *len += 1;
+    Ok(())
+}
+
+/// Encodes into a possibly uninitialized buffer and returns the written prefix.
+pub fn encode_unsigned_uninit<T: PrimInt + Unsigned>(
+    mut value: T,
+    buf: &mut [MaybeUninit<u8>],
+) -> Result<&[u8], EncodeError> {
+    let mut len = 0;
+
+    loop {
+        let byte = low7(value);
+        value = value >> 7;
+
+        if value.is_zero() {
+            push(buf, &mut len, byte)?;
+            break;
+        }
+        push(buf, &mut len, byte | 0b10000000)?;
+    }
+
+    // SAFETY: `push` initialized the first `len` bytes.
+    Ok(unsafe { crate::__private::assume_init(buf, len) })
+}
+
+/// Encodes into a possibly uninitialized buffer and returns the written prefix.
+pub fn encode_signed_uninit<T: PrimInt + Signed>(
+    mut value: T,
+    buf: &mut [MaybeUninit<u8>],
+) -> Result<&[u8], EncodeError> {
+    let mut len = 0;
+
+    loop {
+        let byte = low7(value);
+        // Arithmetic shift, so negative values converge on -1.
+        value = value >> 7;
+
+        let sign_clear = byte & 0b01000000 == 0;
+        if (value.is_zero() && sign_clear) || (value == !T::zero() && !sign_clear) {
+            push(buf, &mut len, byte)?;
+            break;
+        }
+        push(buf, &mut len, byte | 0b10000000)?;
+    }
+
+    // SAFETY: `push` initialized the first `len` bytes.
+    Ok(unsafe { crate::__private::assume_init(buf, len) })
+}
+
+fn decode_unsigned_with<T: PrimInt + Unsigned>(
+    mut next: impl FnMut() -> Result<u8, FromLeb128Error>,
+) -> Result<(T, usize), FromLeb128Error> {
+    let bit = bits::<T>();
+    let mut result = T::zero();
+    let mut shift = 0;
+
+    loop {
+        let byte = next()?;
+        let b = T::from(byte & 0b01111111).unwrap();
+
+        if shift >= bit {
+            return Err(FromLeb128Error::TooLong);
+        }
+        // Only `bit - shift` bits of the last byte fit in the type.
+        if shift + 7 > bit && !(b >> (bit - shift)).is_zero() {
+            return Err(FromLeb128Error::Overflow);
+        }
+
+        result = result | b << shift;
+        shift += 7;
+
+        if byte & 0b10000000 == 0 {
+            return Ok((result, shift / 7));
+        }
+    }
+}
+
+fn decode_signed_with<T: PrimInt + Signed>(
+    mut next: impl FnMut() -> Result<u8, FromLeb128Error>,
+) -> Result<(T, usize), FromLeb128Error> {
+    let bit = bits::<T>();
+    let mut result = T::zero();
+    let mut shift = 0;
+
+    loop {
+        let byte = next()?;
+        let b = T::from(byte & 0b01111111).unwrap();
+
+        // The bits of the last byte from the type's sign bit up must all be
+        // copies of it.
+        if shift >= bit {
+            return Err(FromLeb128Error::TooLong);
+        }
+        if shift + 7 > bit {
+            let sign = b >> (bit - shift - 1);
+            if !sign.is_zero() && sign != T::from(0b01111111 >> (bit - shift - 1)).unwrap() {
+                return Err(FromLeb128Error::Overflow);
+            }
+        }
+
+        result = result | b << shift;
+        shift += 7;
+
+        if byte & 0b10000000 == 0 {
+            if byte & 0b01000000 != 0 && shift < bit {
+                result = result | !T::zero() << shift;
+            }
+            return Ok((result, shift / 7));
+        }
+    }
+}
+
+fn slice_bytes(bytes: &[u8]) -> impl FnMut() -> Result<u8, FromLeb128Error> + '_ {
+    let mut bytes = bytes.iter();
+    move || bytes.next().copied().ok_or(FromLeb128Error::UnexpectedEof)
+}
+
+/// Decodes from the front of `bytes` and returns the value with the number of
+/// bytes consumed.
+pub fn decode_unsigned_slice<T: PrimInt + Unsigned>(
+    bytes: &[u8],
+) -> Result<(T, usize), FromLeb128Error> {
+    decode_unsigned_with(slice_bytes(bytes))
+}
+
+/// Decodes from the front of `bytes` and returns the value with the number of
+/// bytes consumed.
+pub fn decode_signed_slice<T: PrimInt + Signed>(
+    bytes: &[u8],
+) -> Result<(T, usize), FromLeb128Error> {
+    decode_signed_with(slice_bytes(bytes))
+}
+
+#[cfg(feature = "std")]
+pub fn encode_unsigned<T: PrimInt + Unsigned>(
+    value: T,
+    writer: &mut (impl Write + ?Sized),
+) -> io::Result<usize> {
+    let mut buf = [MaybeUninit::uninit(); u128::MAX_LEB128_LEN];
+    let bytes = encode_unsigned_uninit(value, &mut buf)?;
+    writer.write_all(bytes)?;
+    Ok(bytes.len())
+}
+
+#[cfg(feature = "std")]
+pub fn encode_signed<T: PrimInt + Signed>(
+    value: T,
+    writer: &mut (impl Write + ?Sized),
+) -> io::Result<usize> {
+    let mut buf = [MaybeUninit::uninit(); u128::MAX_LEB128_LEN];
+    let bytes = encode_signed_uninit(value, &mut buf)?;
+    writer.write_all(bytes)?;
+    Ok(bytes.len())
+}
+
+#[cfg(feature = "std")]
+fn reader_bytes(
+    reader: &mut (impl Read + ?Sized),
+) -> impl FnMut() -> Result<u8, FromLeb128Error> + '_ {
+    move || Ok(crate::stream::read_byte(reader)?)
+}
+
+#[cfg(feature = "std")]
+pub fn decode_unsigned<T: PrimInt + Unsigned>(
+    reader: &mut (impl Read + ?Sized),
+) -> Result<T, FromLeb128Error> {
+    decode_unsigned_with(reader_bytes(reader)).map(|(value, _)| value)
+}
+
+#[cfg(feature = "std")]
+pub fn decode_signed<T: PrimInt + Signed>(
+    reader: &mut (impl Read + ?Sized),
+) -> Result<T, FromLeb128Error> {
+    decode_signed_with(reader_bytes(reader)).map(|(value, _)| value)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{FromLeb128i, FromLeb128u, ToLeb128i};
+
+    #[test]
+    fn matches_builtin() {
+        for v in [0u64, 1, 127, 128, 300, u64::MAX] {
+            let mut buf = Vec::new();
+            encode_unsigned(v, &mut buf).unwrap();
+            assert_eq!(buf, v.to_leb128u_vec());
+            assert_eq!(decode_unsigned::<u64>(&mut &buf[..]).unwrap(), v);
+        }
+        for v in [0i32, -1, 63, 64, -64, -65, i32::MIN, i32::MAX] {
+            let mut buf = Vec::new();
+            encode_signed(v, &mut buf).unwrap();
+            assert_eq!(buf, v.to_leb128i_vec());
+            assert_eq!(decode_signed_slice::<i32>(&buf).unwrap(), (v, buf.len()));
+        }
+
+        for bytes in [&[0x80, 0x02][..], &[0x80; 3], &[0xff, 0x01], &[0x80]] {
+            assert_eq!(
+                format!("{:?}", decode_unsigned_slice::<u8>(bytes)),
+                format!("{:?}", u8::from_leb128u_slice(bytes))
+            );
+        }
+        for bytes in [&[0x80, 0x01][..], &[0xff, 0x7e], &[0xc0, 0x00], &[0xff]] {
+            assert_eq!(
+                format!("{:?}", decode_signed_slice::<i8>(bytes)),
+                format!("{:?}", i8::from_leb128i_slice(bytes))
+            );
+        }
+    }
+}
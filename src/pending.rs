@@ -0,0 +1,70 @@
+//! Byte-at-a-time decoding for input that cannot be passed to
+//! `from_leb128u`.
+
+#[cfg(feature = "std")]
+use std::io::Read;
+
+use crate::{FromLeb128Error, ToLeb128u};
+
+/// Longest encoding handled, enough for a tuple of four `u128`s.
+pub(crate) const SCRATCH_LEN: usize = 4 * u128::MAX_LEB128_LEN;
+
+/// Bytes of a value read so far, one byte at a time.
+///
+/// The value is decoded at each varint boundary, so nothing past it is
+/// consumed from the reader.
+#[derive(Debug, Clone)]
+pub(crate) struct Pending {
+    buf: [u8; SCRATCH_LEN],
+    len: usize,
+}
+
+impl Pending {
+    pub(crate) fn new() -> Self {
+        Self {
+            buf: [0; SCRATCH_LEN],
+            len: 0,
+        }
+    }
+
+    /// Where to read the next byte.
+    pub(crate) fn slot(&mut self) -> Result<&mut [u8], FromLeb128Error> {
+        self.buf
+            .get_mut(self.len..self.len + 1)
+            .ok_or(FromLeb128Error::TooLong)
+    }
+
+    /// Accepts the byte just read into `slot`, returning the decoded value
+    /// once it is complete.
+    pub(crate) fn advance<T>(
+        &mut self,
+        decode: impl Fn(&[u8]) -> Result<(T, usize), FromLeb128Error>,
+    ) -> Option<Result<T, FromLeb128Error>> {
+        self.len += 1;
+
+        if self.buf[self.len - 1] & 0b10000000 != 0 {
+            return None;
+        }
+
+        match decode(&self.buf[..self.len]) {
+            Err(FromLeb128Error::UnexpectedEof) => None,
+            result => Some(result.map(|(value, _)| value)),
+        }
+    }
+}
+
+/// Decodes one value from `reader` with a slice decoder, reading no further
+/// than its end.
+#[cfg(feature = "std")]
+pub(crate) fn read_with<T>(
+    reader: &mut (impl Read + ?Sized),
+    decode: impl Fn(&[u8]) -> Result<(T, usize), FromLeb128Error>,
+) -> Result<T, FromLeb128Error> {
+    let mut pending = Pending::new();
+    loop {
+        pending.slot()?[0] = crate::stream::read_byte(reader)?;
+        if let Some(result) = pending.advance(&decode) {
+            return result;
+        }
+    }
+}
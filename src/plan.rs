@@ -0,0 +1,117 @@
+use std::io::Read;
+
+use crate::string::{read_leb128_bytes, read_leb128_str};
+use crate::{FromLeb128Error, FromLeb128i, FromLeb128u};
+
+/// The encoding of one field in a [`ReadPlan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    /// Length-prefixed UTF-8.
+    Str,
+    /// Length-prefixed bytes.
+    Bytes,
+}
+
+/// A decoded field; integers are widened to 64 bits after range checking
+/// against their declared type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Value {
+    Unsigned(u64),
+    Signed(i64),
+    Str(String),
+    Bytes(Vec<u8>),
+}
+
+impl FieldType {
+    pub fn read(self, reader: &mut impl Read) -> Result<Value, FromLeb128Error> {
+        Ok(match self {
+            FieldType::U8 => Value::Unsigned(u8::from_leb128u(reader)?.into()),
+            FieldType::U16 => Value::Unsigned(u16::from_leb128u(reader)?.into()),
+            FieldType::U32 => Value::Unsigned(u32::from_leb128u(reader)?.into()),
+            FieldType::U64 => Value::Unsigned(u64::from_leb128u(reader)?),
+            FieldType::I8 => Value::Signed(i8::from_leb128i(reader)?.into()),
+            FieldType::I16 => Value::Signed(i16::from_leb128i(reader)?.into()),
+            FieldType::I32 => Value::Signed(i32::from_leb128i(reader)?.into()),
+            FieldType::I64 => Value::Signed(i64::from_leb128i(reader)?),
+            FieldType::Str => Value::Str(read_leb128_str(reader)?),
+            FieldType::Bytes => Value::Bytes(read_leb128_bytes(reader)?),
+        })
+    }
+}
+
+/// A record layout built at runtime, e.g. from a user-supplied schema.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ReadPlan {
+    fields: Vec<FieldType>,
+}
+
+impl ReadPlan {
+    pub fn new(fields: impl Into<Vec<FieldType>>) -> Self {
+        Self {
+            fields: fields.into(),
+        }
+    }
+
+    pub fn fields(&self) -> &[FieldType] {
+        &self.fields
+    }
+
+    /// Reads one record.
+    pub fn execute(&self, reader: &mut impl Read) -> Result<Vec<Value>, FromLeb128Error> {
+        self.fields.iter().map(|field| field.read(reader)).collect()
+    }
+
+    /// Reads one record from the front of `bytes`, returning it with the
+    /// number of bytes consumed.
+    pub fn execute_slice(&self, bytes: &[u8]) -> Result<(Vec<Value>, usize), FromLeb128Error> {
+        let mut reader = bytes;
+        let values = self.execute(&mut reader)?;
+        Ok((values, bytes.len() - reader.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::string::write_leb128_str;
+    use crate::{ToLeb128i, ToLeb128u};
+
+    #[test]
+    fn execute() {
+        let mut buf = Vec::new();
+        300u32.to_leb128u(&mut buf).unwrap();
+        (-2i64).to_leb128i(&mut buf).unwrap();
+        write_leb128_str(&mut buf, "hi").unwrap();
+        7u32.to_leb128u(&mut buf).unwrap();
+
+        let plan = ReadPlan::new([
+            FieldType::U32,
+            FieldType::I64,
+            FieldType::Str,
+            FieldType::U32,
+        ]);
+        let (values, len) = plan.execute_slice(&buf).unwrap();
+
+        assert_eq!(len, buf.len());
+        assert_eq!(
+            values,
+            vec![
+                Value::Unsigned(300),
+                Value::Signed(-2),
+                Value::Str("hi".into()),
+                Value::Unsigned(7),
+            ]
+        );
+
+        let narrow = ReadPlan::new([FieldType::U8]);
+        assert!(narrow.execute(&mut &buf[..]).is_err());
+    }
+}
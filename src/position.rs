@@ -0,0 +1,136 @@
+//! Decode errors that record where the failure happened.
+
+use std::io::{self, Read};
+
+use crate::stream::Counting;
+use crate::{FromLeb128Error, FromLeb128i, FromLeb128u};
+
+/// A [`FromLeb128Error`] with the position of the failure.
+#[derive(Debug)]
+pub struct DecodeError {
+    pub error: FromLeb128Error,
+    /// Bytes of the value read when the failure was detected, including the
+    /// byte that caused it.
+    pub consumed: usize,
+    /// Offset of the value's first byte in the stream, when decoding through
+    /// an [`OffsetReader`].
+    pub offset: Option<u64>,
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} after {} bytes of value", self.error, self.consumed)?;
+
+        if let Some(offset) = self.offset {
+            write!(f, " starting at offset {}", offset)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<DecodeError> for FromLeb128Error {
+    fn from(value: DecodeError) -> Self {
+        value.error
+    }
+}
+
+pub(crate) fn decode_positioned<R: Read + ?Sized, T>(
+    reader: &mut R,
+    decode: impl FnOnce(&mut Counting<&mut R>) -> Result<T, FromLeb128Error>,
+) -> Result<T, DecodeError> {
+    let mut reader = Counting::new(reader);
+
+    decode(&mut reader).map_err(|error| DecodeError {
+        error,
+        consumed: reader.count() as usize,
+        offset: None,
+    })
+}
+
+/// Reader adapter that tracks its absolute offset, so that decode errors can
+/// report where the failing value started.
+#[derive(Debug)]
+pub struct OffsetReader<R> {
+    inner: R,
+    offset: u64,
+}
+
+impl<R: Read> OffsetReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner, offset: 0 }
+    }
+
+    /// Starts counting from `offset`, for readers that do not start at the
+    /// beginning of the stream.
+    pub fn with_offset(inner: R, offset: u64) -> Self {
+        Self { inner, offset }
+    }
+
+    /// Number of bytes read so far, plus the starting offset.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    pub fn decode_u<T: FromLeb128u>(&mut self) -> Result<T, DecodeError> {
+        let start = self.offset;
+        T::from_leb128u_positioned(self).map_err(|e| DecodeError {
+            offset: Some(start),
+            ..e
+        })
+    }
+
+    pub fn decode_i<T: FromLeb128i>(&mut self) -> Result<T, DecodeError> {
+        let start = self.offset;
+        T::from_leb128i_positioned(self).map_err(|e| DecodeError {
+            offset: Some(start),
+            ..e
+        })
+    }
+}
+
+impl<R: Read> Read for OffsetReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.offset += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_position() {
+        let e = u8::from_leb128u_positioned(&mut &[0x80, 0x80, 0x01][..]).unwrap_err();
+        assert!(matches!(e.error, FromLeb128Error::TooLong));
+        assert_eq!(e.consumed, 3);
+        assert_eq!(e.offset, None);
+
+        let mut reader = OffsetReader::new(&[0x01, 0xac, 0x02, 0xff, 0x01, 0x80][..]);
+        assert_eq!(reader.decode_u::<u8>().unwrap(), 1);
+        assert_eq!(reader.decode_u::<u16>().unwrap(), 300);
+        let e = reader.decode_i::<i8>().unwrap_err();
+        assert!(matches!(e.error, FromLeb128Error::Overflow));
+        assert_eq!((e.consumed, e.offset), (2, Some(3)));
+
+        let e = reader.decode_u::<u32>().unwrap_err();
+        assert!(matches!(e.error, FromLeb128Error::UnexpectedEof));
+        assert_eq!((e.consumed, e.offset), (1, Some(5)));
+        assert_eq!(
+            e.to_string(),
+            "unexpected end of input after 1 bytes of value starting at offset 5"
+        );
+    }
+}
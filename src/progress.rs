@@ -0,0 +1,54 @@
+/// Amount of input processed so far by a long-running operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Progress {
+    pub bytes: u64,
+    pub values: u64,
+}
+
+/// Invokes a callback every `interval` input bytes, and once more when the
+/// operation finishes.
+#[derive(Debug)]
+pub struct ProgressReporter<F> {
+    callback: F,
+    interval: u64,
+    next: u64,
+    progress: Progress,
+}
+
+impl<F: FnMut(Progress)> ProgressReporter<F> {
+    pub fn new(interval: u64, callback: F) -> Self {
+        let interval = interval.max(1);
+
+        Self {
+            callback,
+            interval,
+            next: interval,
+            progress: Progress::default(),
+        }
+    }
+
+    pub fn progress(&self) -> Progress {
+        self.progress
+    }
+
+    /// Records one more value ending at absolute byte offset `bytes`.
+    pub(crate) fn value_at(&mut self, bytes: u64) {
+        self.progress.bytes = bytes;
+        self.progress.values += 1;
+
+        if bytes >= self.next {
+            (self.callback)(self.progress);
+            self.next = bytes.saturating_add(self.interval);
+        }
+    }
+
+    pub(crate) fn finish(&mut self, bytes: u64) {
+        self.progress.bytes = bytes;
+        (self.callback)(self.progress);
+    }
+}
+
+/// A reporter that never calls back.
+pub(crate) fn silent() -> ProgressReporter<fn(Progress)> {
+    ProgressReporter::new(u64::MAX, |_| {})
+}
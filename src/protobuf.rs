@@ -0,0 +1,156 @@
+//! Protobuf wire-format varints.
+//!
+//! Protobuf's `int32` and `int64` are not SLEB128: a negative value is
+//! written as its 64-bit two's complement in ULEB128, so it always takes ten
+//! bytes. `sint32` and `sint64` use zigzag instead; see [`crate::zigzag`].
+//!
+//! Every field starts with a key varint packing the field number and the
+//! [`WireType`]; see [`make_tag`] and [`parse_tag`].
+
+use std::io::{self, Read, Write};
+
+use crate::{stream, FromLeb128Error, FromLeb128u, ToLeb128u};
+
+/// Largest field number protobuf allows.
+pub const MAX_FIELD_NUMBER: u32 = (1 << 29) - 1;
+
+/// How the value after a field key is encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WireType {
+    Varint = 0,
+    I64 = 1,
+    /// Length-delimited: strings, bytes, messages and packed fields.
+    Len = 2,
+    StartGroup = 3,
+    EndGroup = 4,
+    I32 = 5,
+}
+
+impl TryFrom<u32> for WireType {
+    type Error = FromLeb128Error;
+
+    fn try_from(value: u32) -> Result<Self, FromLeb128Error> {
+        match value {
+            0 => Ok(WireType::Varint),
+            1 => Ok(WireType::I64),
+            2 => Ok(WireType::Len),
+            3 => Ok(WireType::StartGroup),
+            4 => Ok(WireType::EndGroup),
+            5 => Ok(WireType::I32),
+            _ => Err(FromLeb128Error::Malformed),
+        }
+    }
+}
+
+/// Packs a field key.
+///
+/// # Panics
+///
+/// If `field_number` is above [`MAX_FIELD_NUMBER`].
+pub fn make_tag(field_number: u32, wire_type: WireType) -> u32 {
+    assert!(
+        field_number <= MAX_FIELD_NUMBER,
+        "field number out of range"
+    );
+    field_number << 3 | wire_type as u32
+}
+
+/// Splits a field key into its field number and wire type.
+///
+/// Fails with `FromLeb128Error::Malformed` on field number zero or an unknown
+/// wire type.
+pub fn parse_tag(tag: u32) -> Result<(u32, WireType), FromLeb128Error> {
+    let wire_type = WireType::try_from(tag & 0b111)?;
+    match tag >> 3 {
+        0 => Err(FromLeb128Error::Malformed),
+        field_number => Ok((field_number, wire_type)),
+    }
+}
+
+/// Writes a field key and returns the number of bytes written.
+pub fn write_tag(
+    field_number: u32,
+    wire_type: WireType,
+    writer: &mut (impl Write + ?Sized),
+) -> io::Result<usize> {
+    make_tag(field_number, wire_type).to_leb128u(writer)
+}
+
+/// Reads a field key, or returns `None` at a clean end of input, which is
+/// where a top-level message ends.
+pub fn read_tag(
+    mut reader: &mut (impl Read + ?Sized),
+) -> Result<Option<(u32, WireType)>, FromLeb128Error> {
+    stream::read_u::<u32>(&mut reader)?
+        .map(parse_tag)
+        .transpose()
+}
+
+/// Writes an `int64` field value and returns the number of bytes written.
+pub fn encode_int64(value: i64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    (value as u64).to_leb128u(writer)
+}
+
+/// Writes an `int32` field value, sign-extended to 64 bits as protobuf does.
+pub fn encode_int32(value: i32, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    encode_int64(value.into(), writer)
+}
+
+pub fn decode_int64(reader: &mut (impl Read + ?Sized)) -> Result<i64, FromLeb128Error> {
+    u64::from_leb128u(reader).map(|value| value as i64)
+}
+
+/// Reads an `int32` field value. Like protobuf parsers, this keeps the low
+/// 32 bits of a wider value.
+pub fn decode_int32(reader: &mut (impl Read + ?Sized)) -> Result<i32, FromLeb128Error> {
+    decode_int64(reader).map(|value| value as i32)
+}
+
+/// Number of bytes `encode_int64` would write.
+pub fn int64_len(value: i64) -> usize {
+    (value as u64).leb128u_len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negative_values_take_ten_bytes() {
+        let mut buf = Vec::new();
+        assert_eq!(encode_int32(150, &mut buf).unwrap(), 2);
+        assert_eq!(encode_int32(-1, &mut buf).unwrap(), 10);
+        assert_eq!(encode_int64(i64::MIN, &mut buf).unwrap(), 10);
+        assert_eq!(&buf[..3], [0x96, 0x01, 0xff]);
+        assert_eq!(
+            &buf[12..],
+            [0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01]
+        );
+        assert_eq!(int64_len(-1), 10);
+
+        let mut reader = &buf[..];
+        assert_eq!(decode_int32(&mut reader).unwrap(), 150);
+        assert_eq!(decode_int32(&mut reader).unwrap(), -1);
+        assert_eq!(decode_int64(&mut reader).unwrap(), i64::MIN);
+
+        let wide = (1u64 << 32 | 7).to_leb128u_vec();
+        assert_eq!(decode_int32(&mut &wide[..]).unwrap(), 7);
+    }
+
+    #[test]
+    fn field_keys() {
+        assert_eq!(make_tag(1, WireType::Varint), 0x08);
+        assert_eq!(make_tag(2, WireType::Len), 0x12);
+        assert_eq!(parse_tag(0x1a).unwrap(), (3, WireType::Len));
+        assert!(parse_tag(0x0e).is_err());
+        assert!(parse_tag(0x02).is_err());
+
+        let mut buf = Vec::new();
+        assert_eq!(write_tag(16, WireType::I32, &mut buf).unwrap(), 2);
+        assert_eq!(buf, [0x85, 0x01]);
+
+        let mut reader = &buf[..];
+        assert_eq!(read_tag(&mut reader).unwrap(), Some((16, WireType::I32)));
+        assert_eq!(read_tag(&mut reader).unwrap(), None);
+    }
+}
@@ -0,0 +1,200 @@
+//! Sans-io decoding of values fed in as they arrive, and encoding into
+//! outputs that accept only part of a value at a time.
+
+use core::mem::MaybeUninit;
+
+use crate::pending::{Pending, SCRATCH_LEN};
+use crate::{EncodeError, FromLeb128Error, FromLeb128i, FromLeb128u, ToLeb128i, ToLeb128u};
+
+/// Outcome of feeding bytes to a [`Leb128Decoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeStatus<T> {
+    /// All input was taken and the value is still incomplete.
+    NeedMore,
+    /// The value is complete; holds it and the number of input bytes used
+    /// from this call.
+    Done(T, usize),
+}
+
+pub(crate) type DecodeFn<T> = fn(&[u8]) -> Result<(T, usize), FromLeb128Error>;
+
+/// Incremental decoder that keeps a partially received value between calls.
+///
+/// After `Done` or an error the decoder starts over on the next value.
+#[derive(Debug, Clone)]
+pub struct Leb128Decoder<T> {
+    pending: Pending,
+    decode: DecodeFn<T>,
+}
+
+impl<T: FromLeb128u> Leb128Decoder<T> {
+    /// A decoder for ULEB128 values.
+    pub fn unsigned() -> Self {
+        Self {
+            pending: Pending::new(),
+            decode: T::from_leb128u_slice,
+        }
+    }
+}
+
+impl<T: FromLeb128i> Leb128Decoder<T> {
+    /// A decoder for SLEB128 values.
+    pub fn signed() -> Self {
+        Self {
+            pending: Pending::new(),
+            decode: T::from_leb128i_slice,
+        }
+    }
+}
+
+impl<T> Leb128Decoder<T> {
+    pub fn push_byte(&mut self, byte: u8) -> Result<DecodeStatus<T>, FromLeb128Error> {
+        self.push_slice(&[byte])
+    }
+
+    /// Feeds bytes until the value is complete; bytes after it are not used.
+    pub fn push_slice(&mut self, bytes: &[u8]) -> Result<DecodeStatus<T>, FromLeb128Error> {
+        for (i, &byte) in bytes.iter().enumerate() {
+            let result = match self.pending.slot() {
+                Ok(slot) => {
+                    slot[0] = byte;
+                    self.pending.advance(self.decode)
+                }
+                Err(e) => Some(Err(e)),
+            };
+
+            if let Some(result) = result {
+                self.reset();
+                return result.map(|value| DecodeStatus::Done(value, i + 1));
+            }
+        }
+
+        Ok(DecodeStatus::NeedMore)
+    }
+
+    /// Discards any partially received value.
+    pub fn reset(&mut self) {
+        self.pending = Pending::new();
+    }
+}
+
+/// Encoder that hands out a value's bytes as the output accepts them.
+///
+/// Drive it from a non-blocking sink by writing [`remaining`](Self::remaining)
+/// and passing the accepted count to [`advance`](Self::advance), or with
+/// [`write_to`](Self::write_to) for plain buffers.
+#[derive(Debug, Clone)]
+pub struct Leb128Encoder {
+    buf: [u8; SCRATCH_LEN],
+    len: usize,
+    pos: usize,
+}
+
+impl Leb128Encoder {
+    /// An encoder for the ULEB128 encoding of `value`.
+    pub fn unsigned<T: ToLeb128u + ?Sized>(value: &T) -> Result<Self, EncodeError> {
+        let mut buf = [MaybeUninit::uninit(); SCRATCH_LEN];
+        let bytes = value.to_leb128u_uninit(&mut buf)?;
+        Ok(Self::from_bytes(bytes))
+    }
+
+    /// An encoder for the SLEB128 encoding of `value`.
+    pub fn signed<T: ToLeb128i + ?Sized>(value: &T) -> Result<Self, EncodeError> {
+        let mut buf = [MaybeUninit::uninit(); SCRATCH_LEN];
+        let bytes = value.to_leb128i_uninit(&mut buf)?;
+        Ok(Self::from_bytes(bytes))
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let mut buf = [0; SCRATCH_LEN];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        Self {
+            buf,
+            len: bytes.len(),
+            pos: 0,
+        }
+    }
+
+    /// The bytes not yet emitted.
+    pub fn remaining(&self) -> &[u8] {
+        &self.buf[self.pos..self.len]
+    }
+
+    /// Marks the first `n` remaining bytes as emitted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than `remaining().len()`.
+    pub fn advance(&mut self, n: usize) {
+        assert!(
+            n <= self.len - self.pos,
+            "advanced past the end of the value"
+        );
+        self.pos += n;
+    }
+
+    /// Copies as many remaining bytes as fit into `out` and returns how many.
+    pub fn write_to(&mut self, out: &mut [u8]) -> usize {
+        let n = out.len().min(self.len - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        n
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pos == self.len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn survives_split_input() {
+        let mut decoder = Leb128Decoder::<u32>::unsigned();
+        assert_eq!(decoder.push_slice(&[0xac]).unwrap(), DecodeStatus::NeedMore);
+        assert_eq!(
+            decoder.push_slice(&[0x02, 0x05]).unwrap(),
+            DecodeStatus::Done(300, 1)
+        );
+        assert_eq!(decoder.push_byte(0x05).unwrap(), DecodeStatus::Done(5, 1));
+
+        let mut signed = Leb128Decoder::<(i8, i64)>::signed();
+        assert_eq!(
+            signed.push_slice(&[0x7f, 0xbf]).unwrap(),
+            DecodeStatus::NeedMore
+        );
+        assert_eq!(
+            signed.push_slice(&[0x7f]).unwrap(),
+            DecodeStatus::Done((-1, -65), 1)
+        );
+    }
+
+    #[test]
+    fn errors_reset() {
+        let mut decoder = Leb128Decoder::<u8>::unsigned();
+        assert!(matches!(
+            decoder.push_slice(&[0x80, 0x02]),
+            Err(FromLeb128Error::Overflow)
+        ));
+        assert_eq!(decoder.push_byte(0x07).unwrap(), DecodeStatus::Done(7, 1));
+    }
+
+    #[test]
+    fn encoder_resumes() {
+        let mut encoder = Leb128Encoder::unsigned(&u64::MAX).unwrap();
+        let mut out = [0; 10];
+        assert_eq!(encoder.write_to(&mut out[..3]), 3);
+        assert_eq!(encoder.remaining().len(), 7);
+        encoder.advance(2);
+        assert_eq!(encoder.write_to(&mut out[5..]), 5);
+        assert!(encoder.is_done());
+        assert_eq!(encoder.write_to(&mut out), 0);
+
+        let mut encoder = Leb128Encoder::signed(&-65i32).unwrap();
+        assert_eq!(encoder.remaining(), &[0xbf, 0x7f]);
+        encoder.advance(2);
+        assert!(encoder.is_done());
+    }
+}
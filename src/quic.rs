@@ -0,0 +1,129 @@
+//! QUIC variable-length integers (RFC 9000, section 16), also used by HTTP/3.
+//!
+//! Unlike LEB128 these are big-endian, with the length in the top two bits of
+//! the first byte: 1, 2, 4 or 8 bytes holding 6, 14, 30 or 62 bits. QUIC
+//! allows any length that fits the value, so decoding accepts non-minimal
+//! encodings, and [`encode_with_len`] can pick a longer one on purpose.
+
+use std::io::{self, Read, Write};
+
+use crate::FromLeb128Error;
+
+/// Largest value that can be encoded.
+pub const MAX_VALUE: u64 = (1 << 62) - 1;
+
+/// Shortest length for `value`, or `None` above [`MAX_VALUE`].
+pub fn encoded_len(value: u64) -> Option<usize> {
+    match value {
+        0..=0x3f => Some(1),
+        0x40..=0x3fff => Some(2),
+        0x4000..=0x3fff_ffff => Some(4),
+        0x4000_0000..=MAX_VALUE => Some(8),
+        _ => None,
+    }
+}
+
+/// Writes `value` in its shortest form and returns the number of bytes
+/// written.
+///
+/// Fails with `io::ErrorKind::InvalidInput` above [`MAX_VALUE`].
+pub fn encode(value: u64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    let len = encoded_len(value)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "value above 2^62 - 1"))?;
+    encode_with_len(value, len, writer)
+}
+
+/// Writes `value` using exactly `len` bytes, for frames whose size must not
+/// depend on the value.
+///
+/// Fails with `io::ErrorKind::InvalidInput` if `len` is not 1, 2, 4 or 8, or
+/// is too short for `value`.
+pub fn encode_with_len(
+    value: u64,
+    len: usize,
+    writer: &mut (impl Write + ?Sized),
+) -> io::Result<usize> {
+    let prefix = match len {
+        1 => 0b00,
+        2 => 0b01,
+        4 => 0b10,
+        8 => 0b11,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "length must be 1, 2, 4 or 8",
+            ))
+        }
+    };
+    if encoded_len(value).is_none_or(|min| min > len) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "value does not fit the length",
+        ));
+    }
+
+    let mut bytes = value.to_be_bytes();
+    bytes[8 - len] |= prefix << 6;
+    writer.write_all(&bytes[8 - len..])?;
+    Ok(len)
+}
+
+fn assemble(first: u8, rest: &[u8]) -> u64 {
+    rest.iter().fold(u64::from(first & 0b00111111), |acc, &b| {
+        acc << 8 | u64::from(b)
+    })
+}
+
+pub fn decode(reader: &mut (impl Read + ?Sized)) -> Result<u64, FromLeb128Error> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf[..1])?;
+    let len = 1 << (buf[0] >> 6);
+    reader.read_exact(&mut buf[1..len])?;
+    Ok(assemble(buf[0], &buf[1..len]))
+}
+
+/// Decodes from the front of `bytes` and returns the value with the number of
+/// bytes consumed.
+pub fn decode_slice(bytes: &[u8]) -> Result<(u64, usize), FromLeb128Error> {
+    let first = *bytes.first().ok_or(FromLeb128Error::UnexpectedEof)?;
+    let len = 1 << (first >> 6);
+    let rest = bytes.get(1..len).ok_or(FromLeb128Error::UnexpectedEof)?;
+    Ok((assemble(first, rest), len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc_examples() {
+        // From appendix A.1 of RFC 9000.
+        for (value, bytes) in [
+            (
+                151_288_809_941_952_652,
+                &[0xc2, 0x19, 0x7c, 0x5e, 0xff, 0x14, 0xe8, 0x8c][..],
+            ),
+            (494_878_333, &[0x9d, 0x7f, 0x3e, 0x7d]),
+            (15_293, &[0x7b, 0xbd]),
+            (37, &[0x25]),
+        ] {
+            let mut buf = Vec::new();
+            assert_eq!(encode(value, &mut buf).unwrap(), bytes.len());
+            assert_eq!(buf, bytes);
+            assert_eq!(decode(&mut &buf[..]).unwrap(), value);
+            assert_eq!(decode_slice(&buf).unwrap(), (value, bytes.len()));
+        }
+        assert_eq!(decode_slice(&[0x40, 0x25]).unwrap(), (37, 2));
+
+        let mut buf = Vec::new();
+        assert_eq!(encode_with_len(37, 4, &mut buf).unwrap(), 4);
+        assert_eq!(buf, [0x80, 0x00, 0x00, 0x25]);
+        assert!(encode_with_len(15_293, 1, &mut buf).is_err());
+        assert!(encode_with_len(1, 3, &mut buf).is_err());
+        assert!(encode(MAX_VALUE + 1, &mut buf).is_err());
+        assert!(matches!(
+            decode_slice(&[0x9d, 0x7f]),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+    }
+}
@@ -0,0 +1,124 @@
+use std::io::{self, Read, Write};
+use std::ops::{Range, RangeInclusive};
+
+use crate::{FromLeb128Error, FromLeb128u, ToLeb128u};
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+/// Encodes a range as its start followed by its length.
+pub fn encode_range(range: &Range<u64>, writer: &mut impl Write) -> io::Result<usize> {
+    let len = range
+        .end
+        .checked_sub(range.start)
+        .ok_or_else(|| invalid("range end is before its start"))?;
+
+    Ok(range.start.to_leb128u(writer)? + len.to_leb128u(writer)?)
+}
+
+pub fn decode_range(reader: &mut impl Read) -> Result<Range<u64>, FromLeb128Error> {
+    let start = u64::from_leb128u(reader)?;
+    let len = u64::from_leb128u(reader)?;
+    let end = start.checked_add(len).ok_or(FromLeb128Error::Overflow)?;
+
+    Ok(start..end)
+}
+
+/// Encodes an inclusive range as its start followed by `end - start`.
+pub fn encode_range_inclusive(
+    range: &RangeInclusive<u64>,
+    writer: &mut impl Write,
+) -> io::Result<usize> {
+    let span = range
+        .end()
+        .checked_sub(*range.start())
+        .ok_or_else(|| invalid("range end is before its start"))?;
+
+    Ok(range.start().to_leb128u(writer)? + span.to_leb128u(writer)?)
+}
+
+pub fn decode_range_inclusive(
+    reader: &mut impl Read,
+) -> Result<RangeInclusive<u64>, FromLeb128Error> {
+    let start = u64::from_leb128u(reader)?;
+    let span = u64::from_leb128u(reader)?;
+    let end = start.checked_add(span).ok_or(FromLeb128Error::Overflow)?;
+
+    Ok(start..=end)
+}
+
+/// Encodes sorted, non-overlapping intervals.
+///
+/// The layout is the interval count, then for each interval the gap from the
+/// end of the previous one (from zero for the first) and its length.
+pub fn encode_intervals(intervals: &[Range<u64>], writer: &mut impl Write) -> io::Result<usize> {
+    let mut count = (intervals.len() as u64).to_leb128u(writer)?;
+    let mut prev_end = 0;
+
+    for range in intervals {
+        let gap = range
+            .start
+            .checked_sub(prev_end)
+            .ok_or_else(|| invalid("intervals are not sorted and disjoint"))?;
+        let len = range
+            .end
+            .checked_sub(range.start)
+            .ok_or_else(|| invalid("range end is before its start"))?;
+
+        count += gap.to_leb128u(writer)? + len.to_leb128u(writer)?;
+        prev_end = range.end;
+    }
+
+    Ok(count)
+}
+
+pub fn decode_intervals(reader: &mut impl Read) -> Result<Vec<Range<u64>>, FromLeb128Error> {
+    let count = u64::from_leb128u(reader)?;
+    let mut intervals = Vec::with_capacity(count.min(4096) as usize);
+    let mut prev_end = 0u64;
+
+    for _ in 0..count {
+        let gap = u64::from_leb128u(reader)?;
+        let len = u64::from_leb128u(reader)?;
+        let start = prev_end.checked_add(gap).ok_or(FromLeb128Error::Overflow)?;
+        let end = start.checked_add(len).ok_or(FromLeb128Error::Overflow)?;
+
+        intervals.push(start..end);
+        prev_end = end;
+    }
+
+    Ok(intervals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges() {
+        let mut buf = Vec::new();
+        encode_range(&(300..310), &mut buf).unwrap();
+        assert_eq!(buf, vec![0xac, 0x02, 0x0a]);
+        assert_eq!(decode_range(&mut &buf[..]).unwrap(), 300..310);
+
+        buf.clear();
+        encode_range_inclusive(&(0..=u64::MAX), &mut buf).unwrap();
+        assert_eq!(decode_range_inclusive(&mut &buf[..]).unwrap(), 0..=u64::MAX);
+
+        assert!(decode_range(
+            &mut &[0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01][..]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn intervals() {
+        let intervals = vec![4096..8192, 8192..8200, 1 << 40..(1 << 40) + 1];
+        let mut buf = Vec::new();
+        encode_intervals(&intervals, &mut buf).unwrap();
+        assert_eq!(decode_intervals(&mut &buf[..]).unwrap(), intervals);
+
+        assert!(encode_intervals(&[10..20, 15..30], &mut Vec::new()).is_err());
+    }
+}
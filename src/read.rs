@@ -0,0 +1,32 @@
+//! Free functions for one-off reads of `u64` and `i64` values, without
+//! importing the traits.
+
+use std::io::Read;
+
+use crate::{FromLeb128Error, FromLeb128i, FromLeb128u};
+
+/// Reads a ULEB128 value.
+pub fn unsigned(reader: &mut (impl Read + ?Sized)) -> Result<u64, FromLeb128Error> {
+    u64::from_leb128u(reader)
+}
+
+/// Reads an SLEB128 value.
+pub fn signed(reader: &mut (impl Read + ?Sized)) -> Result<i64, FromLeb128Error> {
+    i64::from_leb128i(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads() {
+        let mut reader = &[0xac, 0x02, 0xbf, 0x7f, 0x80][..];
+        assert_eq!(unsigned(&mut reader).unwrap(), 300);
+        assert_eq!(signed(&mut reader).unwrap(), -65);
+        assert!(matches!(
+            unsigned(&mut reader),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+    }
+}
@@ -0,0 +1,201 @@
+//! Fixed-capacity ring buffer of ULEB128 values for flight-recorder style
+//! logging.
+
+use std::io;
+use std::mem::MaybeUninit;
+
+use crate::deque::from_leb128u_split;
+use crate::ToLeb128u;
+
+/// Ring buffer holding encoded `u64` records in `N` bytes without allocating.
+///
+/// When a new record does not fit, the oldest complete records are dropped
+/// until it does.
+#[derive(Debug, Clone)]
+pub struct Leb128Ring<const N: usize> {
+    buf: [u8; N],
+    head: usize,
+    used: usize,
+    count: usize,
+}
+
+impl<const N: usize> Leb128Ring<N> {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            head: 0,
+            used: 0,
+            count: 0,
+        }
+    }
+
+    /// Appends `value`, returning the number of old records overwritten.
+    ///
+    /// Fails with `io::ErrorKind::InvalidInput` if the encoding is longer than
+    /// the whole buffer.
+    pub fn push(&mut self, value: u64) -> io::Result<usize> {
+        let mut storage = [MaybeUninit::uninit(); u64::MAX_LEB128_LEN];
+        let bytes = value
+            .to_leb128u_uninit(&mut storage)
+            .expect("buffer fits any u64");
+
+        if bytes.len() > N {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "record is larger than the ring buffer",
+            ));
+        }
+
+        let mut dropped = 0;
+        while N - self.used < bytes.len() {
+            self.pop_front();
+            dropped += 1;
+        }
+
+        let mut pos = (self.head + self.used) % N;
+        for &byte in bytes {
+            self.buf[pos] = byte;
+            pos = (pos + 1) % N;
+        }
+        self.used += bytes.len();
+        self.count += 1;
+
+        Ok(dropped)
+    }
+
+    /// Removes and returns the oldest record.
+    pub fn pop_front(&mut self) -> Option<u64> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let (front, back) = self.as_slices();
+        let (value, len) =
+            from_leb128u_split::<u64>(front, back).expect("ring holds complete records");
+        self.head = (self.head + len) % N;
+        self.used -= len;
+        self.count -= 1;
+
+        Some(value)
+    }
+
+    /// Number of records held.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Number of bytes occupied by the held records.
+    pub fn bytes_used(&self) -> usize {
+        self.used
+    }
+
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.used = 0;
+        self.count = 0;
+    }
+
+    /// Iterates the held records from oldest to newest.
+    pub fn iter(&self) -> Iter<'_> {
+        let (front, back) = self.as_slices();
+        Iter {
+            front,
+            back,
+            remaining: self.count,
+        }
+    }
+
+    fn as_slices(&self) -> (&[u8], &[u8]) {
+        let end = self.head + self.used;
+
+        if end <= N {
+            (&self.buf[self.head..end], &[])
+        } else {
+            (&self.buf[self.head..], &self.buf[..end - N])
+        }
+    }
+}
+
+impl<const N: usize> Default for Leb128Ring<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    front: &'a [u8],
+    back: &'a [u8],
+    remaining: usize,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let (value, len) =
+            from_leb128u_split::<u64>(self.front, self.back).expect("ring holds complete records");
+        if len <= self.front.len() {
+            self.front = &self.front[len..];
+        } else {
+            self.back = &self.back[len - self.front.len()..];
+            self.front = &[];
+        }
+        self.remaining -= 1;
+
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl<'a, const N: usize> IntoIterator for &'a Leb128Ring<N> {
+    type Item = u64;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overwrites_oldest() {
+        let mut ring = Leb128Ring::<8>::new();
+        for value in [1, 300, 2, 70000] {
+            assert_eq!(ring.push(value).unwrap(), 0);
+        }
+        assert_eq!(ring.bytes_used(), 7);
+
+        // 3 bytes needed: drops 1 and 300, and the record wraps around.
+        assert_eq!(ring.push(16384).unwrap(), 2);
+        assert_eq!(ring.iter().collect::<Vec<_>>(), vec![2, 70000, 16384]);
+        assert_eq!(ring.pop_front(), Some(2));
+        assert_eq!(ring.iter().len(), 2);
+
+        for value in 0..100 {
+            ring.push(value).unwrap();
+        }
+        assert_eq!(
+            ring.iter().collect::<Vec<_>>(),
+            (92..100).collect::<Vec<_>>()
+        );
+
+        assert!(Leb128Ring::<2>::new().push(u64::MAX).is_err());
+    }
+}
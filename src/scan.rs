@@ -0,0 +1,492 @@
+//! Boundary scanning without decoding values.
+//!
+//! The scanners look at sixteen bytes at a time and pick out the bytes whose
+//! continuation bit is clear, which are exactly the last bytes of values. On
+//! `x86_64` (SSE2) and on `wasm32` with `simd128` the bits are gathered with a
+//! vector movemask; elsewhere two 64-bit words are used.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead};
+
+#[cfg(feature = "std")]
+use crate::progress::{self, Progress, ProgressReporter};
+use crate::FromLeb128Error;
+
+/// Calls `f` with the end offset (exclusive) of every complete value.
+fn try_for_each_end<E>(bytes: &[u8], mut f: impl FnMut(usize) -> Result<(), E>) -> Result<(), E> {
+    let mut chunks = bytes.chunks_exact(16);
+    let mut base = 0;
+
+    for chunk in &mut chunks {
+        let mut ends = kernel::end_mask(chunk.try_into().unwrap());
+
+        while ends != 0 {
+            f(base + ends.trailing_zeros() as usize + 1)?;
+            ends &= ends - 1;
+        }
+
+        base += 16;
+    }
+
+    for (i, b) in chunks.remainder().iter().enumerate() {
+        if b & 0b10000000 == 0 {
+            f(base + i + 1)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Counts the complete values in `bytes`.
+pub fn count_values(bytes: &[u8]) -> usize {
+    let mut chunks = bytes.chunks_exact(16);
+    let mut count = 0;
+
+    for chunk in &mut chunks {
+        count += kernel::end_mask(chunk.try_into().unwrap()).count_ones() as usize;
+    }
+
+    count
+        + chunks
+            .remainder()
+            .iter()
+            .filter(|&&b| b & 0b10000000 == 0)
+            .count()
+}
+
+/// Returns the end offset (exclusive) of every complete value in `bytes`.
+#[cfg(feature = "alloc")]
+pub fn boundaries(bytes: &[u8]) -> Vec<usize> {
+    let mut ends = Vec::with_capacity(count_values(bytes));
+    let _ = try_for_each_end(bytes, |end| {
+        ends.push(end);
+        Ok::<_, ()>(())
+    });
+    ends
+}
+
+/// Returns the offset just past the first `n` values of `bytes`.
+///
+/// Values are skipped by their boundaries only and are not checked for
+/// overflow. Fails with `UnexpectedEof` if there are fewer than `n` values.
+pub fn skip_values(bytes: &[u8], n: usize) -> Result<usize, FromLeb128Error> {
+    if n == 0 {
+        return Ok(0);
+    }
+
+    let mut seen = 0;
+    let found = try_for_each_end(bytes, |end| {
+        seen += 1;
+        if seen == n {
+            Err(end)
+        } else {
+            Ok(())
+        }
+    });
+
+    match found {
+        Err(end) => Ok(end),
+        Ok(()) => Err(FromLeb128Error::UnexpectedEof),
+    }
+}
+
+/// Returns the start offset of every `every`-th complete value of `bytes`:
+/// entry `i` is where value `i * every` begins.
+///
+/// Value `m` can then be reached with
+/// `index[m / every] + skip_values(&bytes[index[m / every]..], m % every)?`.
+///
+/// # Panics
+///
+/// Panics if `every` is 0.
+#[cfg(feature = "alloc")]
+pub fn build_index(bytes: &[u8], every: usize) -> Vec<usize> {
+    assert!(every > 0, "index interval must be nonzero");
+
+    let mut index = Vec::with_capacity(count_values(bytes).div_ceil(every));
+    let mut start = 0;
+    let mut seen = 0;
+    let _ = try_for_each_end(bytes, |end| {
+        if seen % every == 0 {
+            index.push(start);
+        }
+        seen += 1;
+        start = end;
+        Ok::<_, ()>(())
+    });
+    index
+}
+
+/// Splits `bytes` at value boundaries into slices of `n` values each, e.g. to
+/// hand them to separate threads.
+///
+/// The last slice holds whatever is left, including a truncated final value.
+pub fn split_values(bytes: &[u8], n: usize) -> SplitValues<'_> {
+    SplitValues {
+        rest: bytes,
+        n: n.max(1),
+    }
+}
+
+/// Iterator returned by [`split_values`].
+#[derive(Debug, Clone)]
+pub struct SplitValues<'a> {
+    rest: &'a [u8],
+    n: usize,
+}
+
+impl<'a> Iterator for SplitValues<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        let len = skip_values(self.rest, self.n).unwrap_or(self.rest.len());
+        let (chunk, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Some(chunk)
+    }
+}
+
+/// Consumes the first `n` values of `reader` and returns the number of bytes
+/// skipped.
+///
+/// Like [`skip_values`], values are not decoded.
+#[cfg(feature = "std")]
+pub fn skip_values_buf(
+    reader: &mut (impl BufRead + ?Sized),
+    n: u64,
+) -> Result<u64, FromLeb128Error> {
+    let mut remaining = n;
+    let mut skipped = 0;
+
+    while remaining > 0 {
+        let buf = reader.fill_buf()?;
+
+        if buf.is_empty() {
+            return Err(FromLeb128Error::UnexpectedEof);
+        }
+
+        let ends = count_values(buf) as u64;
+        let used = if ends >= remaining {
+            let used = skip_values(buf, remaining as usize)?;
+            remaining = 0;
+            used
+        } else {
+            remaining -= ends;
+            buf.len()
+        };
+
+        reader.consume(used);
+        skipped += used as u64;
+    }
+
+    Ok(skipped)
+}
+
+/// Returns how many bytes to discard from the front of `bytes` to resume
+/// decoding after a malformed value.
+///
+/// Skips through the next byte with its continuation bit clear, so decoding
+/// restarts at the following value boundary; if there is none, all of `bytes`
+/// is discarded.
+pub fn resync(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .position(|b| b & 0b10000000 == 0)
+        .map_or(bytes.len(), |i| i + 1)
+}
+
+/// Like [`resync`], consuming the discarded bytes from `reader` and returning
+/// their number.
+#[cfg(feature = "std")]
+pub fn resync_buf(reader: &mut impl BufRead) -> io::Result<u64> {
+    let mut discarded = 0;
+
+    loop {
+        let buf = reader.fill_buf()?;
+
+        if buf.is_empty() {
+            return Ok(discarded);
+        }
+
+        let used = resync(buf);
+        let done = buf[used - 1] & 0b10000000 == 0;
+        reader.consume(used);
+        discarded += used as u64;
+
+        if done {
+            return Ok(discarded);
+        }
+    }
+}
+
+/// Checks that `bytes` is a sequence of well-formed ULEB128 `u64` values and
+/// returns how many there are.
+///
+/// Fails with `Overflow` or `TooLong` for a value that does not fit in a `u64`
+/// and `UnexpectedEof` for a truncated final value, as the decoders do.
+#[cfg(feature = "std")]
+pub fn validate(bytes: &[u8]) -> Result<usize, FromLeb128Error> {
+    validate_with_progress(bytes, &mut progress::silent())
+}
+
+/// Like [`validate`], reporting progress in bytes and values.
+#[cfg(feature = "std")]
+pub fn validate_with_progress(
+    bytes: &[u8],
+    progress: &mut ProgressReporter<impl FnMut(Progress)>,
+) -> Result<usize, FromLeb128Error> {
+    let mut start = 0;
+    let mut count = 0;
+
+    try_for_each_end(bytes, |end| {
+        check_u64(end - start, bytes[end - 1])?;
+        start = end;
+        count += 1;
+        progress.value_at(end as u64);
+        Ok::<_, FromLeb128Error>(())
+    })?;
+
+    if start != bytes.len() {
+        if bytes.len() - start > 10 {
+            return Err(FromLeb128Error::TooLong);
+        }
+        return Err(FromLeb128Error::UnexpectedEof);
+    }
+
+    progress.finish(bytes.len() as u64);
+    Ok(count)
+}
+
+/// Like [`validate`], reading from `reader` until a clean end of input and
+/// returning the number of values.
+#[cfg(feature = "std")]
+pub fn validate_stream(reader: &mut (impl BufRead + ?Sized)) -> Result<u64, FromLeb128Error> {
+    validate_stream_with_progress(reader, &mut progress::silent())
+}
+
+/// Like [`validate_stream`], reporting progress in bytes and values.
+#[cfg(feature = "std")]
+pub fn validate_stream_with_progress(
+    reader: &mut (impl BufRead + ?Sized),
+    progress: &mut ProgressReporter<impl FnMut(Progress)>,
+) -> Result<u64, FromLeb128Error> {
+    let mut offset = 0u64;
+    let mut count = 0;
+    // Bytes of the current value seen in earlier buffers.
+    let mut pending = 0;
+
+    loop {
+        let buf = reader.fill_buf()?;
+
+        if buf.is_empty() {
+            break;
+        }
+
+        let mut start = 0;
+        try_for_each_end(buf, |end| {
+            check_u64(pending + end - start, buf[end - 1])?;
+            pending = 0;
+            start = end;
+            count += 1;
+            progress.value_at(offset + end as u64);
+            Ok::<_, FromLeb128Error>(())
+        })?;
+
+        pending += buf.len() - start;
+        if pending > 10 {
+            return Err(FromLeb128Error::TooLong);
+        }
+
+        let len = buf.len();
+        reader.consume(len);
+        offset += len as u64;
+    }
+
+    if pending != 0 {
+        return Err(FromLeb128Error::UnexpectedEof);
+    }
+
+    progress.finish(offset);
+    Ok(count)
+}
+
+/// Checks the length and final byte of one ULEB128 `u64`.
+#[cfg(feature = "std")]
+fn check_u64(len: usize, last: u8) -> Result<(), FromLeb128Error> {
+    if len > 10 {
+        return Err(FromLeb128Error::TooLong);
+    }
+    if len == 10 && last > 1 {
+        return Err(FromLeb128Error::Overflow);
+    }
+    Ok(())
+}
+
+#[cfg(target_arch = "x86_64")]
+mod kernel {
+    use core::arch::x86_64::*;
+
+    /// Bit `i` is set if `chunk[i]` has its continuation bit clear.
+    pub(super) fn end_mask(chunk: &[u8; 16]) -> u32 {
+        // SAFETY: SSE2 is part of the x86_64 baseline and unaligned loads are
+        // allowed.
+        let high = unsafe { _mm_movemask_epi8(_mm_loadu_si128(chunk.as_ptr().cast())) };
+        !high as u32 & 0xffff
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod kernel {
+    use core::arch::wasm32::*;
+
+    /// Bit `i` is set if `chunk[i]` has its continuation bit clear.
+    pub(super) fn end_mask(chunk: &[u8; 16]) -> u32 {
+        // SAFETY: `chunk` is 16 bytes long and wasm loads may be unaligned.
+        let v = unsafe { v128_load(chunk.as_ptr().cast()) };
+        !u32::from(u8x16_bitmask(v)) & 0xffff
+    }
+}
+
+#[cfg(not(any(
+    target_arch = "x86_64",
+    all(target_arch = "wasm32", target_feature = "simd128")
+)))]
+mod kernel {
+    const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+    /// Bit `i` is set if `chunk[i]` has its continuation bit clear.
+    pub(super) fn end_mask(chunk: &[u8; 16]) -> u32 {
+        let (low, high) = chunk.split_at(8);
+        word_mask(low.try_into().unwrap()) | word_mask(high.try_into().unwrap()) << 8
+    }
+
+    fn word_mask(bytes: &[u8; 8]) -> u32 {
+        // One bit per byte, then gathered into the top byte by the multiply.
+        let ends = (!u64::from_le_bytes(*bytes) & HIGH_BITS) >> 7;
+        (ends.wrapping_mul(0x0102_0408_1020_4080) >> 56) as u32
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{FromLeb128u, ToLeb128u};
+    use std::io;
+
+    #[test]
+    fn scan_matches_encoding() {
+        let mut buf = Vec::new();
+        let mut expected = Vec::new();
+        for v in (0..64).map(|i| (1u64 << i) - 1).chain([u64::MAX]) {
+            v.to_leb128u(&mut buf).unwrap();
+            expected.push(buf.len());
+        }
+
+        assert_eq!(count_values(&buf), 65);
+        assert_eq!(boundaries(&buf), expected);
+        assert_eq!(validate(&buf).unwrap(), 65);
+
+        let mut reader = io::BufReader::with_capacity(7, &buf[..]);
+        assert_eq!(validate_stream(&mut reader).unwrap(), 65);
+        assert!(matches!(
+            validate_stream(&mut io::BufReader::with_capacity(3, &[0x80; 11][..])),
+            Err(FromLeb128Error::TooLong)
+        ));
+        assert!(matches!(
+            validate_stream(&mut &[0x01, 0x80][..]),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn end_mask_matches_bytes() {
+        let mut chunk = [0u8; 16];
+        for seed in 0..256u32 {
+            for (i, b) in chunk.iter_mut().enumerate() {
+                *b = (seed as u8)
+                    .wrapping_mul(i as u8 * 2 + 1)
+                    .rotate_left(i as u32);
+            }
+            let expected = (0..16)
+                .filter(|&i| chunk[i] & 0b10000000 == 0)
+                .fold(0, |acc, i| acc | 1 << i);
+            assert_eq!(kernel::end_mask(&chunk), expected);
+        }
+    }
+
+    #[test]
+    fn index_and_split() {
+        let mut buf = Vec::new();
+        for v in 0..100u64 {
+            (v * v * v).to_leb128u(&mut buf).unwrap();
+        }
+        let ends = boundaries(&buf);
+
+        let index = build_index(&buf, 8);
+        assert_eq!(index.len(), 13);
+        assert_eq!(index[0], 0);
+        assert_eq!(index[5], ends[39]);
+        let start = index[61 / 8] + skip_values(&buf[index[61 / 8]..], 61 % 8).unwrap();
+        assert_eq!(u64::from_leb128u(&mut &buf[start..]).unwrap(), 61 * 61 * 61);
+
+        let parts = split_values(&buf, 30).collect::<Vec<_>>();
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[0].len(), ends[29]);
+        assert_eq!(parts.concat(), buf);
+        assert_eq!(
+            split_values(&[0x01, 0x80], 1).collect::<Vec<_>>(),
+            [&[0x01][..], &[0x80]]
+        );
+    }
+
+    #[test]
+    fn skip() {
+        let bytes = [0x01, 0xac, 0x02, 0x80, 0x80, 0x01, 0x05];
+
+        assert_eq!(skip_values(&bytes, 0).unwrap(), 0);
+        assert_eq!(skip_values(&bytes, 3).unwrap(), 6);
+        assert!(skip_values(&bytes, 5).is_err());
+
+        let mut reader = io::BufReader::with_capacity(2, &bytes[..]);
+        assert_eq!(skip_values_buf(&mut reader, 3).unwrap(), 6);
+        assert_eq!(reader.fill_buf().unwrap(), &[0x05]);
+    }
+
+    #[test]
+    fn validate_rejects() {
+        let overflow = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02];
+        assert!(matches!(
+            validate(&overflow),
+            Err(FromLeb128Error::Overflow)
+        ));
+        assert!(matches!(
+            validate(&[0x01, 0x80]),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+        assert!(matches!(
+            validate(&[0x80; 11]),
+            Err(FromLeb128Error::TooLong)
+        ));
+    }
+
+    #[test]
+    fn resync_after_malformed() {
+        let bytes = [0xff, 0xff, 0x03, 0x05, 0x80];
+        assert_eq!(resync(&bytes), 3);
+        assert_eq!(resync(&bytes[3..]), 1);
+        assert_eq!(resync(&bytes[4..]), 1);
+        assert_eq!(resync(&[]), 0);
+
+        let mut reader = io::BufReader::with_capacity(2, &bytes[..]);
+        assert_eq!(resync_buf(&mut reader).unwrap(), 3);
+        assert_eq!(u8::from_leb128u(&mut reader).unwrap(), 5);
+        assert_eq!(resync_buf(&mut reader).unwrap(), 1);
+        assert_eq!(resync_buf(&mut reader).unwrap(), 0);
+    }
+}
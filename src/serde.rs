@@ -0,0 +1,978 @@
+//! A compact serde data format built on LEB128.
+//!
+//! Unsigned integers are ULEB128 and signed integers SLEB128. Floats are
+//! little-endian, and `bool` and the `Option` tag are a single `0` or `1`
+//! byte. Strings, byte strings, sequences and maps are prefixed with their
+//! length as ULEB128, and enum variants with their index. Structs and tuples
+//! are their fields in order with no framing.
+//!
+//! Like other non-self-describing formats, it cannot back `deserialize_any`,
+//! so types such as untagged enums are not supported.
+//!
+//! A stream can optionally start with a [`Header`]: magic bytes followed by a
+//! format version as ULEB128, written by [`Serializer::with_header`] and
+//! checked by [`Deserializer::with_header`].
+//!
+//! Independently of the format, the [`uleb128`] and [`sleb128`] modules let
+//! single fields of a type serialized with any other format opt into LEB128.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Serialize};
+
+use crate::string::{read_leb128_bytes, write_leb128_bytes};
+use crate::{FromLeb128Error, FromLeb128i, FromLeb128u, ToLeb128i, ToLeb128u};
+
+#[derive(Debug)]
+pub enum Error {
+    /// Encoding, decoding or the underlying I/O failed.
+    Leb128(FromLeb128Error),
+    /// A sequence or map was serialized without a known length.
+    UnknownLength,
+    /// The type asked for `deserialize_any`, which the format cannot support.
+    NotSelfDescribing,
+    /// A message from a `Serialize` or `Deserialize` implementation.
+    Message(String),
+    /// The input does not start with the expected magic bytes.
+    Magic,
+    /// The header holds a version newer than the one supported; holds the
+    /// version found.
+    Version(u64),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Leb128(e) => write!(f, "{}", e),
+            Error::UnknownLength => write!(f, "sequence length must be known up front"),
+            Error::NotSelfDescribing => write!(f, "format is not self-describing"),
+            Error::Message(msg) => write!(f, "{}", msg),
+            Error::Magic => write!(f, "missing magic bytes"),
+            Error::Version(version) => write!(f, "unsupported format version {}", version),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Leb128(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Message(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Message(msg.to_string())
+    }
+}
+
+impl From<FromLeb128Error> for Error {
+    fn from(value: FromLeb128Error) -> Self {
+        Error::Leb128(value)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Error::Leb128(value.into())
+    }
+}
+
+/// Errors other than `Error::Leb128` become `io::ErrorKind::InvalidData`.
+impl From<Error> for FromLeb128Error {
+    fn from(value: Error) -> Self {
+        match value {
+            Error::Leb128(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidData, e).into(),
+        }
+    }
+}
+
+pub fn to_writer<T: Serialize + ?Sized>(value: &T, writer: impl Write) -> Result<(), Error> {
+    value.serialize(&mut Serializer::new(writer))
+}
+
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::new();
+    to_writer(value, &mut buf)?;
+    Ok(buf)
+}
+
+pub fn from_reader<T: DeserializeOwned>(reader: impl Read) -> Result<T, Error> {
+    T::deserialize(&mut Deserializer::new(reader))
+}
+
+/// Deserializes a value that must span all of `bytes`.
+///
+/// Fails with `FromLeb128Error::TrailingBytes` if any input is left over.
+pub fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Error> {
+    let mut reader = bytes;
+    let value = from_reader(&mut reader)?;
+
+    if !reader.is_empty() {
+        return Err(FromLeb128Error::trailing(bytes.len() - reader.len()).into());
+    }
+
+    Ok(value)
+}
+
+/// Magic bytes and format version identifying a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    pub magic: &'static [u8],
+    pub version: u64,
+}
+
+#[derive(Debug)]
+pub struct Serializer<W> {
+    writer: W,
+}
+
+impl<W: Write> Serializer<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Writes `header` and returns a serializer for what follows it.
+    pub fn with_header(mut writer: W, header: Header) -> Result<Self, Error> {
+        writer.write_all(header.magic)?;
+        header.version.to_leb128u(&mut writer)?;
+        Ok(Self::new(writer))
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_len(&mut self, len: Option<usize>) -> Result<(), Error> {
+        let len = len.ok_or(Error::UnknownLength)?;
+        (len as u64).to_leb128u(&mut self.writer)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::Serializer for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.writer.write_all(&[v as u8])?;
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        v.to_leb128i(&mut self.writer)?;
+        Ok(())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        v.to_leb128i(&mut self.writer)?;
+        Ok(())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        v.to_leb128i(&mut self.writer)?;
+        Ok(())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        v.to_leb128i(&mut self.writer)?;
+        Ok(())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), Error> {
+        v.to_leb128i(&mut self.writer)?;
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        v.to_leb128u(&mut self.writer)?;
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        v.to_leb128u(&mut self.writer)?;
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        v.to_leb128u(&mut self.writer)?;
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        v.to_leb128u(&mut self.writer)?;
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), Error> {
+        v.to_leb128u(&mut self.writer)?;
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.writer.write_all(&v.to_le_bytes())?;
+        Ok(())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.writer.write_all(&v.to_le_bytes())?;
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        v.to_leb128u(&mut self.writer)?;
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        write_leb128_bytes(&mut self.writer, v.as_bytes())?;
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        write_leb128_bytes(&mut self.writer, v)?;
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.serialize_bool(false)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        self.serialize_bool(true)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), Error> {
+        self.serialize_u32(variant_index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        variant_index.to_leb128u(&mut self.writer)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, Error> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Error> {
+        variant_index.to_leb128u(&mut self.writer)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, Error> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Error> {
+        variant_index.to_leb128u(&mut self.writer)?;
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<W: Write> ser::SerializeSeq for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeTuple for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeTupleStruct for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeTupleVariant for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeMap for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeStruct for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeStructVariant for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct Deserializer<R> {
+    reader: R,
+    version: Option<u64>,
+}
+
+impl<R: Read> Deserializer<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            version: None,
+        }
+    }
+
+    /// Reads and checks a header, returning a deserializer for what follows it.
+    ///
+    /// Fails with [`Error::Magic`] if the magic bytes differ, and with
+    /// [`Error::Version`] if the version is above `header.version`; older
+    /// versions are accepted and reported by [`version`](Self::version).
+    pub fn with_header(mut reader: R, header: Header) -> Result<Self, Error> {
+        for &expected in header.magic {
+            let mut buf = [0; 1];
+            reader.read_exact(&mut buf)?;
+            if buf[0] != expected {
+                return Err(Error::Magic);
+            }
+        }
+
+        let version = u64::from_leb128u(&mut reader)?;
+        if version > header.version {
+            return Err(Error::Version(version));
+        }
+
+        Ok(Self {
+            reader,
+            version: Some(version),
+        })
+    }
+
+    /// The version read by [`with_header`](Self::with_header), if any.
+    pub fn version(&self) -> Option<u64> {
+        self.version
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn read_bool(&mut self) -> Result<bool, Error> {
+        let mut buf = [0; 1];
+        self.reader.read_exact(&mut buf)?;
+
+        match buf[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(FromLeb128Error::Malformed.into()),
+        }
+    }
+
+    fn read_len(&mut self) -> Result<usize, Error> {
+        Ok(usize::from_leb128u(&mut self.reader)?)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut buf = [0; N];
+        self.reader.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+impl<'de, R: Read> de::Deserializer<'de> for &mut Deserializer<R> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::NotSelfDescribing)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bool(self.read_bool()?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i8(i8::from_leb128i(&mut self.reader)?)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i16(i16::from_leb128i(&mut self.reader)?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i32(i32::from_leb128i(&mut self.reader)?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i64(i64::from_leb128i(&mut self.reader)?)
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i128(i128::from_leb128i(&mut self.reader)?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u8(u8::from_leb128u(&mut self.reader)?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u16(u16::from_leb128u(&mut self.reader)?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u32(u32::from_leb128u(&mut self.reader)?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u64(u64::from_leb128u(&mut self.reader)?)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u128(u128::from_leb128u(&mut self.reader)?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f32(f32::from_le_bytes(self.read_array()?))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f64(f64::from_le_bytes(self.read_array()?))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_char(char::from_leb128u(&mut self.reader)?)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let bytes = read_leb128_bytes(&mut self.reader)?;
+        let s = String::from_utf8(bytes).map_err(|_| FromLeb128Error::Malformed)?;
+        visitor.visit_string(s)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_byte_buf(read_leb128_bytes(&mut self.reader)?)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.read_bool()? {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let len = self.read_len()?;
+        visitor.visit_seq(Counted { de: self, len })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(Counted { de: self, len })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_seq(Counted { de: self, len })
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let len = self.read_len()?;
+        visitor.visit_map(Counted { de: self, len })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_seq(Counted {
+            de: self,
+            len: fields.len(),
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_u32(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::NotSelfDescribing)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Access to a known number of sequence elements or map entries.
+struct Counted<'a, R> {
+    de: &'a mut Deserializer<R>,
+    len: usize,
+}
+
+impl<'de, R: Read> de::SeqAccess<'de> for Counted<'_, R> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+
+        self.len -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        // Capped like other untrusted counts, so a corrupt length cannot
+        // reserve a huge collection up front.
+        Some(self.len.min(4096))
+    }
+}
+
+impl<'de, R: Read> de::MapAccess<'de> for Counted<'_, R> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+
+        self.len -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len.min(4096))
+    }
+}
+
+impl<'de, R: Read> de::EnumAccess<'de> for &mut Deserializer<R> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let index = u32::from_leb128u(&mut self.reader)?;
+        let value = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(index))?;
+        Ok((value, self))
+    }
+}
+
+impl<'de, R: Read> de::VariantAccess<'de> for &mut Deserializer<R> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(Counted { de: self, len })
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_seq(Counted {
+            de: self,
+            len: fields.len(),
+        })
+    }
+}
+
+macro_rules! with_module {
+    ($name:ident, $to:ident, $from:ident, $encode:ident, $decode:ident, $desc:literal) => {
+        #[doc = concat!("Stores a field as its ", $desc, " encoding inside any serde format, e.g.")]
+        #[doc = concat!("`#[serde(with = \"leb128_rs::serde::", stringify!($name), "\")]`.")]
+        ///
+        /// The value is serialized as a byte string, and on the way back
+        /// either a byte string or a sequence of bytes is accepted.
+        pub mod $name {
+            use serde::{Deserializer, Serializer};
+
+            use super::VarintVisitor;
+            use crate::{$from, $to};
+
+            pub fn serialize<T: $to, S: Serializer>(
+                value: &T,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(&value.$encode())
+            }
+
+            pub fn deserialize<'de, T: $from, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<T, D::Error> {
+                deserializer.deserialize_bytes(VarintVisitor { decode: T::$decode })
+            }
+        }
+    };
+}
+
+with_module!(
+    uleb128,
+    ToLeb128u,
+    FromLeb128u,
+    to_leb128u_vec,
+    from_leb128u_exact,
+    "ULEB128"
+);
+with_module!(
+    sleb128,
+    ToLeb128i,
+    FromLeb128i,
+    to_leb128i_vec,
+    from_leb128i_exact,
+    "SLEB128"
+);
+
+struct VarintVisitor<T> {
+    decode: fn(&[u8]) -> Result<T, FromLeb128Error>,
+}
+
+impl<'de, T> Visitor<'de> for VarintVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LEB128 bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<T, E> {
+        (self.decode)(v).map_err(E::custom)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(32));
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+        (self.decode)(&bytes).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Point,
+        Circle(u32),
+        Rect { w: u16, h: u16 },
+        Line(i8, i8),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Message {
+        id: u64,
+        delta: i32,
+        name: String,
+        ratio: f32,
+        flag: bool,
+        tag: Option<char>,
+        shapes: Vec<Shape>,
+        counts: BTreeMap<String, u8>,
+        unit: (),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Wrapped {
+        #[serde(with = "uleb128")]
+        size: u64,
+        #[serde(with = "sleb128")]
+        offset: i32,
+        plain: u8,
+    }
+
+    #[test]
+    fn with_helpers() {
+        let wrapped = Wrapped {
+            size: 300,
+            offset: -65,
+            plain: 1,
+        };
+
+        let bytes = to_vec(&wrapped).unwrap();
+        assert_eq!(bytes, [0x02, 0xac, 0x02, 0x02, 0xbf, 0x7f, 0x01]);
+        assert_eq!(from_slice::<Wrapped>(&bytes).unwrap(), wrapped);
+
+        let overlong = [0x03, 0xac, 0x02, 0x00, 0x01, 0x00, 0x01];
+        assert!(matches!(
+            from_slice::<Wrapped>(&overlong),
+            Err(Error::Message(_))
+        ));
+    }
+
+    #[test]
+    fn round_trip() {
+        let message = Message {
+            id: 300,
+            delta: -65,
+            name: "hi".to_string(),
+            ratio: 1.5,
+            flag: true,
+            tag: Some('é'),
+            shapes: vec![
+                Shape::Point,
+                Shape::Circle(7),
+                Shape::Rect { w: 2, h: 200 },
+                Shape::Line(-1, 1),
+            ],
+            counts: BTreeMap::from([("a".to_string(), 200)]),
+            unit: (),
+        };
+
+        let bytes = to_vec(&message).unwrap();
+        assert_eq!(&bytes[..7], &[0xac, 0x02, 0xbf, 0x7f, 0x02, b'h', b'i']);
+        assert_eq!(from_slice::<Message>(&bytes).unwrap(), message);
+
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert!(matches!(
+            from_slice::<Message>(&padded),
+            Err(Error::Leb128(FromLeb128Error::TrailingBytes(_)))
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert!(matches!(
+            from_slice::<bool>(&[2]),
+            Err(Error::Leb128(FromLeb128Error::Malformed))
+        ));
+        assert!(matches!(from_slice::<Shape>(&[9]), Err(Error::Message(_))));
+        assert!(matches!(
+            from_slice::<u8>(&[0x80, 0x02]),
+            Err(Error::Leb128(FromLeb128Error::Overflow))
+        ));
+    }
+
+    #[test]
+    fn header() {
+        const V1: Header = Header {
+            magic: b"LEB",
+            version: 1,
+        };
+        const V2: Header = Header { version: 2, ..V1 };
+
+        let mut ser = Serializer::with_header(Vec::new(), V2).unwrap();
+        (7u8, "x").serialize(&mut ser).unwrap();
+        let bytes = ser.into_inner();
+        assert_eq!(bytes, b"LEB\x02\x07\x01x");
+
+        let mut de = Deserializer::with_header(&bytes[..], V2).unwrap();
+        assert_eq!(de.version(), Some(2));
+        assert_eq!(
+            <(u8, String)>::deserialize(&mut de).unwrap(),
+            (7, "x".into())
+        );
+
+        assert!(matches!(
+            Deserializer::with_header(&bytes[..], V1),
+            Err(Error::Version(2))
+        ));
+        assert!(matches!(
+            Deserializer::with_header(&b"LEX\x01"[..], V2),
+            Err(Error::Magic)
+        ));
+        assert_eq!(Deserializer::new(&bytes[..]).version(), None);
+    }
+}
@@ -0,0 +1,104 @@
+//! Jackson Smile `VInt`s.
+//!
+//! Values are split into big-endian groups: leading bytes carry 7 bits with
+//! the high bit clear, and the final byte carries 6 bits with the high bit set.
+//! Signed values are zigzag-mapped first.
+
+use std::io::{self, Read, Write};
+
+use crate::{zigzag, FromLeb128Error};
+
+pub fn encode_u64(value: u64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    let mut buf = [0u8; 10];
+    let mut start = buf.len() - 1;
+    buf[start] = 0b10000000 | (value & 0b00111111) as u8;
+
+    let mut rest = value >> 6;
+    while rest != 0 {
+        start -= 1;
+        buf[start] = (rest & 0b01111111) as u8;
+        rest >>= 7;
+    }
+
+    writer.write_all(&buf[start..])?;
+    Ok(buf.len() - start)
+}
+
+pub fn decode_u64(reader: &mut (impl Read + ?Sized)) -> Result<u64, FromLeb128Error> {
+    let mut result = 0u64;
+    let mut buf = [0; 1];
+
+    loop {
+        reader.read_exact(&mut buf)?;
+        let b = buf[0];
+
+        if b & 0b10000000 != 0 {
+            if b & 0b01000000 != 0 {
+                return Err(FromLeb128Error::Malformed);
+            }
+            if result >> 58 != 0 {
+                return Err(FromLeb128Error::Overflow);
+            }
+            return Ok(result << 6 | u64::from(b & 0b00111111));
+        }
+
+        if result >> 57 != 0 {
+            return Err(FromLeb128Error::Overflow);
+        }
+        result = result << 7 | u64::from(b);
+    }
+}
+
+pub fn encode_i64(value: i64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    encode_u64(zigzag::encode_i64(value), writer)
+}
+
+pub fn decode_i64(reader: &mut (impl Read + ?Sized)) -> Result<i64, FromLeb128Error> {
+    decode_u64(reader).map(zigzag::decode_i64)
+}
+
+pub fn encode_i32(value: i32, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    encode_u64(zigzag::encode_i32(value).into(), writer)
+}
+
+pub fn decode_i32(reader: &mut (impl Read + ?Sized)) -> Result<i32, FromLeb128Error> {
+    let value = u32::try_from(decode_u64(reader)?).map_err(|_| FromLeb128Error::Overflow)?;
+    Ok(zigzag::decode_i32(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoding() {
+        let mut buf = Vec::new();
+        encode_u64(0x3f, &mut buf).unwrap();
+        assert_eq!(buf, vec![0xbf]);
+
+        buf.clear();
+        encode_u64(0x40, &mut buf).unwrap();
+        assert_eq!(buf, vec![0x01, 0x80]);
+
+        buf.clear();
+        encode_i32(-1, &mut buf).unwrap();
+        assert_eq!(buf, vec![0x81]);
+    }
+
+    #[test]
+    fn round_trip() {
+        for v in [0, 1, -1, 63, -64, 1 << 20, i64::MIN, i64::MAX] {
+            let mut buf = Vec::new();
+            encode_i64(v, &mut buf).unwrap();
+            assert_eq!(decode_i64(&mut &buf[..]).unwrap(), v);
+        }
+
+        let mut buf = Vec::new();
+        encode_u64(u64::MAX, &mut buf).unwrap();
+        assert_eq!(buf.len(), 10);
+        assert_eq!(decode_u64(&mut &buf[..]).unwrap(), u64::MAX);
+
+        assert!(decode_u64(&mut &[0x7f; 10][..]).is_err());
+        assert!(decode_i32(&mut &buf[..]).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+use crate::FromLeb128Error;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Appends the Base64 VLQ encoding of `value`.
+pub fn encode_vlq(value: i64, out: &mut String) {
+    // The sign bit makes `i64::MIN` 65 bits wide.
+    let mut v = u128::from(value.unsigned_abs()) << 1 | u128::from(value < 0);
+
+    loop {
+        let mut digit = (v & 0b11111) as usize;
+        v >>= 5;
+
+        if v != 0 {
+            digit |= 0b100000;
+        }
+
+        out.push(BASE64[digit] as char);
+
+        if v == 0 {
+            break;
+        }
+    }
+}
+
+/// Decodes one Base64 VLQ value from the front of `input`.
+///
+/// A value outside the range of `i64` fails with `FromLeb128Error::Overflow`.
+pub fn decode_vlq(input: &mut &[u8]) -> Result<i64, FromLeb128Error> {
+    let mut result = 0u128;
+    let mut shift = 0;
+
+    loop {
+        let (&c, rest) = input.split_first().ok_or(FromLeb128Error::Malformed)?;
+        *input = rest;
+
+        let digit = BASE64
+            .iter()
+            .position(|&b| b == c)
+            .ok_or(FromLeb128Error::Malformed)? as u128;
+
+        if shift > 60 {
+            return Err(FromLeb128Error::Malformed);
+        }
+
+        result |= (digit & 0b11111) << shift;
+        shift += 5;
+
+        if digit & 0b100000 == 0 {
+            break;
+        }
+    }
+
+    let magnitude = (result >> 1) as i128;
+    let value = if result & 1 != 0 {
+        -magnitude
+    } else {
+        magnitude
+    };
+    i64::try_from(value).map_err(|_| FromLeb128Error::Overflow)
+}
+
+/// A decoded mapping segment with absolute positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub generated_column: u32,
+    pub source: Option<Source>,
+}
+
+/// The original location a segment maps to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Source {
+    pub index: u32,
+    pub line: u32,
+    pub column: u32,
+    pub name: Option<u32>,
+}
+
+/// Parses a source-map `mappings` string into one segment list per generated line.
+pub fn parse_mappings(mappings: &str) -> Result<Vec<Vec<Segment>>, FromLeb128Error> {
+    let mut lines = Vec::new();
+    let mut state = [0i64; 5];
+
+    for line in mappings.split(';') {
+        let mut segments = Vec::new();
+        state[0] = 0;
+
+        for field in line.split(',').filter(|f| !f.is_empty()) {
+            let mut input = field.as_bytes();
+            let mut n = 0;
+
+            while !input.is_empty() {
+                if n == 5 {
+                    return Err(FromLeb128Error::Malformed);
+                }
+                state[n] = state[n]
+                    .checked_add(decode_vlq(&mut input)?)
+                    .ok_or(FromLeb128Error::Overflow)?;
+                n += 1;
+            }
+
+            if n == 2 || n == 3 {
+                return Err(FromLeb128Error::Malformed);
+            }
+
+            let abs = |v: i64| u32::try_from(v).map_err(|_| FromLeb128Error::Overflow);
+            segments.push(Segment {
+                generated_column: abs(state[0])?,
+                source: if n >= 4 {
+                    Some(Source {
+                        index: abs(state[1])?,
+                        line: abs(state[2])?,
+                        column: abs(state[3])?,
+                        name: if n == 5 { Some(abs(state[4])?) } else { None },
+                    })
+                } else {
+                    None
+                },
+            });
+        }
+
+        lines.push(segments);
+    }
+
+    Ok(lines)
+}
+
+/// Emits a `mappings` string; the inverse of [`parse_mappings`].
+pub fn emit_mappings(lines: &[Vec<Segment>]) -> String {
+    let mut out = String::new();
+    let mut state = [0i64; 5];
+
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            out.push(';');
+        }
+        state[0] = 0;
+
+        for (j, segment) in line.iter().enumerate() {
+            if j > 0 {
+                out.push(',');
+            }
+
+            let mut fields = vec![segment.generated_column];
+            if let Some(source) = segment.source {
+                fields.extend([source.index, source.line, source.column]);
+                fields.extend(source.name);
+            }
+
+            for (n, value) in fields.into_iter().enumerate() {
+                encode_vlq(i64::from(value) - state[n], &mut out);
+                state[n] = i64::from(value);
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vlq_round_trip() {
+        for value in [
+            0,
+            1,
+            -1,
+            15,
+            16,
+            -16,
+            1000,
+            -123456,
+            i32::MAX as i64,
+            i64::MAX,
+            i64::MIN,
+        ] {
+            let mut s = String::new();
+            encode_vlq(value, &mut s);
+            assert_eq!(decode_vlq(&mut s.as_bytes()).unwrap(), value);
+        }
+
+        let mut s = String::new();
+        encode_vlq(16, &mut s);
+        assert_eq!(s, "gB");
+    }
+
+    #[test]
+    fn mappings_round_trip() {
+        let mappings = "AAAA,SAASA;;IACIC,GAAG;EACA";
+        let lines = parse_mappings(mappings).unwrap();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].is_empty());
+        assert_eq!(
+            lines[0][1],
+            Segment {
+                generated_column: 9,
+                source: Some(Source {
+                    index: 0,
+                    line: 0,
+                    column: 9,
+                    name: Some(0),
+                }),
+            }
+        );
+        assert_eq!(lines[2][0].source.unwrap().line, 1);
+        assert_eq!(emit_mappings(&lines), mappings);
+
+        assert!(parse_mappings("AA").is_err());
+        assert!(parse_mappings("D").is_err());
+    }
+
+    #[test]
+    fn out_of_range() {
+        let mut mappings = String::new();
+        encode_vlq(4_000_000_000, &mut mappings);
+        mappings.push(',');
+        encode_vlq(i64::MAX, &mut mappings);
+        assert!(matches!(
+            parse_mappings(&mappings),
+            Err(FromLeb128Error::Overflow)
+        ));
+
+        // One past i64::MAX.
+        assert!(matches!(
+            decode_vlq(&mut &b"ggggggggggggQ"[..]),
+            Err(FromLeb128Error::Overflow)
+        ));
+    }
+}
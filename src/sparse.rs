@@ -0,0 +1,197 @@
+use std::io::{self, Read, Write};
+
+use crate::{FromLeb128Error, FromLeb128i, FromLeb128u, ToLeb128i, ToLeb128u};
+
+/// A sparse vector stored as sorted indices and their values.
+///
+/// Encoded as the entry count, the indices as ULEB128 deltas (the first one
+/// absolute), then the values as ULEB128, or SLEB128 with the `_signed`
+/// methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseVector<T> {
+    /// Strictly increasing positions of the stored entries.
+    pub indices: Vec<u64>,
+    pub values: Vec<T>,
+}
+
+impl<T> SparseVector<T> {
+    pub fn new() -> Self {
+        Self {
+            indices: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Returns the value stored at `index`, if any.
+    pub fn get(&self, index: u64) -> Option<&T> {
+        let i = self.indices.binary_search(&index).ok()?;
+        self.values.get(i)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &T)> {
+        self.indices.iter().copied().zip(&self.values)
+    }
+
+    fn encode_with(
+        &self,
+        writer: &mut impl Write,
+        encode: impl Fn(&T, &mut Vec<u8>) -> io::Result<usize>,
+    ) -> io::Result<usize> {
+        if self.indices.len() != self.values.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "indices and values differ in length",
+            ));
+        }
+
+        let mut buf = Vec::with_capacity(self.indices.len() * 2 + 10);
+        (self.indices.len() as u64).to_leb128u(&mut buf)?;
+
+        let mut prev = None;
+        for &index in &self.indices {
+            let delta = match prev {
+                None => index,
+                Some(p) if index > p => index - p,
+                Some(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "indices are not strictly increasing",
+                    ))
+                }
+            };
+            delta.to_leb128u(&mut buf)?;
+            prev = Some(index);
+        }
+
+        for value in &self.values {
+            encode(value, &mut buf)?;
+        }
+
+        writer.write_all(&buf)?;
+        Ok(buf.len())
+    }
+
+    fn decode_with<R: Read>(
+        reader: &mut R,
+        decode: impl Fn(&mut R) -> Result<T, FromLeb128Error>,
+    ) -> Result<Self, FromLeb128Error> {
+        let count = u64::from_leb128u(reader)?;
+        let capacity = count.min(4096) as usize;
+        let mut indices = Vec::with_capacity(capacity);
+        let mut values = Vec::with_capacity(capacity);
+
+        let mut prev: Option<u64> = None;
+        for _ in 0..count {
+            let delta = u64::from_leb128u(reader)?;
+            let index = match prev {
+                None => delta,
+                Some(_) if delta == 0 => return Err(FromLeb128Error::Malformed),
+                Some(p) => p.checked_add(delta).ok_or(FromLeb128Error::Overflow)?,
+            };
+            indices.push(index);
+            prev = Some(index);
+        }
+
+        for _ in 0..count {
+            values.push(decode(reader)?);
+        }
+
+        Ok(Self { indices, values })
+    }
+}
+
+impl<T> Default for SparseVector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ToLeb128u> SparseVector<T> {
+    /// Fails with `io::ErrorKind::InvalidInput` if the indices are not strictly
+    /// increasing or do not match the number of values.
+    pub fn encode(&self, writer: &mut impl Write) -> io::Result<usize> {
+        self.encode_with(writer, |value, buf| value.to_leb128u(buf))
+    }
+}
+
+impl<T: ToLeb128i> SparseVector<T> {
+    /// Like [`encode`](Self::encode), with the values as SLEB128.
+    pub fn encode_signed(&self, writer: &mut impl Write) -> io::Result<usize> {
+        self.encode_with(writer, |value, buf| value.to_leb128i(buf))
+    }
+}
+
+impl<T: FromLeb128u> SparseVector<T> {
+    pub fn decode(reader: &mut impl Read) -> Result<Self, FromLeb128Error> {
+        Self::decode_with(reader, |r| T::from_leb128u(r))
+    }
+}
+
+impl<T: FromLeb128i> SparseVector<T> {
+    /// Decodes a vector written by [`encode_signed`](Self::encode_signed).
+    pub fn decode_signed(reader: &mut impl Read) -> Result<Self, FromLeb128Error> {
+        Self::decode_with(reader, |r| T::from_leb128i(r))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let v = SparseVector {
+            indices: vec![3, 4, 1000, u64::MAX],
+            values: vec![1u32, 2, 300, 4],
+        };
+        let mut buf = Vec::new();
+        let len = v.encode(&mut buf).unwrap();
+
+        assert_eq!(len, buf.len());
+        assert_eq!(&buf[..4], &[4, 3, 1, 0xe4]);
+        let decoded = SparseVector::<u32>::decode(&mut &buf[..]).unwrap();
+        assert_eq!(decoded, v);
+        assert_eq!(decoded.get(1000), Some(&300));
+        assert_eq!(decoded.get(5), None);
+    }
+
+    #[test]
+    fn signed_values() {
+        struct NoDefault;
+        assert!(SparseVector::<NoDefault>::default().is_empty());
+
+        let v = SparseVector {
+            indices: vec![0, 7],
+            values: vec![-1i64, i64::MIN],
+        };
+        let mut buf = Vec::new();
+        v.encode_signed(&mut buf).unwrap();
+
+        assert_eq!(&buf[..4], &[2, 0, 7, 0x7f]);
+        assert_eq!(
+            SparseVector::<i64>::decode_signed(&mut &buf[..]).unwrap(),
+            v
+        );
+    }
+
+    #[test]
+    fn rejects_unsorted() {
+        let v = SparseVector {
+            indices: vec![4, 4],
+            values: vec![1u8, 2],
+        };
+        assert!(v.encode(&mut Vec::new()).is_err());
+        assert!(matches!(
+            SparseVector::<u8>::decode(&mut &[2, 4, 0, 1, 2][..]),
+            Err(FromLeb128Error::Malformed)
+        ));
+    }
+}
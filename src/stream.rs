@@ -0,0 +1,164 @@
+use std::io::{self, Chain, Read};
+
+use crate::{FromLeb128Error, FromLeb128u};
+
+/// Reads exactly one byte.
+pub(crate) fn read_byte(reader: &mut (impl Read + ?Sized)) -> io::Result<u8> {
+    let mut buf = [0];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+/// Reads one byte, or returns `None` at the end of input.
+fn try_read_byte(reader: &mut (impl Read + ?Sized)) -> io::Result<Option<u8>> {
+    let mut buf = [0];
+    Ok((reader.read(&mut buf)? == 1).then_some(buf[0]))
+}
+
+/// Runs `decode` on the next value, or returns `None` on a clean end of input.
+///
+/// The first byte is read separately so that an end of input before a value
+/// starts can be told apart from one in the middle of a value.
+pub(crate) fn read_next<'a, R: Read + ?Sized, T>(
+    reader: &'a mut R,
+    decode: impl FnOnce(&mut Chain<&[u8], &'a mut R>) -> Result<T, FromLeb128Error>,
+) -> Result<Option<T>, FromLeb128Error> {
+    let Some(byte) = try_read_byte(reader)? else {
+        return Ok(None);
+    };
+
+    decode(&mut (&[byte][..]).chain(reader)).map(Some)
+}
+
+pub(crate) fn read_u<T: FromLeb128u>(reader: &mut impl Read) -> Result<Option<T>, FromLeb128Error> {
+    read_next(reader, |r| T::from_leb128u(r))
+}
+
+/// Reader adapter that counts the bytes read through it.
+#[derive(Debug)]
+pub(crate) struct Counting<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R> Counting<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self { inner, count: 0 }
+    }
+
+    pub(crate) fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl<R: Read> Read for Counting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
+/// Whether an unsigned encoding ending in `tail` ends in a redundant zero
+/// byte.
+pub(crate) fn padded_u(tail: &[u8]) -> bool {
+    matches!(tail, [_, .., 0x00])
+}
+
+/// Whether a signed encoding ending in `tail` ends in a byte that only
+/// repeats the sign bit of the byte before it.
+pub(crate) fn padded_i(tail: &[u8]) -> bool {
+    match tail {
+        [.., prev, 0x00] => prev & 0b01000000 == 0,
+        [.., prev, 0x7f] => prev & 0b01000000 != 0,
+        _ => false,
+    }
+}
+
+/// Reader adapter that remembers the last two bytes read through it.
+#[derive(Debug)]
+pub(crate) struct Tail<R> {
+    inner: R,
+    count: u64,
+    last: [u8; 2],
+}
+
+impl<R> Tail<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self {
+            inner,
+            count: 0,
+            last: [0; 2],
+        }
+    }
+
+    /// The last two bytes read, or fewer if fewer were read.
+    pub(crate) fn tail(&self) -> &[u8] {
+        &self.last[2 - self.count.min(2) as usize..]
+    }
+}
+
+impl<R: Read> Read for Tail<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+
+        for &b in &buf[..n] {
+            self.last = [self.last[1], b];
+        }
+        self.count += n as u64;
+
+        Ok(n)
+    }
+}
+
+/// Reader adapter that ends the input after `limit` bytes and remembers
+/// whether more were asked for.
+#[derive(Debug)]
+pub(crate) struct Limit<R> {
+    inner: R,
+    limit: usize,
+    exceeded: bool,
+}
+
+impl<R> Limit<R> {
+    pub(crate) fn new(inner: R, limit: usize) -> Self {
+        Self {
+            inner,
+            limit,
+            exceeded: false,
+        }
+    }
+
+    pub(crate) fn exceeded(&self) -> bool {
+        self.exceeded
+    }
+}
+
+impl<R: Read> Read for Limit<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.limit == 0 && !buf.is_empty() {
+            self.exceeded = true;
+            return Ok(0);
+        }
+
+        let len = buf.len().min(self.limit);
+        let n = self.inner.read(&mut buf[..len])?;
+        self.limit -= n;
+        Ok(n)
+    }
+}
+
+/// Decodes a `u64` from the front of `bytes` without consuming it.
+///
+/// Returns the value and its encoded length, or `None` if `bytes` ends in the
+/// middle of the value.
+#[cfg(any(feature = "asynchronous-codec", feature = "tokio-util"))]
+pub(crate) fn peek_u64(bytes: &[u8]) -> Result<Option<(u64, usize)>, FromLeb128Error> {
+    if bytes.len() < 10 && bytes.iter().all(|b| b & 0b10000000 != 0) {
+        return Ok(None);
+    }
+
+    let mut reader = bytes;
+    let value = u64::from_leb128u(&mut reader)?;
+    Ok(Some((value, bytes.len() - reader.len())))
+}
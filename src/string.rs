@@ -0,0 +1,146 @@
+use std::io::{self, Read, Write};
+
+use crate::{FromLeb128Error, FromLeb128u, ToLeb128u};
+
+// Upper bound on the up-front reservation for a length read from the input,
+// so that a corrupt length cannot trigger a huge allocation on its own.
+const MAX_RESERVE: u64 = 1 << 16;
+
+/// Writes `bytes` prefixed with its length as ULEB128.
+pub fn write_leb128_bytes(writer: &mut impl Write, bytes: &[u8]) -> io::Result<usize> {
+    let count = (bytes.len() as u64).to_leb128u(writer)?;
+    writer.write_all(bytes)?;
+    Ok(count + bytes.len())
+}
+
+/// Writes `s` as length-prefixed UTF-8.
+pub fn write_leb128_str(writer: &mut impl Write, s: &str) -> io::Result<usize> {
+    write_leb128_bytes(writer, s.as_bytes())
+}
+
+pub fn read_leb128_bytes(reader: &mut impl Read) -> Result<Vec<u8>, FromLeb128Error> {
+    let mut buf = Vec::new();
+    read_leb128_bytes_into(reader, &mut buf)?;
+    Ok(buf)
+}
+
+pub fn read_leb128_str(reader: &mut impl Read) -> Result<String, FromLeb128Error> {
+    let mut buf = String::new();
+    read_leb128_str_into(reader, &mut buf)?;
+    Ok(buf)
+}
+
+/// Reads length-prefixed bytes into `buf`, replacing its contents but reusing
+/// its allocation.
+pub fn read_leb128_bytes_into(
+    reader: &mut impl Read,
+    buf: &mut Vec<u8>,
+) -> Result<(), FromLeb128Error> {
+    let len = u64::from_leb128u(reader)?;
+    read_exact_into(reader, len, buf)
+}
+
+/// Replaces the contents of `buf` with exactly `len` bytes from `reader`.
+pub(crate) fn read_exact_into(
+    reader: &mut impl Read,
+    len: u64,
+    buf: &mut Vec<u8>,
+) -> Result<(), FromLeb128Error> {
+    buf.clear();
+    let mut remaining = len;
+
+    // Grow in bounded steps with `try_reserve`, so that a large length fails
+    // with `Alloc` instead of aborting and a short input never over-allocates.
+    while remaining > 0 {
+        let chunk = remaining.min(MAX_RESERVE) as usize;
+        buf.try_reserve(chunk)?;
+
+        let start = buf.len();
+        buf.resize(start + chunk, 0);
+        if let Err(e) = reader.read_exact(&mut buf[start..]) {
+            buf.clear();
+            return Err(e.into());
+        }
+
+        remaining -= chunk as u64;
+    }
+
+    Ok(())
+}
+
+/// Reads a length-prefixed UTF-8 string into `buf`, replacing its contents but
+/// reusing its allocation.
+///
+/// Invalid UTF-8 is reported as `Malformed` and leaves `buf` empty.
+pub fn read_leb128_str_into(
+    reader: &mut impl Read,
+    buf: &mut String,
+) -> Result<(), FromLeb128Error> {
+    let mut bytes = std::mem::take(buf).into_bytes();
+    let result = read_leb128_bytes_into(reader, &mut bytes);
+
+    match String::from_utf8(bytes) {
+        Ok(s) => {
+            *buf = s;
+            result
+        }
+        Err(e) => {
+            let mut bytes = e.into_bytes();
+            bytes.clear();
+            *buf = String::from_utf8(bytes).unwrap();
+            result.and(Err(FromLeb128Error::Malformed))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_buffer() {
+        let mut input = Vec::new();
+        write_leb128_str(&mut input, "hello").unwrap();
+        write_leb128_str(&mut input, "").unwrap();
+        write_leb128_bytes(&mut input, &[0xff; 200]).unwrap();
+
+        let mut reader = &input[..];
+        let mut s = String::with_capacity(64);
+        read_leb128_str_into(&mut reader, &mut s).unwrap();
+        assert_eq!(s, "hello");
+        read_leb128_str_into(&mut reader, &mut s).unwrap();
+        assert_eq!(s, "");
+        assert!(s.capacity() >= 64);
+
+        assert!(matches!(
+            read_leb128_str_into(&mut reader, &mut s),
+            Err(FromLeb128Error::Malformed)
+        ));
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn truncated() {
+        assert!(matches!(
+            read_leb128_bytes(&mut &[0x03, 1, 2][..]),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn huge_length() {
+        let mut buf = Vec::new();
+        let input = [
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01, 1,
+        ];
+        assert!(matches!(
+            read_leb128_bytes_into(&mut &input[..], &mut buf),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+        assert!(buf.is_empty());
+        assert!(buf.capacity() <= 2 * MAX_RESERVE as usize);
+
+        let e = Vec::<u8>::new().try_reserve(usize::MAX).unwrap_err();
+        assert!(matches!(FromLeb128Error::from(e), FromLeb128Error::Alloc));
+    }
+}
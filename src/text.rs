@@ -0,0 +1,141 @@
+//! Conversion between decimal text and varint streams.
+
+use std::fmt::Display;
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+use crate::format::VarintFormat;
+use crate::FromLeb128Error;
+
+// Longest token accepted; `i64::MIN` is 20 characters, so anything longer can
+// only be garbage and is rejected without buffering it.
+const MAX_TOKEN: usize = 40;
+
+/// Parses whitespace- or comma-separated decimal integers from `reader` and
+/// writes each one in format `F`.
+///
+/// Input is read in fixed-size chunks, so memory use does not depend on the
+/// input size. Returns the number of values written; a token that does not
+/// parse as `F::Value` fails with `FromLeb128Error::Malformed`.
+pub fn text_to_varints<F>(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+) -> Result<u64, FromLeb128Error>
+where
+    F: VarintFormat,
+    F::Value: FromStr,
+{
+    let mut chunk = [0; 4096];
+    let mut token = [0; MAX_TOKEN];
+    let mut token_len = 0;
+    let mut count = 0;
+
+    loop {
+        let n = match reader.read(&mut chunk) {
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        for &byte in &chunk[..n] {
+            if byte.is_ascii_whitespace() || byte == b',' {
+                if token_len > 0 {
+                    write_token::<F>(&token[..token_len], writer)?;
+                    token_len = 0;
+                    count += 1;
+                }
+            } else if token_len < MAX_TOKEN {
+                token[token_len] = byte;
+                token_len += 1;
+            } else {
+                return Err(FromLeb128Error::Malformed);
+            }
+        }
+
+        if n == 0 {
+            break;
+        }
+    }
+
+    if token_len > 0 {
+        write_token::<F>(&token[..token_len], writer)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Like [`text_to_varints`], for text already in memory.
+pub fn str_to_varints<F>(text: &str, writer: &mut impl Write) -> Result<u64, FromLeb128Error>
+where
+    F: VarintFormat,
+    F::Value: FromStr,
+{
+    text_to_varints::<F>(&mut text.as_bytes(), writer)
+}
+
+fn write_token<F>(token: &[u8], writer: &mut impl Write) -> Result<(), FromLeb128Error>
+where
+    F: VarintFormat,
+    F::Value: FromStr,
+{
+    let value = std::str::from_utf8(token)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or(FromLeb128Error::Malformed)?;
+    F::encode(value, writer)?;
+    Ok(())
+}
+
+/// Decodes every value of `reader` in format `F` and writes it to `writer` as
+/// decimal text, one value per line.
+///
+/// Returns the number of values written.
+pub fn varints_to_text<F>(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+) -> Result<u64, FromLeb128Error>
+where
+    F: VarintFormat,
+    F::Value: Display,
+{
+    let mut count = 0;
+
+    while let Some(value) = F::decode_next(reader)? {
+        writeln!(writer, "{}", value)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::{Sleb128, Uleb128};
+
+    #[test]
+    fn round_trip() {
+        let mut encoded = Vec::new();
+        assert_eq!(
+            str_to_varints::<Sleb128>("1, -2,300\n\t-9223372036854775808 ", &mut encoded).unwrap(),
+            4
+        );
+
+        let mut text = Vec::new();
+        varints_to_text::<Sleb128>(&mut &encoded[..], &mut text).unwrap();
+        assert_eq!(text, b"1\n-2\n300\n-9223372036854775808\n");
+    }
+
+    #[test]
+    fn rejects_bad_tokens() {
+        let mut out = Vec::new();
+        assert!(matches!(
+            str_to_varints::<Uleb128>("1 -2", &mut out),
+            Err(FromLeb128Error::Malformed)
+        ));
+        assert!(str_to_varints::<Uleb128>("12x", &mut out).is_err());
+        assert!(str_to_varints::<Uleb128>(&"1".repeat(100), &mut out).is_err());
+        assert_eq!(str_to_varints::<Uleb128>(" ,, ", &mut out).unwrap(), 0);
+    }
+}
@@ -0,0 +1,169 @@
+//! Varints of the Thrift compact protocol.
+//!
+//! Lengths and sizes are ULEB128 `u32`, and `i16`, `i32` and `i64` are
+//! zigzag-mapped ULEB128. Field headers pack the distance from the previous
+//! field id with the field type into one byte when the distance is 1 to 15,
+//! and otherwise follow the type byte with the id as a zigzag `i16`. List
+//! and set headers pack small sizes the same way.
+//!
+//! Types are the compact protocol's 4-bit type codes, e.g. 5 for `i32` and
+//! 12 for a struct.
+
+use std::io::{self, Read, Write};
+
+use crate::zigzag::Leb128Zigzag;
+use crate::{FromLeb128Error, FromLeb128u, ToLeb128u};
+
+pub fn write_i16(value: i16, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    value.to_leb128_zigzag(writer)
+}
+
+pub fn read_i16(reader: &mut (impl Read + ?Sized)) -> Result<i16, FromLeb128Error> {
+    i16::from_leb128_zigzag(reader)
+}
+
+pub fn write_i32(value: i32, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    value.to_leb128_zigzag(writer)
+}
+
+pub fn read_i32(reader: &mut (impl Read + ?Sized)) -> Result<i32, FromLeb128Error> {
+    i32::from_leb128_zigzag(reader)
+}
+
+pub fn write_i64(value: i64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    value.to_leb128_zigzag(writer)
+}
+
+pub fn read_i64(reader: &mut (impl Read + ?Sized)) -> Result<i64, FromLeb128Error> {
+    i64::from_leb128_zigzag(reader)
+}
+
+/// Writes a length or size.
+pub fn write_varint32(value: u32, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    value.to_leb128u(writer)
+}
+
+pub fn read_varint32(reader: &mut (impl Read + ?Sized)) -> Result<u32, FromLeb128Error> {
+    u32::from_leb128u(reader)
+}
+
+fn read_byte(reader: &mut (impl Read + ?Sized)) -> Result<u8, FromLeb128Error> {
+    let mut byte = [0];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+/// Writes the header of field `id` of type `field_type`, given the id of the
+/// previous field in the struct (0 for the first).
+///
+/// # Panics
+///
+/// If `field_type` does not fit in four bits.
+pub fn write_field_header(
+    last_id: i16,
+    id: i16,
+    field_type: u8,
+    writer: &mut (impl Write + ?Sized),
+) -> io::Result<usize> {
+    assert!(field_type < 16, "field type out of range");
+
+    match id.checked_sub(last_id) {
+        Some(delta @ 1..=15) => {
+            writer.write_all(&[(delta as u8) << 4 | field_type])?;
+            Ok(1)
+        }
+        _ => {
+            writer.write_all(&[field_type])?;
+            Ok(1 + write_i16(id, writer)?)
+        }
+    }
+}
+
+/// Writes the stop byte ending a struct's fields.
+pub fn write_field_stop(writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    writer.write_all(&[0])?;
+    Ok(1)
+}
+
+/// Reads a field header and returns the field id and type, or `None` at the
+/// stop byte.
+pub fn read_field_header(
+    reader: &mut (impl Read + ?Sized),
+    last_id: i16,
+) -> Result<Option<(i16, u8)>, FromLeb128Error> {
+    let byte = read_byte(reader)?;
+    let field_type = byte & 0x0f;
+
+    if byte == 0 {
+        return Ok(None);
+    }
+
+    let id = match byte >> 4 {
+        0 => read_i16(reader)?,
+        delta => last_id
+            .checked_add(i16::from(delta))
+            .ok_or(FromLeb128Error::Overflow)?,
+    };
+    Ok(Some((id, field_type)))
+}
+
+/// Writes the header of a list or set of `size` elements of `element_type`.
+///
+/// # Panics
+///
+/// If `element_type` does not fit in four bits.
+pub fn write_list_header(
+    size: u32,
+    element_type: u8,
+    writer: &mut (impl Write + ?Sized),
+) -> io::Result<usize> {
+    assert!(element_type < 16, "element type out of range");
+
+    if size < 15 {
+        writer.write_all(&[(size as u8) << 4 | element_type])?;
+        Ok(1)
+    } else {
+        writer.write_all(&[0xf0 | element_type])?;
+        Ok(1 + write_varint32(size, writer)?)
+    }
+}
+
+/// Reads a list or set header and returns its size and element type.
+pub fn read_list_header(reader: &mut (impl Read + ?Sized)) -> Result<(u32, u8), FromLeb128Error> {
+    let byte = read_byte(reader)?;
+    let size = match byte >> 4 {
+        15 => read_varint32(reader)?,
+        size => u32::from(size),
+    };
+    Ok((size, byte & 0x0f))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_protocol() {
+        let mut buf = Vec::new();
+        write_field_header(0, 1, 5, &mut buf).unwrap();
+        write_i32(-2, &mut buf).unwrap();
+        assert_eq!(write_field_header(1, 300, 6, &mut buf).unwrap(), 3);
+        write_i64(150, &mut buf).unwrap();
+        write_field_header(300, 301, 9, &mut buf).unwrap();
+        write_list_header(20, 8, &mut buf).unwrap();
+        write_field_stop(&mut buf).unwrap();
+        assert_eq!(
+            buf,
+            [0x15, 0x03, 0x06, 0xd8, 0x04, 0xac, 0x02, 0x19, 0xf8, 0x14, 0x00]
+        );
+
+        let mut reader = &buf[..];
+        assert_eq!(read_field_header(&mut reader, 0).unwrap(), Some((1, 5)));
+        assert_eq!(read_i32(&mut reader).unwrap(), -2);
+        assert_eq!(read_field_header(&mut reader, 1).unwrap(), Some((300, 6)));
+        assert_eq!(read_i64(&mut reader).unwrap(), 150);
+        assert_eq!(read_field_header(&mut reader, 300).unwrap(), Some((301, 9)));
+        assert_eq!(read_list_header(&mut reader).unwrap(), (20, 8));
+        assert_eq!(read_field_header(&mut reader, 301).unwrap(), None);
+    }
+}
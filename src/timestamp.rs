@@ -0,0 +1,113 @@
+use std::io::{self, Read, Write};
+
+use crate::{zigzag, FromLeb128Error, FromLeb128u, ToLeb128u};
+
+/// Encodes a block of timestamps as delta-of-deltas.
+///
+/// The block header is the count followed by the first timestamp; each later
+/// timestamp is the zigzag ULEB128 of the change between consecutive deltas.
+pub fn encode_block(timestamps: &[i64], writer: &mut impl Write) -> io::Result<usize> {
+    let mut count = (timestamps.len() as u64).to_leb128u(writer)?;
+    let mut prev = 0i64;
+    let mut prev_delta = 0i64;
+
+    for (i, &t) in timestamps.iter().enumerate() {
+        if i == 0 {
+            count += zigzag::encode_i64(t).to_leb128u(writer)?;
+        } else {
+            let delta = t.wrapping_sub(prev);
+            count += zigzag::encode_i64(delta.wrapping_sub(prev_delta)).to_leb128u(writer)?;
+            prev_delta = delta;
+        }
+        prev = t;
+    }
+
+    Ok(count)
+}
+
+pub fn decode_block(reader: &mut impl Read) -> Result<Vec<i64>, FromLeb128Error> {
+    TimestampIter::new(reader)?.collect()
+}
+
+/// Lazily decodes the timestamps of one block.
+#[derive(Debug)]
+pub struct TimestampIter<R> {
+    reader: R,
+    remaining: u64,
+    started: bool,
+    prev: i64,
+    prev_delta: i64,
+}
+
+impl<R: Read> TimestampIter<R> {
+    /// Reads the block header.
+    pub fn new(mut reader: R) -> Result<Self, FromLeb128Error> {
+        let remaining = u64::from_leb128u(&mut reader)?;
+
+        Ok(Self {
+            reader,
+            remaining,
+            started: false,
+            prev: 0,
+            prev_delta: 0,
+        })
+    }
+
+    /// Number of timestamps not yet yielded.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    fn read_next(&mut self) -> Result<i64, FromLeb128Error> {
+        let value = zigzag::decode_i64(u64::from_leb128u(&mut self.reader)?);
+
+        if self.started {
+            self.prev_delta = self.prev_delta.wrapping_add(value);
+            self.prev = self.prev.wrapping_add(self.prev_delta);
+        } else {
+            self.started = true;
+            self.prev = value;
+        }
+
+        Ok(self.prev)
+    }
+}
+
+impl<R: Read> Iterator for TimestampIter<R> {
+    type Item = Result<i64, FromLeb128Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+        Some(self.read_next())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regular_interval() {
+        let timestamps = (0..100)
+            .map(|i| 1_700_000_000 + i * 60)
+            .collect::<Vec<i64>>();
+        let mut buf = Vec::new();
+        let len = encode_block(&timestamps, &mut buf).unwrap();
+
+        assert_eq!(len, buf.len());
+        assert_eq!(len, 1 + 5 + 1 + 98);
+        assert_eq!(decode_block(&mut &buf[..]).unwrap(), timestamps);
+    }
+
+    #[test]
+    fn extremes() {
+        let timestamps = vec![i64::MAX, i64::MIN, 0, -1, i64::MAX];
+        let mut buf = Vec::new();
+        encode_block(&timestamps, &mut buf).unwrap();
+        assert_eq!(decode_block(&mut &buf[..]).unwrap(), timestamps);
+    }
+}
@@ -0,0 +1,6 @@
+//! Encoding and decoding over the `tokio` `AsyncRead` and `AsyncWrite`
+//! traits.
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+crate::async_io::async_io!();
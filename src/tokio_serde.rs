@@ -0,0 +1,144 @@
+//! A `tokio-serde` format for the LEB128 serde data format, to be stacked on
+//! [`Leb128FrameCodec`] framing.
+
+use std::marker::PhantomData;
+use std::pin::Pin;
+
+use bytes::{Bytes, BytesMut};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+use crate::buf;
+use crate::codec::Leb128FrameCodec;
+use crate::serde::{from_slice, to_vec, Error};
+use crate::FromLeb128Error;
+
+/// [`Leb128FrameCodec`] handing out frames as the `BytesMut` that
+/// `tokio-serde` expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SerdeFrameCodec(pub Leb128FrameCodec);
+
+impl Encoder<Bytes> for SerdeFrameCodec {
+    type Error = FromLeb128Error;
+
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> Result<(), FromLeb128Error> {
+        self.0.encode(item, dst)
+    }
+}
+
+impl Decoder for SerdeFrameCodec {
+    type Item = BytesMut;
+    type Error = FromLeb128Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<BytesMut>, FromLeb128Error> {
+        buf::split_frame(src, self.0.max_frame_len())
+    }
+}
+
+/// Serializes `SinkItem`s and deserializes `Item`s, one per frame, with
+/// [`crate::serde`].
+#[derive(Debug)]
+pub struct Leb128Serde<Item, SinkItem = Item> {
+    marker: PhantomData<fn(SinkItem) -> Item>,
+}
+
+impl<Item, SinkItem> Leb128Serde<Item, SinkItem> {
+    pub fn new() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Item, SinkItem> Default for Leb128Serde<Item, SinkItem> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Item: DeserializeOwned, SinkItem> ::tokio_serde::Deserializer<Item>
+    for Leb128Serde<Item, SinkItem>
+{
+    type Error = Error;
+
+    /// Fails with `FromLeb128Error::TrailingBytes` if the frame holds more
+    /// than one value.
+    fn deserialize(self: Pin<&mut Self>, src: &BytesMut) -> Result<Item, Error> {
+        from_slice(src)
+    }
+}
+
+impl<Item, SinkItem: Serialize> ::tokio_serde::Serializer<SinkItem>
+    for Leb128Serde<Item, SinkItem>
+{
+    type Error = Error;
+
+    fn serialize(self: Pin<&mut Self>, item: &SinkItem) -> Result<Bytes, Error> {
+        to_vec(item).map(Bytes::from)
+    }
+}
+
+/// A typed stream and sink of messages over `io`, as returned by [`framed`].
+pub type Leb128Framed<T, Item, SinkItem = Item> =
+    ::tokio_serde::Framed<Framed<T, SerdeFrameCodec>, Item, SinkItem, Leb128Serde<Item, SinkItem>>;
+
+/// Frames `io` with `codec` and carries one serialized message per frame.
+pub fn framed<T, Item, SinkItem>(io: T, codec: Leb128FrameCodec) -> Leb128Framed<T, Item, SinkItem>
+where
+    T: AsyncRead + AsyncWrite,
+{
+    ::tokio_serde::Framed::new(Framed::new(io, SerdeFrameCodec(codec)), Leb128Serde::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ready;
+    use futures_util::{SinkExt, StreamExt};
+
+    #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+    enum Msg {
+        Ping(u64),
+        Say { from: String, delta: i32 },
+    }
+
+    #[test]
+    fn typed_round_trip() {
+        let (a, b) = tokio::io::duplex(1024);
+        let mut tx = framed::<_, Msg, Msg>(a, Leb128FrameCodec::default());
+        let mut rx = framed::<_, Msg, Msg>(b, Leb128FrameCodec::default());
+
+        let messages = [
+            Msg::Ping(300),
+            Msg::Say {
+                from: "leb".into(),
+                delta: -65,
+            },
+        ];
+        for msg in &messages {
+            ready(tx.feed(msg.clone())).unwrap();
+        }
+        ready(tx.flush()).unwrap();
+
+        for msg in messages {
+            assert_eq!(ready(rx.next()).unwrap().unwrap(), msg);
+        }
+
+        drop(tx);
+        assert!(ready(rx.next()).is_none());
+    }
+
+    #[test]
+    fn malformed_frame() {
+        let (mut a, b) = tokio::io::duplex(1024);
+        let mut rx = framed::<_, Msg, Msg>(b, Leb128FrameCodec::default());
+
+        ready(tokio::io::AsyncWriteExt::write_all(&mut a, &[1, 9])).unwrap();
+        assert!(matches!(
+            ready(rx.next()).unwrap(),
+            Err(FromLeb128Error::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+use std::io::{Read, Write};
+
+use crate::format::{Uleb128, VarintFormat};
+use crate::progress::{self, Progress, ProgressReporter};
+use crate::stream::Counting;
+use crate::FromLeb128Error;
+
+/// Re-encodes every value of `reader` from format `F` to format `T`.
+///
+/// Values are converted one at a time, so memory use does not depend on the
+/// input size. Returns the number of values transcoded; a value that does not
+/// fit the target value type, or that the target format cannot represent,
+/// fails with `FromLeb128Error::Overflow`.
+pub fn transcode<F, T>(
+    reader: &mut (impl Read + ?Sized),
+    writer: &mut (impl Write + ?Sized),
+) -> Result<u64, FromLeb128Error>
+where
+    F: VarintFormat,
+    T: VarintFormat,
+    T::Value: TryFrom<F::Value>,
+{
+    transcode_with_progress::<F, T>(reader, writer, &mut progress::silent())
+}
+
+/// Like [`transcode`], reporting progress in input bytes and values.
+pub fn transcode_with_progress<F, T>(
+    reader: &mut (impl Read + ?Sized),
+    writer: &mut (impl Write + ?Sized),
+    progress: &mut ProgressReporter<impl FnMut(Progress)>,
+) -> Result<u64, FromLeb128Error>
+where
+    F: VarintFormat,
+    T: VarintFormat,
+    T::Value: TryFrom<F::Value>,
+{
+    let mut reader = Counting::new(reader);
+    let mut count = 0;
+
+    while let Some(value) = F::decode_next(&mut reader)? {
+        let value = match T::Value::try_from(value) {
+            Ok(value) if T::encoded_len(value).is_some() => value,
+            _ => return Err(FromLeb128Error::Overflow),
+        };
+        T::encode(value, writer)?;
+        count += 1;
+        progress.value_at(reader.count());
+    }
+
+    progress.finish(reader.count());
+    Ok(count)
+}
+
+/// Rewrites every ULEB128 `u64` of `reader` in its minimal encoding, dropping
+/// any padding bytes.
+///
+/// Returns the number of values written.
+pub fn canonicalize(
+    reader: &mut (impl Read + ?Sized),
+    writer: &mut (impl Write + ?Sized),
+) -> Result<u64, FromLeb128Error> {
+    canonicalize_with_progress(reader, writer, &mut progress::silent())
+}
+
+/// Like [`canonicalize`], reporting progress in input bytes and values.
+pub fn canonicalize_with_progress(
+    reader: &mut (impl Read + ?Sized),
+    writer: &mut (impl Write + ?Sized),
+    progress: &mut ProgressReporter<impl FnMut(Progress)>,
+) -> Result<u64, FromLeb128Error> {
+    transcode_with_progress::<Uleb128, Uleb128>(reader, writer, progress)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::{Quic, Sleb128, UnsignedVarint, Zigzag};
+    use crate::{quic, ToLeb128i, ToLeb128u};
+
+    #[test]
+    fn signed_to_zigzag_and_back() {
+        let mut signed = Vec::new();
+        for v in [0i64, -1, 1, -64, 300, i64::MIN, i64::MAX] {
+            v.to_leb128i(&mut signed).unwrap();
+        }
+
+        let mut zz = Vec::new();
+        assert_eq!(
+            transcode::<Sleb128, Zigzag>(&mut &signed[..], &mut zz).unwrap(),
+            7
+        );
+        assert_eq!(&zz[..3], &[0x00, 0x01, 0x02]);
+
+        let mut back = Vec::new();
+        transcode::<Zigzag, Sleb128>(&mut &zz[..], &mut back).unwrap();
+        assert_eq!(back, signed);
+    }
+
+    #[test]
+    fn quic_to_uleb128() {
+        // RFC 9000, appendix A.1.
+        let input = [0x25, 0x7b, 0xbd, 0x9d, 0x7f, 0x3e, 0x7d];
+        let mut out = Vec::new();
+        let writer: &mut dyn Write = &mut out;
+        let reader: &mut dyn Read = &mut &input[..];
+
+        assert_eq!(transcode::<Quic, Uleb128>(reader, writer).unwrap(), 3);
+        assert_eq!(out, [0x25, 0xbd, 0x77, 0xfd, 0xfc, 0xfc, 0xeb, 0x01]);
+    }
+
+    #[test]
+    fn reports_progress() {
+        let input = [0x01; 10];
+        let mut reports = Vec::new();
+        let mut progress = ProgressReporter::new(4, |p: Progress| reports.push(p.bytes));
+
+        transcode_with_progress::<Uleb128, Zigzag>(&mut &input[..], &mut Vec::new(), &mut progress)
+            .unwrap();
+        assert_eq!(progress.progress().values, 10);
+        assert_eq!(reports, vec![4, 8, 10]);
+    }
+
+    #[test]
+    fn canonicalize_drops_padding() {
+        let input = [0x81, 0x80, 0x00, 0x7f, 0x80, 0x00];
+        let mut out = Vec::new();
+        let mut reports = Vec::new();
+        let mut progress = ProgressReporter::new(4, |p: Progress| reports.push(p.bytes));
+
+        assert_eq!(
+            canonicalize_with_progress(&mut &input[..], &mut out, &mut progress).unwrap(),
+            3
+        );
+        assert_eq!(out, [0x01, 0x7f, 0x00]);
+        assert_eq!(reports, [4, 6]);
+    }
+
+    #[test]
+    fn out_of_range_and_truncated() {
+        let mut out = Vec::new();
+        assert!(matches!(
+            transcode::<Sleb128, Uleb128>(&mut &[0x7f][..], &mut out),
+            Err(FromLeb128Error::Overflow)
+        ));
+        assert!(matches!(
+            transcode::<Uleb128, Sleb128>(&mut &[0x80][..], &mut out),
+            Err(FromLeb128Error::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn unrepresentable_in_target() {
+        let too_big = (quic::MAX_VALUE + 1).to_leb128u_vec();
+        assert!(matches!(
+            transcode::<Uleb128, Quic>(&mut &too_big[..], &mut Vec::new()),
+            Err(FromLeb128Error::Overflow)
+        ));
+
+        let too_big = (1u64 << 63).to_leb128u_vec();
+        assert!(matches!(
+            transcode::<Uleb128, UnsignedVarint>(&mut &too_big[..], &mut Vec::new()),
+            Err(FromLeb128Error::Overflow)
+        ));
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+
+use crate::{FromLeb128Error, FromLeb128u, ToLeb128u};
+
+/// A version vector mapping actor ids to counters.
+///
+/// Encoded as the entry count followed by (actor delta, counter) pairs in
+/// increasing actor order, the first actor absolute. Equal vectors always
+/// encode to the same bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VersionVector {
+    entries: BTreeMap<u64, u64>,
+}
+
+impl VersionVector {
+    pub fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Counter of `actor`, zero if it has none.
+    pub fn get(&self, actor: u64) -> u64 {
+        self.entries.get(&actor).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, actor: u64, counter: u64) {
+        self.entries.insert(actor, counter);
+    }
+
+    /// Increments the counter of `actor` and returns the new value.
+    pub fn increment(&mut self, actor: u64) -> u64 {
+        let counter = self.entries.entry(actor).or_insert(0);
+        *counter = counter.saturating_add(1);
+        *counter
+    }
+
+    /// Raises every counter to the maximum of `self` and `other`.
+    pub fn merge(&mut self, other: &VersionVector) {
+        for (&actor, &counter) in &other.entries {
+            let entry = self.entries.entry(actor).or_insert(0);
+            *entry = (*entry).max(counter);
+        }
+    }
+
+    /// Whether every counter in `self` is at most the one in `other`.
+    pub fn dominated_by(&self, other: &VersionVector) -> bool {
+        self.entries
+            .iter()
+            .all(|(&actor, &counter)| counter <= other.get(actor))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates (actor, counter) pairs in increasing actor order.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.entries.iter().map(|(&a, &c)| (a, c))
+    }
+
+    pub fn encode(&self, writer: &mut impl Write) -> io::Result<usize> {
+        let mut buf = Vec::with_capacity(self.entries.len() * 4 + 10);
+        (self.entries.len() as u64).to_leb128u(&mut buf)?;
+
+        let mut prev = None;
+        for (&actor, &counter) in &self.entries {
+            let delta = match prev {
+                None => actor,
+                Some(p) => actor - p,
+            };
+            delta.to_leb128u(&mut buf)?;
+            counter.to_leb128u(&mut buf)?;
+            prev = Some(actor);
+        }
+
+        writer.write_all(&buf)?;
+        Ok(buf.len())
+    }
+
+    /// Fails with `FromLeb128Error::Malformed` if actors are not strictly
+    /// increasing.
+    pub fn decode(reader: &mut impl Read) -> Result<Self, FromLeb128Error> {
+        let count = u64::from_leb128u(reader)?;
+        let mut entries = BTreeMap::new();
+
+        let mut prev: Option<u64> = None;
+        for _ in 0..count {
+            let delta = u64::from_leb128u(reader)?;
+            let actor = match prev {
+                None => delta,
+                Some(_) if delta == 0 => return Err(FromLeb128Error::Malformed),
+                Some(p) => p.checked_add(delta).ok_or(FromLeb128Error::Overflow)?,
+            };
+            entries.insert(actor, u64::from_leb128u(reader)?);
+            prev = Some(actor);
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+impl FromIterator<(u64, u64)> for VersionVector {
+    fn from_iter<I: IntoIterator<Item = (u64, u64)>>(iter: I) -> Self {
+        Self {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_and_round_trip() {
+        let mut a: VersionVector = [(7, 1), (2, 5)].into_iter().collect();
+        let b: VersionVector = [(2, 3), (300, 9)].into_iter().collect();
+        assert!(!b.dominated_by(&a));
+
+        a.merge(&b);
+        assert_eq!(a.increment(7), 2);
+        assert_eq!(a.iter().collect::<Vec<_>>(), vec![(2, 5), (7, 2), (300, 9)]);
+        assert!(b.dominated_by(&a));
+
+        let mut buf = Vec::new();
+        a.encode(&mut buf).unwrap();
+        assert_eq!(buf, vec![3, 2, 5, 5, 2, 0xa5, 0x02, 9]);
+        assert_eq!(VersionVector::decode(&mut &buf[..]).unwrap(), a);
+
+        assert!(matches!(
+            VersionVector::decode(&mut &[2, 1, 1, 0, 1][..]),
+            Err(FromLeb128Error::Malformed)
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+//! Parsers for the `winnow` combinator library.
+//!
+//! The parsers work on any byte stream, e.g. `leb128_u64.parse_next(&mut
+//! input)`. On a `Partial` stream, running out of bytes mid-value is
+//! `ErrMode::Incomplete` so the caller can buffer more and retry; on complete
+//! input it is an error like any other. Failures are reported through
+//! `FromExternalError` with the original [`FromLeb128Error`].
+
+use winnow::error::{FromExternalError, Needed, ParserError};
+use winnow::stream::{AsBytes, Stream, StreamIsPartial};
+
+use crate::push::DecodeFn;
+use crate::{FromLeb128Error, FromLeb128i, FromLeb128u};
+
+fn parse<I, T, E>(input: &mut I, decode: DecodeFn<T>) -> Result<T, E>
+where
+    I: StreamIsPartial + Stream + Clone,
+    I::Slice: AsBytes,
+    E: ParserError<I> + FromExternalError<I, FromLeb128Error>,
+{
+    match decode(input.peek_finish().as_bytes()) {
+        Ok((value, len)) => {
+            input.next_slice(len);
+            Ok(value)
+        }
+        Err(FromLeb128Error::UnexpectedEof) if input.is_partial() => {
+            Err(E::incomplete(input, Needed::Unknown))
+        }
+        Err(e) => Err(E::from_external_error(input, e)),
+    }
+}
+
+/// Parses a ULEB128 value of any [`FromLeb128u`] type.
+pub fn uleb128<I, T, E>(input: &mut I) -> Result<T, E>
+where
+    I: StreamIsPartial + Stream + Clone,
+    I::Slice: AsBytes,
+    T: FromLeb128u,
+    E: ParserError<I> + FromExternalError<I, FromLeb128Error>,
+{
+    parse(input, T::from_leb128u_slice)
+}
+
+/// Parses an SLEB128 value of any [`FromLeb128i`] type.
+pub fn sleb128<I, T, E>(input: &mut I) -> Result<T, E>
+where
+    I: StreamIsPartial + Stream + Clone,
+    I::Slice: AsBytes,
+    T: FromLeb128i,
+    E: ParserError<I> + FromExternalError<I, FromLeb128Error>,
+{
+    parse(input, T::from_leb128i_slice)
+}
+
+macro_rules! parsers {
+    ($generic:ident: $($name:ident => $t:ty),*) => {
+        $(
+            #[doc = concat!("Parses a `", stringify!($t), "`.")]
+            pub fn $name<I, E>(input: &mut I) -> Result<$t, E>
+            where
+                I: StreamIsPartial + Stream + Clone,
+                I::Slice: AsBytes,
+                E: ParserError<I> + FromExternalError<I, FromLeb128Error>,
+            {
+                $generic(input)
+            }
+        )*
+    };
+}
+
+parsers!(uleb128: leb128_u8 => u8, leb128_u16 => u16, leb128_u32 => u32, leb128_u64 => u64, leb128_u128 => u128, leb128_usize => usize);
+parsers!(sleb128: leb128_i8 => i8, leb128_i16 => i16, leb128_i32 => i32, leb128_i64 => i64, leb128_i128 => i128, leb128_isize => isize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winnow::error::{ContextError, ErrMode, ModalResult};
+    use winnow::{Parser, Partial};
+
+    #[test]
+    fn parses_values() {
+        let mut input = &[0xac, 0x02, 0xbf, 0x7f, 0x01, 0xff][..];
+        let pair: ModalResult<_> = (leb128_u32, leb128_i64).parse_next(&mut input);
+        assert_eq!(pair.unwrap(), (300, -65));
+        assert_eq!(leb128_u8::<_, ContextError>.parse_next(&mut input), Ok(1));
+        assert!(leb128_u8::<_, ContextError>.parse_next(&mut input).is_err());
+        assert_eq!(input, [0xff]);
+
+        let mut partial = Partial::new(&[0xac, 0x02, 0x80][..]);
+        let value: ModalResult<u16> = leb128_u16.parse_next(&mut partial);
+        assert_eq!(value, Ok(300));
+        let value: ModalResult<u16> = leb128_u16.parse_next(&mut partial);
+        assert_eq!(value, Err(ErrMode::Incomplete(Needed::Unknown)));
+
+        let overflow = &[0x80, 0x02][..];
+        assert!(leb128_u8::<_, ContextError>.parse(overflow).is_err());
+    }
+}
@@ -0,0 +1,29 @@
+//! Free functions for one-off writes of `u64` and `i64` values, without
+//! importing the traits.
+
+use std::io::{self, Write};
+
+use crate::{ToLeb128i, ToLeb128u};
+
+/// Writes `value` as ULEB128 and returns the number of bytes written.
+pub fn unsigned(value: u64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    value.to_leb128u(writer)
+}
+
+/// Writes `value` as SLEB128 and returns the number of bytes written.
+pub fn signed(value: i64, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+    value.to_leb128i(writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes() {
+        let mut buf = Vec::new();
+        assert_eq!(unsigned(300, &mut buf).unwrap(), 2);
+        assert_eq!(signed(-65, &mut buf).unwrap(), 2);
+        assert_eq!(buf, [0xac, 0x02, 0xbf, 0x7f]);
+    }
+}
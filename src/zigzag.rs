@@ -0,0 +1,116 @@
+//! Zigzag encoding, as used for `sint` fields by protobuf and for all
+//! integers by Avro.
+//!
+//! Zigzag maps signed integers to unsigned ones so that values of small
+//! magnitude stay small: 0, -1, 1, -2, 2, ... become 0, 1, 2, 3, 4, ....
+//! The result is then written as ULEB128. That takes as many bytes as SLEB128
+//! of the same value, but the bytes differ, so the two do not interoperate.
+
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+use core::mem::MaybeUninit;
+
+use crate::{EncodeError, FromLeb128Error, FromLeb128u, ToLeb128u};
+
+pub fn encode_i64(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+pub fn decode_i64(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+pub fn encode_i32(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+pub fn decode_i32(value: u32) -> i32 {
+    ((value >> 1) as i32) ^ -((value & 1) as i32)
+}
+
+/// Zigzag followed by ULEB128, for every signed integer type.
+///
+/// The reader and writer methods have defaults, so the required methods are
+/// the same whichever features are enabled.
+pub trait Leb128Zigzag: Sized {
+    #[cfg(feature = "std")]
+    fn to_leb128_zigzag(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+        let mut buf = [MaybeUninit::uninit(); crate::pending::SCRATCH_LEN];
+        let bytes = self.to_leb128_zigzag_uninit(&mut buf)?;
+        writer.write_all(bytes)?;
+        Ok(bytes.len())
+    }
+
+    /// Encodes into a possibly uninitialized buffer and returns the written prefix.
+    fn to_leb128_zigzag_uninit<'a>(
+        &self,
+        buf: &'a mut [MaybeUninit<u8>],
+    ) -> Result<&'a [u8], EncodeError>;
+
+    #[cfg(feature = "std")]
+    fn from_leb128_zigzag(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+        crate::pending::read_with(reader, Self::from_leb128_zigzag_slice)
+    }
+
+    /// Decodes from the front of `bytes` and returns the value with the number
+    /// of bytes consumed.
+    fn from_leb128_zigzag_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error>;
+}
+
+macro_rules! impl_zigzag {
+    ($($ty:ty => $uty:ty),*) => {
+        $(
+            impl Leb128Zigzag for $ty {
+                #[cfg(feature = "std")]
+                fn to_leb128_zigzag(&self, writer: &mut (impl Write + ?Sized)) -> io::Result<usize> {
+                    let value = ((*self << 1) ^ (*self >> (<$ty>::BITS - 1))) as $uty;
+                    value.to_leb128u(writer)
+                }
+
+                fn to_leb128_zigzag_uninit<'a>(&self, buf: &'a mut [MaybeUninit<u8>]) -> Result<&'a [u8], EncodeError> {
+                    let value = ((*self << 1) ^ (*self >> (<$ty>::BITS - 1))) as $uty;
+                    value.to_leb128u_uninit(buf)
+                }
+
+                #[cfg(feature = "std")]
+                fn from_leb128_zigzag(reader: &mut (impl Read + ?Sized)) -> Result<Self, FromLeb128Error> {
+                    let value = <$uty>::from_leb128u(reader)?;
+                    Ok(((value >> 1) as $ty) ^ -((value & 1) as $ty))
+                }
+
+                fn from_leb128_zigzag_slice(bytes: &[u8]) -> Result<(Self, usize), FromLeb128Error> {
+                    let (value, len) = <$uty>::from_leb128u_slice(bytes)?;
+                    Ok((((value >> 1) as $ty) ^ -((value & 1) as $ty), len))
+                }
+            }
+        )*
+    };
+}
+
+impl_zigzag!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(encode_i64(0), 0);
+        assert_eq!(encode_i64(-1), 1);
+        assert_eq!(encode_i64(1), 2);
+        assert_eq!(encode_i32(i32::MIN), u32::MAX);
+        assert_eq!(decode_i64(u64::MAX), i64::MIN);
+
+        let mut buf = Vec::new();
+        assert_eq!((-1i64).to_leb128_zigzag(&mut buf).unwrap(), 1);
+        assert_eq!((-65i32).to_leb128_zigzag(&mut buf).unwrap(), 2);
+        assert_eq!(i8::MIN.to_leb128_zigzag(&mut buf).unwrap(), 2);
+        assert_eq!(buf, [0x01, 0x81, 0x01, 0xff, 0x01]);
+
+        let mut reader = &buf[..];
+        assert_eq!(i64::from_leb128_zigzag(&mut reader).unwrap(), -1);
+        assert_eq!(i32::from_leb128_zigzag(&mut reader).unwrap(), -65);
+        assert_eq!(i8::from_leb128_zigzag_slice(reader).unwrap(), (i8::MIN, 2));
+    }
+}